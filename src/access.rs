@@ -0,0 +1,359 @@
+// ---------------------------------------------------------------------------
+// Public/private split and access control
+// Each predicate of the card is sorted into a public or a private document
+// according to a `VisibilityPolicy`. Nodes that are only described because a
+// private triple points at them (friends, places, ...) move along with it.
+// Matching Web Access Control (.acl) or Access Control Policy (.acr)
+// resources are generated for every document in the pod.
+// ---------------------------------------------------------------------------
+
+use crate::rdf::{Graph, Namespace, Node, Triple, Uri, RDF_TYPE};
+use crate::timestamps;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::error;
+
+const ACL: &str = "http://www.w3.org/ns/auth/acl#";
+const ACP: &str = "http://www.w3.org/ns/solid/acp#";
+const FOAF: &str = "http://xmlns.com/foaf/0.1/";
+const RDFS: &str = "http://www.w3.org/2000/01/rdf-schema#";
+
+/// Predicates kept out of the public card unless a policy says otherwise.
+const DEFAULT_PRIVATE: &[&str] = &[
+    "http://xmlns.com/foaf/0.1/phone",
+    "http://schema.org/telephone",
+    "http://xmlns.com/foaf/0.1/mbox",
+    "http://schema.org/email",
+    "http://xmlns.com/foaf/0.1/birthday",
+    "http://schema.org/birthDate",
+    "http://xmlns.com/foaf/0.1/knows",
+    "http://schema.org/knows",
+    PRIVATE_LISTS,
+    "http://www.w3.org/2006/vcard/ns#hasMember",
+];
+
+/// Links the friend requests and removed friends (see `map_export`), which
+/// are moved out of the card whatever the policy.
+pub const PRIVATE_LISTS: &str = "https://www.w3.org/ns/activitystreams#streams";
+
+/// Well-known prefixes accepted in policy files.
+const PREFIXES: &[(&str, &str)] = &[
+    ("foaf", FOAF),
+    ("schema", "http://schema.org/"),
+    ("vcard", "http://www.w3.org/2006/vcard/ns#"),
+    ("dcterms", "http://purl.org/dc/terms/"),
+    ("rdfs", RDFS),
+    ("owl", "http://www.w3.org/2002/07/owl#"),
+    ("as", "https://www.w3.org/ns/activitystreams#"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    Public,
+    Private,
+}
+
+#[derive(Clone, Debug)]
+pub struct VisibilityPolicy {
+    private: HashSet<String>,
+}
+
+impl Default for VisibilityPolicy {
+    fn default() -> Self {
+        VisibilityPolicy {
+            private: DEFAULT_PRIVATE.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PolicyFile {
+    #[serde(default)]
+    public: Vec<String>,
+    #[serde(default)]
+    private: Vec<String>,
+}
+
+impl VisibilityPolicy {
+    /// Reads overrides of the default policy from JSON such as
+    /// `{"public": ["foaf:knows"], "private": ["schema:homeLocation"]}`.
+    /// Predicates may be full IRIs or use the common prefixes (foaf, schema, ...).
+    pub fn from_json(contents: &str) -> Result<VisibilityPolicy, Box<dyn error::Error>> {
+        let file: PolicyFile = serde_json::from_str(contents)?;
+        let mut policy = VisibilityPolicy::default();
+        for predicate in file.public {
+            policy.set(&expand(&predicate)?, Visibility::Public);
+        }
+        for predicate in file.private {
+            policy.set(&expand(&predicate)?, Visibility::Private);
+        }
+        Ok(policy)
+    }
+
+    /// A policy that only keeps the private lists out of the card.
+    pub fn private_lists() -> VisibilityPolicy {
+        VisibilityPolicy {
+            private: HashSet::from([PRIVATE_LISTS.to_string()]),
+        }
+    }
+
+    pub fn set(&mut self, predicate: &str, visibility: Visibility) {
+        match visibility {
+            Visibility::Public => self.private.remove(predicate),
+            Visibility::Private => self.private.insert(predicate.to_string()),
+        };
+    }
+
+    pub fn visibility(&self, predicate: &str) -> Visibility {
+        if self.private.contains(predicate) {
+            Visibility::Private
+        } else {
+            Visibility::Public
+        }
+    }
+}
+
+fn expand(name: &str) -> Result<String, Box<dyn error::Error>> {
+    if name.contains("://") {
+        return Ok(name.to_string());
+    }
+    let (prefix, local) = name
+        .split_once(':')
+        .ok_or_else(|| format!("'{}' is neither an IRI nor a prefixed name", name))?;
+    PREFIXES
+        .iter()
+        .find(|(p, _)| *p == prefix)
+        .map(|(_, iri)| format!("{}{}", iri, local))
+        .ok_or_else(|| format!("unknown prefix '{}:' in '{}'", prefix, name).into())
+}
+
+/// Splits `card` into its public part and a private document.
+/// `card_name` and `private_name` are the two documents' file names within
+/// the same container; references from the private document back to the card
+/// (such as `#me`) are rewritten to `<card_name#me>`.
+pub fn split(
+    card: &Graph,
+    policy: &VisibilityPolicy,
+    card_name: &str,
+    private_name: &str,
+) -> (Graph, Graph) {
+    let is_private = |t: &Triple| match &t.predicate {
+        Node::Uri(p) => policy.visibility(p) == Visibility::Private,
+        _ => false,
+    };
+
+    // Nodes described in the card that are only reachable through private
+    // triples. The card and the person it is about (under whatever IRI) stay.
+    let document = Node::Uri(String::new());
+    let mut anchors: Vec<Node> = card
+        .triples()
+        .iter()
+        .filter(|t| t.predicate == Node::Uri(format!("{}primaryTopic", FOAF)))
+        .map(|t| t.object.clone())
+        .collect();
+    anchors.extend([Node::Uri("#me".to_string()), document]);
+    let mut moved: HashSet<Node> = HashSet::new();
+    // Statements about private triples (see `timestamps`) would repeat them
+    for (statement, quoted) in timestamps::statements(card) {
+        if is_private(&quoted) {
+            moved.insert(statement);
+        }
+    }
+    let mut frontier: Vec<Node> = card
+        .triples()
+        .iter()
+        .filter(|t| is_private(t))
+        .map(|t| t.object.clone())
+        .collect();
+    while let Some(node) = frontier.pop() {
+        let referenced_publicly = card
+            .triples()
+            .iter()
+            .any(|t| !is_private(t) && !moved.contains(&t.subject) && t.object == node);
+        if anchors.contains(&node) || referenced_publicly || !moved.insert(node.clone())
+        {
+            continue;
+        }
+        frontier.extend(
+            card.triples()
+                .iter()
+                .filter(|t| t.subject == node)
+                .map(|t| t.object.clone()),
+        );
+    }
+
+    let mut public = Graph::new();
+    let mut private = Graph::new();
+    for ns in card.namespaces() {
+        public.add_namespace(ns);
+        private.add_namespace(ns);
+    }
+    let rebase = |node: &Node| match node {
+        Node::Uri(iri) if iri.is_empty() => Node::Uri(card_name.to_string()),
+        Node::Uri(iri) if iri.starts_with('#') && !moved.contains(node) => {
+            Node::Uri(format!("{}{}", card_name, iri))
+        }
+        other => other.clone(),
+    };
+    for t in card.triples() {
+        if is_private(t) || moved.contains(&t.subject) {
+            private.add_triple(&Triple::new(&rebase(&t.subject), &t.predicate, &rebase(&t.object)));
+        } else {
+            public.add_triple(t);
+        }
+    }
+    // A card that was split before already links to the private document
+    let see_also = Triple::new(
+        &Node::Uri("#me".to_string()),
+        &Node::Uri(format!("{}seeAlso", RDFS)),
+        &Node::Uri(private_name.to_string()),
+    );
+    if !private.triples().is_empty() && !public.triples().contains(&see_also) {
+        public.add_namespace(&Namespace::new("rdfs".to_string(), Uri::new(RDFS.to_string())));
+        public.add_triple(&see_also);
+    }
+    (public, private)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessControl {
+    /// Web Access Control, written to `.acl` resources.
+    Wac,
+    /// Access Control Policy, written to `.acr` resources.
+    Acp,
+}
+
+impl AccessControl {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AccessControl::Wac => ".acl",
+            AccessControl::Acp => ".acr",
+        }
+    }
+}
+
+/// Who besides the owner may access a resource.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PublicAccess {
+    None,
+    Read,
+    Append,
+}
+
+/// Builds the access control resource for the resource at `path` (relative to
+/// the pod root). Containers pass their rules on to their members.
+/// Returns the path of the ACL/ACR resource and its contents.
+pub fn access_resource(
+    kind: AccessControl,
+    path: &str,
+    public: PublicAccess,
+    owner: &str,
+) -> (String, Graph) {
+    let is_container = path.is_empty() || path.ends_with('/');
+    let acl_path = format!("{}{}", path, kind.extension());
+    let target = if is_container {
+        "./".to_string()
+    } else {
+        path.rsplit('/').next().unwrap_or(path).to_string()
+    };
+    let owner = Node::Uri(owner.to_string());
+    let target = Node::Uri(target);
+
+    let mut graph = Graph::new();
+    graph.add_namespace(&Namespace::new("acl".to_string(), Uri::new(ACL.to_string())));
+    graph.add_namespace(&Namespace::new("foaf".to_string(), Uri::new(FOAF.to_string())));
+    let public_mode = match public {
+        PublicAccess::None => None,
+        PublicAccess::Read => Some("Read"),
+        PublicAccess::Append => Some("Append"),
+    };
+    match kind {
+        AccessControl::Wac => wac(&mut graph, &target, &owner, public_mode, is_container),
+        AccessControl::Acp => {
+            graph.add_namespace(&Namespace::new("acp".to_string(), Uri::new(ACP.to_string())));
+            acp(&mut graph, &target, &owner, public_mode, is_container)
+        }
+    }
+    (acl_path, graph)
+}
+
+fn iri(ns: &str, local: &str) -> Node {
+    Node::Uri(format!("{}{}", ns, local))
+}
+
+fn wac(graph: &mut Graph, target: &Node, owner: &Node, public: Option<&str>, container: bool) {
+    let is_a = Node::Uri(RDF_TYPE.to_string());
+    let mut authorization = |id: &str, agent: (&str, &Node), modes: &[&str]| {
+        let auth = Node::Uri(format!("#{}", id));
+        graph.add_triple(&Triple::new(&auth, &is_a, &iri(ACL, "Authorization")));
+        graph.add_triple(&Triple::new(&auth, &iri(ACL, agent.0), agent.1));
+        graph.add_triple(&Triple::new(&auth, &iri(ACL, "accessTo"), target));
+        if container {
+            graph.add_triple(&Triple::new(&auth, &iri(ACL, "default"), target));
+        }
+        for mode in modes {
+            graph.add_triple(&Triple::new(&auth, &iri(ACL, "mode"), &iri(ACL, mode)));
+        }
+    };
+    authorization("owner", ("agent", owner), &["Read", "Write", "Control"]);
+    if let Some(mode) = public {
+        authorization("public", ("agentClass", &iri(FOAF, "Agent")), &[mode]);
+    }
+}
+
+fn acp(graph: &mut Graph, target: &Node, owner: &Node, public: Option<&str>, container: bool) {
+    let is_a = Node::Uri(RDF_TYPE.to_string());
+    let root = Node::Uri("#root".to_string());
+    graph.add_triple(&Triple::new(&root, &is_a, &iri(ACP, "AccessControlResource")));
+    graph.add_triple(&Triple::new(&root, &iri(ACP, "resource"), target));
+    let mut control = |id: &str, agent: &Node, modes: &[&str]| {
+        let access_control = Node::Uri(format!("#{}Access", id));
+        let policy = Node::Uri(format!("#{}Policy", id));
+        let matcher = Node::Uri(format!("#{}Matcher", id));
+        graph.add_triple(&Triple::new(&root, &iri(ACP, "accessControl"), &access_control));
+        if container {
+            graph.add_triple(&Triple::new(&root, &iri(ACP, "memberAccessControl"), &access_control));
+        }
+        graph.add_triple(&Triple::new(&access_control, &is_a, &iri(ACP, "AccessControl")));
+        graph.add_triple(&Triple::new(&access_control, &iri(ACP, "apply"), &policy));
+        graph.add_triple(&Triple::new(&policy, &is_a, &iri(ACP, "Policy")));
+        for mode in modes {
+            graph.add_triple(&Triple::new(&policy, &iri(ACP, "allow"), &iri(ACL, mode)));
+        }
+        graph.add_triple(&Triple::new(&policy, &iri(ACP, "anyOf"), &matcher));
+        graph.add_triple(&Triple::new(&matcher, &is_a, &iri(ACP, "Matcher")));
+        graph.add_triple(&Triple::new(&matcher, &iri(ACP, "agent"), agent));
+    };
+    control("owner", owner, &["Read", "Write", "Control"]);
+    if let Some(mode) = public {
+        control("public", &iri(ACP, "PublicAgent"), &[mode]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, Visibility, VisibilityPolicy};
+
+    #[test]
+    fn default_policy_keeps_contact_details_private() {
+        let policy = VisibilityPolicy::default();
+        assert_eq!(policy.visibility("http://xmlns.com/foaf/0.1/phone"), Visibility::Private);
+        assert_eq!(policy.visibility("http://xmlns.com/foaf/0.1/name"), Visibility::Public);
+    }
+
+    #[test]
+    fn policy_file_overrides_defaults() {
+        let policy = VisibilityPolicy::from_json(
+            r#"{"public": ["foaf:knows"], "private": ["http://schema.org/homeLocation"]}"#,
+        )
+        .unwrap();
+        assert_eq!(policy.visibility("http://xmlns.com/foaf/0.1/knows"), Visibility::Public);
+        assert_eq!(policy.visibility("http://schema.org/homeLocation"), Visibility::Private);
+    }
+
+    #[test]
+    fn expand_rejects_unknown_prefixes() {
+        assert!(expand("nope:thing").is_err());
+        assert_eq!(expand("schema:email").unwrap(), "http://schema.org/email");
+    }
+}
@@ -0,0 +1,589 @@
+// ---------------------------------------------------------------------------
+// Converter
+// The configurable library entry point. `ConvertOptions` holds settings that
+// can be shared between many conversions; `Converter` adds the inputs and a
+// warning sink and returns the graph together with a report, so callers never
+// have to parse hatchling's Turtle output back in.
+// ---------------------------------------------------------------------------
+
+use crate::access::{self, VisibilityPolicy};
+use crate::drift::{self, Unmapped};
+use crate::error::{Error, Input};
+use crate::facebook_parser::{FBAlbum, FBPost, FBPosts, FriendsVersion, ProfileVersion};
+use crate::lint::{self, Finding};
+use crate::photos::{self, Photos};
+use crate::pod::PodResource;
+use crate::posts::{self, PostsOptions};
+use crate::profile_builder::Profile;
+use crate::provenance::{Provenance, Source};
+use crate::rdf::{Graph, Namespace, Node, Triple, Uri, RDF_TYPE};
+use crate::selection::Selection;
+use crate::timestamps;
+use crate::turtle::resolve_iri;
+use crate::webid_map::{self, WebIdMap};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::time::SystemTime;
+
+const FOAF: &str = "http://xmlns.com/foaf/0.1/";
+const SCHEMA: &str = "http://schema.org/";
+
+/// FOAF and schema.org terms the profile builder writes side by side.
+const EQUIVALENTS: &[(&str, &str)] = &[
+    ("name", "name"),
+    ("familyName", "familyName"),
+    ("givenName", "givenName"),
+    ("gender", "gender"),
+    ("birthday", "birthDate"),
+    ("phone", "telephone"),
+    ("mbox", "email"),
+    ("img", "image"),
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Turtle,
+    JsonLd,
+}
+
+/// Which vocabulary to describe the person with. Terms that only exist in one
+/// of the two (such as `foaf:knows` or `schema:worksFor`) are always kept, and
+/// so is `schema:birthDate`: `foaf:birthday` has no year.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Vocabulary {
+    Foaf,
+    Schema,
+    #[default]
+    Both,
+}
+
+/// Whether and how to record when facts were recorded in the export.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Timestamps {
+    #[default]
+    Off,
+    /// RDF-star annotations on quoted triples. JSON-LD output falls back to
+    /// reification.
+    Star,
+    /// Standard `rdf:Statement` reification, for consumers without RDF-star.
+    Reification,
+}
+
+/// How to deal with parts of the export that cannot be read.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Fail on the first problem.
+    #[default]
+    Strict,
+    /// Skip or coerce what cannot be read, reporting it as a warning.
+    Lenient,
+}
+
+/// Something the conversion had to skip or guess at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    /// Field of the export the warning is about, e.g. `friends[3]`.
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Which generation of the export each input belongs to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportVersion {
+    pub profile: ProfileVersion,
+    /// One entry per friends input, in the order they were given.
+    pub friends: Vec<FriendsVersion>,
+    /// The top-level key of each connections input, e.g. `following_v3`.
+    pub connections: Vec<String>,
+}
+
+impl Default for ExportVersion {
+    fn default() -> Self {
+        ExportVersion {
+            profile: ProfileVersion::V2,
+            friends: Vec::new(),
+            connections: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ConvertOptions {
+    /// IRI identifying the person; defaults to `#me` within the card.
+    pub webid: Option<String>,
+    /// IRI the card will be published at. Relative IRIs are resolved against it.
+    pub base: Option<String>,
+    pub format: OutputFormat,
+    pub vocabulary: Vocabulary,
+    pub selection: Selection,
+    pub mode: Mode,
+    pub timestamps: Timestamps,
+    /// Describe where the data came from (see `provenance`).
+    pub provenance: Option<ProvenanceOptions>,
+    /// Describe the friends it lists under their WebIDs (see `webid_map`).
+    pub webid_map: Option<WebIdMap>,
+    /// Keep where photos were taken. Off by default, as the coordinates are
+    /// often those of someone's home.
+    pub photo_locations: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProvenanceOptions {
+    /// Recorded as the time the document was generated.
+    pub generated_at: SystemTime,
+    /// File names of the profile, of each friends file, of each connections
+    /// file, of each posts file and of each album, in that order.
+    /// Inputs without a name are identified by their hash only.
+    pub file_names: Vec<String>,
+}
+
+impl Default for ProvenanceOptions {
+    fn default() -> Self {
+        ProvenanceOptions {
+            generated_at: SystemTime::now(),
+            file_names: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    /// The export generation detected for the inputs.
+    pub version: ExportVersion,
+    pub warnings: Vec<Warning>,
+    /// Privacy findings for the public card (see `lint`), naming the triples as
+    /// they are written.
+    pub findings: Vec<Finding>,
+    /// Data in the inputs that hatchling does not know how to convert.
+    pub unmapped: Vec<Unmapped>,
+    /// Names of the friends the WebID map has no WebID for. Empty without a map.
+    pub without_webid: Vec<String>,
+}
+
+pub struct Conversion {
+    /// The converted data. Timestamps are included as reified statements
+    /// regardless of the `Timestamps` mode.
+    pub graph: Graph,
+    /// Friend requests and removed friends, which never go into the card. They
+    /// belong in `private.ttl` next to it, which the card links to.
+    pub private: Graph,
+    /// Documents of the `posts/` container, for the pod layout (see `posts`).
+    pub posts: Vec<PodResource>,
+    /// Albums and media files of the `photos/` container (see `photos`).
+    pub photos: Photos,
+    pub report: Report,
+    pub format: OutputFormat,
+    pub timestamps: Timestamps,
+}
+
+impl Conversion {
+    /// Serializes the graph in the format chosen in the options.
+    pub fn serialize(&self) -> String {
+        match (self.format, self.timestamps) {
+            (OutputFormat::Turtle, Timestamps::Star) => timestamps::serialize_turtle_star(&self.graph),
+            (OutputFormat::Turtle, _) => self.graph.serialize_turtle(),
+            (OutputFormat::JsonLd, _) => self.graph.serialize_jsonld(),
+        }
+    }
+}
+
+type WarningSink<'a> = Box<dyn FnMut(&Warning) + 'a>;
+
+pub struct Converter<'a> {
+    options: ConvertOptions,
+    profile: Option<&'a str>,
+    friends: Vec<&'a str>,
+    connections: Vec<&'a str>,
+    posts: Vec<&'a str>,
+    albums: Vec<&'a str>,
+    on_warning: Option<WarningSink<'a>>,
+}
+
+impl Default for Converter<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Converter<'a> {
+    pub fn new() -> Converter<'a> {
+        Converter::with_options(ConvertOptions::default())
+    }
+
+    pub fn with_options(options: ConvertOptions) -> Converter<'a> {
+        Converter {
+            options,
+            profile: None,
+            friends: Vec::new(),
+            connections: Vec::new(),
+            posts: Vec::new(),
+            albums: Vec::new(),
+            on_warning: None,
+        }
+    }
+
+    /// The contents of profile_information.json.
+    pub fn profile(mut self, json: &'a str) -> Self {
+        self.profile = Some(json);
+        self
+    }
+
+    /// The contents of a friends file; may be given more than once.
+    pub fn friends(mut self, json: &'a str) -> Self {
+        self.friends.push(json);
+        self
+    }
+
+    /// The contents of another list from the `connections` folder, such as
+    /// followers.json or removed_friends.json; may be given more than once.
+    pub fn connections(mut self, json: &'a str) -> Self {
+        self.connections.push(json);
+        self
+    }
+
+    /// The contents of a `your_posts_*.json`; may be given more than once.
+    pub fn posts(mut self, json: &'a str) -> Self {
+        self.posts.push(json);
+        self
+    }
+
+    /// The contents of an album from `photos_and_videos/album/`; may be given
+    /// more than once.
+    pub fn album(mut self, json: &'a str) -> Self {
+        self.albums.push(json);
+        self
+    }
+
+    pub fn webid(mut self, webid: &str) -> Self {
+        self.options.webid = Some(webid.to_string());
+        self
+    }
+
+    pub fn base(mut self, base: &str) -> Self {
+        self.options.base = Some(base.to_string());
+        self
+    }
+
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.options.format = format;
+        self
+    }
+
+    pub fn vocabulary(mut self, vocabulary: Vocabulary) -> Self {
+        self.options.vocabulary = vocabulary;
+        self
+    }
+
+    pub fn selection(mut self, selection: Selection) -> Self {
+        self.options.selection = selection;
+        self
+    }
+
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.options.mode = mode;
+        self
+    }
+
+    pub fn timestamps(mut self, timestamps: Timestamps) -> Self {
+        self.options.timestamps = timestamps;
+        self
+    }
+
+    pub fn provenance(mut self, provenance: ProvenanceOptions) -> Self {
+        self.options.provenance = Some(provenance);
+        self
+    }
+
+    pub fn webid_map(mut self, map: WebIdMap) -> Self {
+        self.options.webid_map = Some(map);
+        self
+    }
+
+    pub fn photo_locations(mut self, photo_locations: bool) -> Self {
+        self.options.photo_locations = photo_locations;
+        self
+    }
+
+    /// Called for every warning, in the order they were raised, once the
+    /// conversion has finished or failed. A successful conversion also lists
+    /// them in its report.
+    pub fn on_warning(mut self, sink: impl FnMut(&Warning) + 'a) -> Self {
+        self.on_warning = Some(Box::new(sink));
+        self
+    }
+
+    pub fn convert(mut self) -> Result<Conversion, Error> {
+        let mut warnings = Vec::new();
+        let conversion = self.run(&mut warnings);
+        self.deliver(&warnings);
+        let mut conversion = conversion?;
+        conversion.report.warnings = warnings;
+        Ok(conversion)
+    }
+
+    /// Only maps the profile, friends and connections onto a `Profile`, which
+    /// records the setter and export field behind every triple (see `lint`
+    /// and `explain`). The other options do not apply.
+    pub fn into_profile(mut self) -> Result<Profile, Error> {
+        let mut warnings = Vec::new();
+        let mapped = self.profile.ok_or(Error::MissingProfile).and_then(|profile| {
+            crate::map_export(
+                profile,
+                &self.friends,
+                &self.connections,
+                &self.options.selection,
+                self.options.mode,
+                &mut warnings,
+            )
+        });
+        self.deliver(&warnings);
+        Ok(mapped?.profile)
+    }
+
+    fn deliver(&mut self, warnings: &[Warning]) {
+        if let Some(sink) = self.on_warning.as_mut() {
+            warnings.iter().for_each(sink);
+        }
+    }
+
+    fn run(&self, warnings: &mut Vec<Warning>) -> Result<Conversion, Error> {
+        let profile = self.profile.ok_or(Error::MissingProfile)?;
+        let mapped = crate::map_export(
+            profile,
+            &self.friends,
+            &self.connections,
+            &self.options.selection,
+            self.options.mode,
+            warnings,
+        )?;
+        let mut posts: Vec<FBPost> = Vec::new();
+        if self.options.selection.is_selected("posts") {
+            for (n, json) in self.posts.iter().enumerate() {
+                let file = match self.options.mode {
+                    Mode::Strict => FBPosts::new(json),
+                    Mode::Lenient => FBPosts::new_lenient(json, warnings),
+                }
+                .map_err(|err| err.for_input(Input::Posts(n)))?;
+                posts.extend(file.posts);
+            }
+        }
+        let mut albums: Vec<FBAlbum> = Vec::new();
+        if self.options.selection.is_selected("photos") {
+            for (n, json) in self.albums.iter().enumerate() {
+                let album = match self.options.mode {
+                    Mode::Strict => FBAlbum::new(json),
+                    Mode::Lenient => FBAlbum::new_lenient(json, warnings),
+                }
+                .map_err(|err| err.for_input(Input::Album(n)))?;
+                albums.push(album);
+            }
+        }
+        let mut findings = lint::lint(&mapped.profile);
+        let mut unmapped = drift::unmapped_in_profile(profile);
+        for friends in &self.friends {
+            unmapped.extend(drift::unmapped_in_friends(friends));
+        }
+
+        let version = mapped.version;
+        let mut graph = match self.options.timestamps {
+            Timestamps::Off => mapped.profile.into_graph(),
+            Timestamps::Star | Timestamps::Reification => {
+                let mut graph = mapped.profile.graph().clone();
+                timestamps::reify(&mapped.profile, &mut graph);
+                graph
+            }
+        };
+        let mut photos = photos::photos(&albums, self.options.photo_locations);
+        if !self.options.selection.is_selected("photos.profile_picture") {
+            photos.profile_picture = None;
+        }
+        if let Some(picture) = &photos.profile_picture {
+            photos::add_profile_picture(&mut graph, picture);
+        }
+        graph = select_vocabulary(&graph, self.options.vocabulary);
+        retain_published(&mut findings, &graph);
+        let mut without_webid = Vec::new();
+        let mut webids = HashMap::new();
+        if let Some(map) = &self.options.webid_map {
+            webids = webid_map::renaming(&graph, map);
+            (graph, without_webid) = webid_map::apply(&graph, map);
+        }
+        let posts = posts::posts_resources(
+            &posts,
+            &graph,
+            &PostsOptions {
+                card: self.options.base.clone(),
+                webid: self.options.webid.clone(),
+            },
+        );
+        if let Some(options) = &self.options.provenance {
+            let name = |i: usize| options.file_names.get(i).map(String::as_str);
+            let mut sources = vec![Source::new(name(0), profile, &version.profile.to_string())];
+            // Friends files that were not read have no detected version
+            for (i, friends) in self.friends.iter().enumerate() {
+                let detected = version.friends.get(i).map(ToString::to_string).unwrap_or_default();
+                sources.push(Source::new(name(i + 1), friends, &detected));
+            }
+            for (i, connections) in self.connections.iter().enumerate() {
+                let detected = version.connections.get(i).cloned().unwrap_or_default();
+                sources.push(Source::new(name(i + 1 + self.friends.len()), connections, &detected));
+            }
+            let before_posts = 1 + self.friends.len() + self.connections.len();
+            for (i, posts) in self.posts.iter().enumerate() {
+                sources.push(Source::new(name(i + before_posts), posts, ""));
+            }
+            let before_albums = before_posts + self.posts.len();
+            for (i, album) in self.albums.iter().enumerate() {
+                sources.push(Source::new(name(i + before_albums), album, ""));
+            }
+            Provenance {
+                generated_at: options.generated_at,
+                sources,
+                times: mapped.times,
+            }
+            .describe(&mut graph);
+        }
+        if let Some(webid) = &self.options.webid {
+            graph = rename(&graph, "#me", webid);
+        }
+        if let Some(base) = &self.options.base {
+            graph = resolve(&graph, base);
+        }
+        // Name the flagged triples as they are written
+        rename_findings(&mut findings, |iri| {
+            let iri = webids.get(iri).map_or(iri, String::as_str);
+            let iri = match &self.options.webid {
+                Some(webid) if iri == "#me" => webid.as_str(),
+                _ => iri,
+            };
+            match &self.options.base {
+                Some(base) => resolve_iri(base, iri),
+                None => iri.to_string(),
+            }
+        });
+        let (graph, private) = access::split(&graph, &VisibilityPolicy::private_lists(), "card", "private.ttl");
+        retain_published(&mut findings, &graph);
+        Ok(Conversion {
+            graph,
+            private,
+            posts,
+            photos,
+            report: Report {
+                version,
+                warnings: Vec::new(),
+                findings,
+                unmapped,
+                without_webid,
+            },
+            format: self.options.format,
+            timestamps: self.options.timestamps,
+        })
+    }
+}
+
+fn select_vocabulary(graph: &Graph, vocabulary: Vocabulary) -> Graph {
+    let (drop, keep) = match vocabulary {
+        Vocabulary::Both => return graph.clone(),
+        Vocabulary::Foaf => (SCHEMA, FOAF),
+        Vocabulary::Schema => (FOAF, SCHEMA),
+    };
+    let counterpart = |term: &str| -> Option<String> {
+        if term == format!("{}Person", drop) {
+            return Some(format!("{}Person", keep));
+        }
+        let local = term.strip_prefix(drop)?;
+        if drop == SCHEMA && local == "birthDate" {
+            return None;
+        }
+        EQUIVALENTS.iter().find_map(|(foaf, schema)| {
+            let (ours, theirs) = if drop == FOAF { (foaf, schema) } else { (schema, foaf) };
+            (*ours == local).then(|| format!("{}{}", keep, theirs))
+        })
+    };
+    let has = |s: &Node, p: &str, o: Option<&str>| {
+        graph.triples().iter().any(|t| {
+            t.subject == *s
+                && t.predicate == Node::Uri(p.to_string())
+                && o.is_none_or(|o| t.object == Node::Uri(o.to_string()))
+        })
+    };
+
+    let mut selected = Graph::new();
+    for ns in graph.namespaces() {
+        selected.add_namespace(ns);
+    }
+    for t in graph.triples() {
+        let redundant = match (&t.predicate, &t.object) {
+            (Node::Uri(p), Node::Uri(class)) if p == RDF_TYPE => counterpart(class)
+                .is_some_and(|other| has(&t.subject, RDF_TYPE, Some(&other))),
+            (Node::Uri(p), _) => {
+                counterpart(p).is_some_and(|other| has(&t.subject, &other, None))
+            }
+            _ => false,
+        };
+        if !redundant {
+            selected.add_triple(t);
+        }
+    }
+    selected
+}
+
+/// Keeps the triples of each finding that `graph` publishes, and the findings
+/// left with any.
+fn retain_published(findings: &mut Vec<Finding>, graph: &Graph) {
+    let published: HashSet<&Triple> = graph.triples().iter().collect();
+    for finding in findings.iter_mut() {
+        finding.triples.retain(|t| published.contains(t));
+    }
+    findings.retain(|f| !f.triples.is_empty());
+}
+
+/// Rewrites the IRIs in the triples of `findings`, as `map_iris` does a graph.
+fn rename_findings(findings: &mut [Finding], f: impl Fn(&str) -> String) {
+    let map = |node: &Node| match node {
+        Node::Uri(iri) => Node::Uri(f(iri)),
+        other => other.clone(),
+    };
+    for finding in findings {
+        for t in finding.triples.iter_mut() {
+            *t = Triple::new(&map(&t.subject), &map(&t.predicate), &map(&t.object));
+        }
+    }
+}
+
+fn rename(graph: &Graph, from: &str, to: &str) -> Graph {
+    map_iris(graph, |iri| if iri == from { to.to_string() } else { iri.to_string() })
+}
+
+fn resolve(graph: &Graph, base: &str) -> Graph {
+    let mut resolved = map_iris(graph, |iri| resolve_iri(base, iri));
+    for ns in graph.namespaces() {
+        resolved.add_namespace(&Namespace::new(
+            ns.prefix.clone(),
+            Uri::new(resolve_iri(base, &ns.iri)),
+        ));
+    }
+    resolved
+}
+
+pub(crate) fn map_iris(graph: &Graph, f: impl Fn(&str) -> String) -> Graph {
+    let map = |node: &Node| match node {
+        Node::Uri(iri) => Node::Uri(f(iri)),
+        other => other.clone(),
+    };
+    let mut mapped = Graph::new();
+    for ns in graph.namespaces() {
+        mapped.add_namespace(ns);
+    }
+    for t in graph.triples() {
+        mapped.add_triple(&Triple::new(&map(&t.subject), &map(&t.predicate), &map(&t.object)));
+    }
+    mapped
+}
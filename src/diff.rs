@@ -0,0 +1,338 @@
+// ---------------------------------------------------------------------------
+// Semantic graph diff
+// Blank node labels are document-local, so two graphs are compared after
+// relabelling every blank node with a signature computed from its
+// surroundings (iterative colour refinement). Structurally identical blank
+// nodes therefore match no matter what they were called in either file.
+// Refinement alone cannot tell apart blank nodes with the same surroundings,
+// such as two identical addresses; those are singled out one at a time,
+// trying each candidate of the new graph and keeping the one that leaves the
+// fewest changes, and refined again. The search is greedy, so graphs that
+// refinement cannot split at all, like regular rings of blank nodes, may
+// show more changes than the smallest diff.
+// ---------------------------------------------------------------------------
+
+use crate::rdf::{Graph, Node, Triple};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+pub struct GraphDiff {
+    pub removed: Vec<Triple>,
+    pub added: Vec<Triple>,
+    /// Carries the prefixes of both graphs, used only to format triples.
+    formatter: Graph,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+
+    /// An empty graph holding the prefixes of both inputs, for formatting triples.
+    pub fn formatter(&self) -> &Graph {
+        &self.formatter
+    }
+}
+
+pub fn diff(old: &Graph, new: &Graph) -> GraphDiff {
+    let (old_colors, new_colors) = blank_node_colors(old, new);
+
+    let old_canonical = canonical(old, &old_colors);
+    let new_canonical = canonical(new, &new_colors);
+    let old_set: HashSet<&Triple> = old_canonical.iter().collect();
+    let new_set: HashSet<&Triple> = new_canonical.iter().collect();
+
+    let mut formatter = Graph::new();
+    for ns in old.namespaces().iter().chain(new.namespaces()) {
+        formatter.add_namespace(ns);
+    }
+
+    GraphDiff {
+        removed: only_in(old.triples(), &old_canonical, &new_set),
+        added: only_in(new.triples(), &new_canonical, &old_set),
+        formatter,
+    }
+}
+
+/// Returns the triples of `triples` whose canonical form is missing from `other`,
+/// dropping duplicates.
+fn only_in(triples: &[Triple], canonical: &[Triple], other: &HashSet<&Triple>) -> Vec<Triple> {
+    let mut seen = HashSet::new();
+    triples
+        .iter()
+        .zip(canonical)
+        .filter(|(_, c)| !other.contains(c) && seen.insert(*c))
+        .map(|(t, _)| t.clone())
+        .collect()
+}
+
+fn canonical(graph: &Graph, colors: &HashMap<String, u64>) -> Vec<Triple> {
+    graph.triples().iter().map(|t| canonicalize(t, colors)).collect()
+}
+
+fn canonicalize(t: &Triple, colors: &HashMap<String, u64>) -> Triple {
+    let relabel = |node: &Node| match node {
+        Node::Blank(id) => Node::Blank(format!("c{:016x}", colors[id])),
+        other => other.clone(),
+    };
+    Triple {
+        subject: relabel(&t.subject),
+        predicate: t.predicate.clone(),
+        object: relabel(&t.object),
+    }
+}
+
+type Colors = HashMap<String, u64>;
+
+/// Colours the blank nodes of both graphs, singling out nodes that refinement
+/// leaves in the same class until every class has one member per graph.
+fn blank_node_colors(old: &Graph, new: &Graph) -> (Colors, Colors) {
+    let (mut old_colors, mut new_colors) = stabilize(old, new, initial_colors(old), initial_colors(new));
+    while let Some(color) = ambiguous_color(&old_colors, &new_colors) {
+        let old_choice = members(&old_colors, color).into_iter().next();
+        let mut candidates: Vec<Option<String>> = members(&new_colors, color).into_iter().map(Some).collect();
+        if candidates.is_empty() {
+            candidates.push(None);
+        }
+        let mut best: Option<(usize, (Colors, Colors))> = None;
+        for candidate in candidates {
+            let colors = stabilize(
+                old,
+                new,
+                single_out(&old_colors, old_choice.as_deref(), color),
+                single_out(&new_colors, candidate.as_deref(), color),
+            );
+            let changes = count_changes(old, new, &colors);
+            if best.as_ref().is_none_or(|(fewest, _)| changes < *fewest) {
+                best = Some((changes, colors));
+            }
+            if changes == 0 {
+                break;
+            }
+        }
+        (old_colors, new_colors) = best.expect("at least one candidate").1;
+    }
+    (old_colors, new_colors)
+}
+
+/// Refines the colours of both graphs in lockstep so that the same number of
+/// rounds is applied to each, stopping once neither partition gets finer.
+fn stabilize(old: &Graph, new: &Graph, mut old_colors: Colors, mut new_colors: Colors) -> (Colors, Colors) {
+    let mut old_classes = count_classes(&old_colors);
+    let mut new_classes = count_classes(&new_colors);
+    loop {
+        let next_old = refine(old, &old_colors);
+        let next_new = refine(new, &new_colors);
+        let next_old_classes = count_classes(&next_old);
+        let next_new_classes = count_classes(&next_new);
+        old_colors = next_old;
+        new_colors = next_new;
+        if next_old_classes <= old_classes && next_new_classes <= new_classes {
+            return (old_colors, new_colors);
+        }
+        old_classes = next_old_classes;
+        new_classes = next_new_classes;
+    }
+}
+
+/// The smallest colour shared by more than one blank node of either graph.
+fn ambiguous_color(old_colors: &Colors, new_colors: &Colors) -> Option<u64> {
+    [old_colors, new_colors]
+        .into_iter()
+        .flat_map(|colors| {
+            let mut counts: HashMap<u64, usize> = HashMap::new();
+            for color in colors.values() {
+                *counts.entry(*color).or_default() += 1;
+            }
+            counts.into_iter().filter(|(_, n)| *n > 1).map(|(color, _)| color)
+        })
+        .min()
+}
+
+/// The blank nodes coloured `color`, sorted by label.
+fn members(colors: &Colors, color: u64) -> Vec<String> {
+    let mut ids: Vec<String> = colors
+        .iter()
+        .filter(|(_, c)| **c == color)
+        .map(|(id, _)| id.clone())
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Gives `id` a colour of its own, derived from its current `color`.
+fn single_out(colors: &Colors, id: Option<&str>, color: u64) -> Colors {
+    let mut colors = colors.clone();
+    if let Some(id) = id {
+        let mut hasher = DefaultHasher::new();
+        (color, "singled out").hash(&mut hasher);
+        colors.insert(id.to_string(), hasher.finish());
+    }
+    colors
+}
+
+fn count_changes(old: &Graph, new: &Graph, (old_colors, new_colors): &(Colors, Colors)) -> usize {
+    let old_canonical: HashSet<Triple> = canonical(old, old_colors).into_iter().collect();
+    let new_canonical: HashSet<Triple> = canonical(new, new_colors).into_iter().collect();
+    old_canonical.symmetric_difference(&new_canonical).count()
+}
+
+fn initial_colors(graph: &Graph) -> HashMap<String, u64> {
+    let mut colors = HashMap::new();
+    for t in graph.triples() {
+        for node in [&t.subject, &t.object] {
+            if let Node::Blank(id) = node {
+                colors.insert(id.clone(), 0);
+            }
+        }
+    }
+    colors
+}
+
+fn refine(graph: &Graph, colors: &HashMap<String, u64>) -> HashMap<String, u64> {
+    let mut signatures: HashMap<&str, Vec<String>> = HashMap::new();
+    for t in graph.triples() {
+        if let Node::Blank(id) = &t.subject {
+            signatures
+                .entry(id)
+                .or_default()
+                .push(edge_signature("out", &t.predicate, &t.object, colors));
+        }
+        if let Node::Blank(id) = &t.object {
+            signatures
+                .entry(id)
+                .or_default()
+                .push(edge_signature("in", &t.predicate, &t.subject, colors));
+        }
+    }
+    colors
+        .iter()
+        .map(|(id, color)| {
+            let mut signature = signatures.remove(id.as_str()).unwrap_or_default();
+            signature.sort();
+            let mut hasher = DefaultHasher::new();
+            color.hash(&mut hasher);
+            signature.hash(&mut hasher);
+            (id.clone(), hasher.finish())
+        })
+        .collect()
+}
+
+fn edge_signature(
+    direction: &str,
+    predicate: &Node,
+    other: &Node,
+    colors: &HashMap<String, u64>,
+) -> String {
+    match blank_color(other, colors) {
+        Some(c) => format!("{} {:?} _:{:x}", direction, predicate, c),
+        None => format!("{} {:?} {:?}", direction, predicate, other),
+    }
+}
+
+fn blank_color(node: &Node, colors: &HashMap<String, u64>) -> Option<u64> {
+    match node {
+        Node::Blank(id) => colors.get(id).copied(),
+        _ => None,
+    }
+}
+
+fn count_classes(colors: &HashMap<String, u64>) -> usize {
+    colors.values().collect::<HashSet<_>>().len()
+}
+
+impl fmt::Display for GraphDiff {
+    /// Groups changes by subject, listing removals before additions.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes.");
+        }
+        let mut subjects: Vec<&Node> = Vec::new();
+        for t in self.removed.iter().chain(&self.added) {
+            if !subjects.contains(&&t.subject) {
+                subjects.push(&t.subject);
+            }
+        }
+        for subject in subjects {
+            writeln!(f, "{}", self.formatter.format_node(subject))?;
+            for (sign, triples) in [("-", &self.removed), ("+", &self.added)] {
+                for t in triples.iter().filter(|t| &t.subject == subject) {
+                    writeln!(
+                        f,
+                        "  {} {} {}",
+                        sign,
+                        self.formatter.format_predicate(&t.predicate),
+                        self.formatter.format_node(&t.object)
+                    )?;
+                }
+            }
+        }
+        writeln!(
+            f,
+            "\n{} removed, {} added",
+            self.removed.len(),
+            self.added.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff;
+    use crate::turtle::parse;
+
+    #[test]
+    fn identical_graphs_have_no_changes() {
+        let g = parse("<#me> <http://xmlns.com/foaf/0.1/name> \"Jane\" .").unwrap();
+        assert!(diff(&g, &g).is_empty());
+    }
+
+    #[test]
+    fn renamed_blank_nodes_are_isomorphic() {
+        let old = parse(
+            "<#me> <http://schema.org/homeLocation> _:a . _:a <http://schema.org/address> \"Portland\" .",
+        )
+        .unwrap();
+        let new = parse("<#me> <http://schema.org/homeLocation> [ <http://schema.org/address> \"Portland\" ] .")
+            .unwrap();
+        assert!(diff(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn blank_nodes_with_the_same_surroundings_are_kept_apart() {
+        let two = "<#me> <http://schema.org/homeLocation> _:a, _:b . \
+                   _:a <http://schema.org/address> \"Portland\" . _:b <http://schema.org/address> \"Portland\" .";
+        let one = "<#me> <http://schema.org/homeLocation> [ <http://schema.org/address> \"Portland\" ] .";
+        let (two, one) = (parse(two).unwrap(), parse(one).unwrap());
+        assert!(diff(&two, &two).is_empty());
+        let d = diff(&two, &one);
+        assert_eq!((d.removed.len(), d.added.len()), (2, 0));
+    }
+
+    #[test]
+    fn blank_nodes_refinement_cannot_tell_apart_are_still_compared() {
+        let p = "<http://example.org/p>";
+        let hexagon = format!("_:a {p} _:b . _:b {p} _:c . _:c {p} _:d . _:d {p} _:e . _:e {p} _:f . _:f {p} _:a .");
+        let triangles = format!("_:a {p} _:b . _:b {p} _:c . _:c {p} _:a . _:d {p} _:e . _:e {p} _:f . _:f {p} _:d .");
+        let (hexagon, triangles) = (parse(&hexagon).unwrap(), parse(&triangles).unwrap());
+        assert!(diff(&hexagon, &hexagon).is_empty());
+        assert!(!diff(&hexagon, &triangles).is_empty());
+    }
+
+    #[test]
+    fn changed_blank_node_content_is_reported() {
+        let old = parse(
+            "<#me> <http://schema.org/homeLocation> _:a . _:a <http://schema.org/address> \"Portland\" .",
+        )
+        .unwrap();
+        let new = parse(
+            "<#me> <http://schema.org/homeLocation> _:a . _:a <http://schema.org/address> \"Seattle\" .",
+        )
+        .unwrap();
+        let d = diff(&old, &new);
+        assert_eq!(d.removed.len(), 2);
+        assert_eq!(d.added.len(), 2);
+    }
+}
@@ -0,0 +1,384 @@
+// ---------------------------------------------------------------------------
+// Schema drift
+// Meta reshapes the DYI export regularly. The shapes below describe the parts
+// of the export hatchling knows about; anything in an input that falls
+// outside them is reported as unmapped data, so new fields worth converting
+// get noticed instead of being silently ignored. Fields the parser reads but
+// the conversion leaves out are marked as such and reported too.
+// ---------------------------------------------------------------------------
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// How many concrete paths to keep as samples for each unmapped entry.
+const MAX_SAMPLES: usize = 3;
+
+enum Shape {
+    String,
+    Number,
+    Bool,
+    Array(&'static Shape),
+    Object(&'static [(&'static str, Shape)]),
+    OneOf(&'static [Shape]),
+    /// Read by the parser, but not converted.
+    Unconverted(&'static Shape),
+}
+
+const NAMED_TIMESTAMP: Shape = Shape::Object(&[("name", Shape::String), ("timestamp", Shape::Number)]);
+const DATE: Shape = Shape::Object(&[
+    ("year", Shape::Number),
+    ("month", Shape::Number),
+    ("day", Shape::Number),
+]);
+const VIEW: Shape = Shape::Object(&[("name", Shape::String), ("description", Shape::String)]);
+
+const PROFILE: Shape = Shape::Object(&[
+    (
+        "name",
+        Shape::Object(&[
+            ("full_name", Shape::String),
+            ("first_name", Shape::String),
+            ("middle_name", Shape::Unconverted(&Shape::String)),
+            ("last_name", Shape::String),
+        ]),
+    ),
+    (
+        "emails",
+        Shape::Object(&[
+            ("emails", Shape::Array(&Shape::String)),
+            ("previous_emails", Shape::Unconverted(&Shape::Array(&Shape::String))),
+            ("pending_emails", Shape::Unconverted(&Shape::Array(&Shape::String))),
+            ("ad_account_emails", Shape::Unconverted(&Shape::Array(&Shape::String))),
+        ]),
+    ),
+    ("birthday", DATE),
+    (
+        "gender",
+        Shape::Object(&[
+            ("gender_option", Shape::String),
+            ("pronoun", Shape::Unconverted(&Shape::String)),
+        ]),
+    ),
+    ("previous_names", Shape::Unconverted(&Shape::Array(&NAMED_TIMESTAMP))),
+    ("current_city", NAMED_TIMESTAMP),
+    ("hometown", NAMED_TIMESTAMP),
+    (
+        "relationship",
+        Shape::Unconverted(&Shape::Object(&[
+            ("status", Shape::String),
+            ("partner", Shape::String),
+            ("anniversary", DATE),
+            ("timestamp", Shape::Number),
+        ])),
+    ),
+    (
+        "family_members",
+        Shape::Unconverted(&Shape::Array(&Shape::Object(&[
+            ("name", Shape::String),
+            ("relation", Shape::String),
+            ("timestamp", Shape::Number),
+        ]))),
+    ),
+    (
+        "education_experiences",
+        Shape::Array(&Shape::Object(&[
+            ("school_type", Shape::String),
+            ("name", Shape::String),
+            ("start_timestamp", Shape::Number),
+            ("end_timestamp", Shape::Number),
+            ("graduated", Shape::Bool),
+            ("description", Shape::Unconverted(&Shape::String)),
+            ("concentrations", Shape::Unconverted(&Shape::Array(&Shape::String))),
+            ("degree", Shape::Unconverted(&Shape::String)),
+        ])),
+    ),
+    (
+        "work_experiences",
+        Shape::Array(&Shape::Object(&[
+            ("employer", Shape::OneOf(&[Shape::String, Shape::Object(&[("name", Shape::String)])])),
+            ("title", Shape::String),
+            ("location", Shape::Unconverted(&Shape::String)),
+            ("description", Shape::Unconverted(&Shape::String)),
+            ("start_timestamp", Shape::Number),
+            ("end_timestamp", Shape::Number),
+        ])),
+    ),
+    (
+        "languages",
+        Shape::Unconverted(&Shape::Array(&Shape::OneOf(&[Shape::String, NAMED_TIMESTAMP]))),
+    ),
+    ("political_view", Shape::Unconverted(&VIEW)),
+    ("religious_view", Shape::Unconverted(&VIEW)),
+    ("professional_skills", Shape::Unconverted(&Shape::Array(&Shape::String))),
+    (
+        "address",
+        Shape::Unconverted(&Shape::Object(&[
+            ("street", Shape::String),
+            ("city", Shape::String),
+            ("zipcode", Shape::String),
+            ("neighborhood", Shape::String),
+            ("country", Shape::String),
+            ("country_code", Shape::String),
+            ("region", Shape::String),
+        ])),
+    ),
+    (
+        "phone_numbers",
+        Shape::Array(&Shape::Object(&[
+            ("phone_type", Shape::Unconverted(&Shape::String)),
+            ("phone_number", Shape::String),
+            ("verified", Shape::Unconverted(&Shape::Bool)),
+        ])),
+    ),
+    ("username", Shape::String),
+    (
+        "places_lived",
+        Shape::Unconverted(&Shape::Array(&Shape::Object(&[
+            ("place", Shape::String),
+            ("start_timestamp", Shape::Number),
+        ]))),
+    ),
+    ("name_pronunciation", Shape::Unconverted(&Shape::String)),
+    ("profile_uri", Shape::String),
+    ("intro_bio", Shape::Unconverted(&Shape::OneOf(&[Shape::String, NAMED_TIMESTAMP]))),
+    ("registration_timestamp", Shape::Number),
+]);
+
+const PROFILE_FILE: Shape = Shape::Object(&[("profile", PROFILE), ("profile_v2", PROFILE)]);
+
+const FRIEND: Shape = Shape::Object(&[
+    ("name", Shape::String),
+    ("target", Shape::String),
+    ("timestamp", Shape::Number),
+]);
+
+const FRIENDS_FILE: Shape = Shape::OneOf(&[
+    Shape::Object(&[("friends_v2", Shape::Array(&FRIEND))]),
+    Shape::Object(&[("friends", Shape::Array(&FRIEND))]),
+    Shape::Array(&FRIEND),
+]);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnmappedKind {
+    /// A key hatchling does not know about.
+    UnknownKey,
+    /// A known key whose value has a different type than expected.
+    UnexpectedShape,
+    /// A key hatchling reads but does not convert yet.
+    NotConverted,
+}
+
+/// Data found in an input that hatchling does not convert.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Unmapped {
+    /// Which input the data is in, e.g. `profile` or `friends`.
+    pub input: String,
+    /// The path with list indices left out, e.g. `profile_v2.phone_numbers[].creation_time`.
+    pub path: String,
+    pub kind: UnmappedKind,
+    /// JSON type of the values found there.
+    pub found: String,
+    /// How often the path occurs.
+    pub count: usize,
+    /// A few concrete paths, e.g. `profile_v2.phone_numbers[0].creation_time`.
+    pub samples: Vec<String>,
+}
+
+/// Lists everything in a profile_information.json that hatchling does not know.
+pub fn unmapped_in_profile(json: &str) -> Vec<Unmapped> {
+    unmapped(json, "profile", &PROFILE_FILE)
+}
+
+/// Lists everything in a friends file that hatchling does not know.
+pub fn unmapped_in_friends(json: &str) -> Vec<Unmapped> {
+    unmapped(json, "friends", &FRIENDS_FILE)
+}
+
+fn unmapped(json: &str, input: &str, shape: &Shape) -> Vec<Unmapped> {
+    let Ok(value) = serde_json::from_str::<Value>(json) else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+    walk(&value, shape, "", "", input, &mut found);
+    found
+}
+
+fn walk(value: &Value, shape: &Shape, path: &str, sample: &str, input: &str, found: &mut Vec<Unmapped>) {
+    match (shape, value) {
+        (Shape::Unconverted(_), value) if is_empty(value) => {}
+        (Shape::Unconverted(_), value) => record(found, input, path, sample, UnmappedKind::NotConverted, value),
+        (Shape::String, Value::String(_))
+        | (Shape::Number, Value::Number(_))
+        | (Shape::Bool, Value::Bool(_)) => {}
+        (_, Value::Null) => {}
+        (Shape::Array(item), Value::Array(items)) => {
+            for (i, value) in items.iter().enumerate() {
+                let sample = format!("{}[{}]", sample, i);
+                walk(value, item, &format!("{}[]", path), &sample, input, found);
+            }
+        }
+        (Shape::Object(fields), Value::Object(map)) => {
+            for (key, value) in map {
+                let path = join(path, key);
+                let sample = join(sample, key);
+                match fields.iter().find(|(name, _)| name == key) {
+                    Some((_, shape)) => walk(value, shape, &path, &sample, input, found),
+                    None => record(found, input, &path, &sample, UnmappedKind::UnknownKey, value),
+                }
+            }
+        }
+        (Shape::OneOf(shapes), _) if shapes.iter().any(|shape| fits(shape, value)) => {
+            // Walk into the alternative the value matches best
+            let best = shapes
+                .iter()
+                .filter(|shape| fits(shape, value))
+                .map(|shape| {
+                    let mut inner = Vec::new();
+                    walk(value, shape, path, sample, input, &mut inner);
+                    inner
+                })
+                .min_by_key(|inner| inner.iter().map(|u| u.count).sum::<usize>())
+                .unwrap_or_default();
+            for entry in best {
+                merge(found, entry);
+            }
+        }
+        _ => record(found, input, path, sample, UnmappedKind::UnexpectedShape, value),
+    }
+}
+
+/// Whether `value` has the outer type of `shape`; objects must also share a key.
+fn fits(shape: &Shape, value: &Value) -> bool {
+    match (shape, value) {
+        (Shape::String, Value::String(_))
+        | (Shape::Number, Value::Number(_))
+        | (Shape::Bool, Value::Bool(_))
+        | (Shape::Array(_), Value::Array(_)) => true,
+        (Shape::Object(fields), Value::Object(map)) => {
+            map.is_empty() || map.keys().any(|key| fields.iter().any(|(name, _)| name == key))
+        }
+        (Shape::OneOf(shapes), _) => shapes.iter().any(|shape| fits(shape, value)),
+        (Shape::Unconverted(shape), _) => fits(shape, value),
+        _ => false,
+    }
+}
+
+/// Whether `value` holds nothing but blanks, zeros and empty containers, as
+/// the export writes for sections the user never filled in.
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::String(s) => s.is_empty(),
+        Value::Number(n) => n.as_f64() == Some(0.0),
+        Value::Array(items) => items.iter().all(is_empty),
+        Value::Object(map) => map.values().all(is_empty),
+        Value::Bool(true) => false,
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn record(found: &mut Vec<Unmapped>, input: &str, path: &str, sample: &str, kind: UnmappedKind, value: &Value) {
+    merge(
+        found,
+        Unmapped {
+            input: input.to_string(),
+            path: if path.is_empty() { ".".to_string() } else { path.to_string() },
+            kind,
+            found: json_type(value).to_string(),
+            count: 1,
+            samples: vec![if sample.is_empty() { ".".to_string() } else { sample.to_string() }],
+        },
+    );
+}
+
+fn merge(found: &mut Vec<Unmapped>, entry: Unmapped) {
+    match found
+        .iter_mut()
+        .find(|u| u.path == entry.path && u.kind == entry.kind && u.input == entry.input)
+    {
+        Some(existing) => {
+            existing.count += entry.count;
+            for sample in entry.samples {
+                if existing.samples.len() < MAX_SAMPLES {
+                    existing.samples.push(sample);
+                }
+            }
+        }
+        None => found.push(entry),
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{unmapped_in_friends, unmapped_in_profile, Shape, UnmappedKind, PROFILE};
+    use crate::facebook_parser::profile_keys;
+
+    #[test]
+    fn unknown_keys_in_lists_are_grouped_with_samples() {
+        let json = r#"{"profile_v2": {"phone_numbers": [
+            {"phone_number": "1", "creation_time": 0},
+            {"phone_number": "2", "creation_time": 0}
+        ]}}"#;
+        let found = unmapped_in_profile(json);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "profile_v2.phone_numbers[].creation_time");
+        assert_eq!(found[0].count, 2);
+        assert_eq!(found[0].samples[1], "profile_v2.phone_numbers[1].creation_time");
+    }
+
+    #[test]
+    fn known_keys_with_new_shapes_are_reported() {
+        let found = unmapped_in_profile(r#"{"profile_v2": {"username": {"name": "jane"}}}"#);
+        assert_eq!(found[0].kind, UnmappedKind::UnexpectedShape);
+        assert_eq!(found[0].found, "object");
+    }
+
+    #[test]
+    fn sections_that_are_read_but_not_converted_are_reported() {
+        let found = unmapped_in_profile(
+            r#"{"profile_v2": {"address": {"street": "12 Main St"}, "religious_view": {"name": ""}}}"#,
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "profile_v2.address");
+        assert_eq!(found[0].kind, UnmappedKind::NotConverted);
+    }
+
+    #[test]
+    fn the_profile_shape_covers_every_key_the_parser_reads() {
+        let Shape::Object(fields) = PROFILE else {
+            unreachable!()
+        };
+        let keys = profile_keys();
+        assert!(!keys.is_empty());
+        for key in keys {
+            assert!(fields.iter().any(|(name, _)| name == key), "{} is missing from PROFILE", key);
+        }
+    }
+
+    #[test]
+    fn each_friends_layout_is_recognised() {
+        assert!(unmapped_in_friends(r#"{"friends_v2": [{"name": "A", "timestamp": 1}]}"#).is_empty());
+        assert!(unmapped_in_friends(r#"[{"name": "A", "target": "https://x"}]"#).is_empty());
+        let found = unmapped_in_friends(r#"{"friends_v2": [{"name": "A", "contact_info": "x"}]}"#);
+        assert_eq!(found[0].path, "friends_v2[].contact_info");
+    }
+}
@@ -0,0 +1,317 @@
+// ---------------------------------------------------------------------------
+// Errors
+// Everything that can go wrong while reading a Facebook export. Each error
+// records which input it came from, the JSON path inside it and, where the
+// parser knows it, the line and column, so the CLI can point at the exact
+// spot and suggest a fix.
+// ---------------------------------------------------------------------------
+
+use crate::facebook_parser::fix_facebook_encoding;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
+
+/// Which of the conversion's inputs an error refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Input {
+    Profile,
+    /// The n-th friends file, counting from 0.
+    Friends(usize),
+    /// The n-th other connections list (followers, requests, ...), counting from 0.
+    Connections(usize),
+    /// The n-th `your_posts_*.json`, counting from 0.
+    Posts(usize),
+    /// The n-th photo album, counting from 0.
+    Album(usize),
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Input::Profile => f.write_str("profile information"),
+            Input::Friends(0) => f.write_str("friends file"),
+            Input::Friends(n) => write!(f, "friends file {}", n + 1),
+            Input::Connections(0) => f.write_str("connections file"),
+            Input::Connections(n) => write!(f, "connections file {}", n + 1),
+            Input::Posts(0) => f.write_str("posts file"),
+            Input::Posts(n) => write!(f, "posts file {}", n + 1),
+            Input::Album(0) => f.write_str("album file"),
+            Input::Album(n) => write!(f, "album file {}", n + 1),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub input: Input,
+    /// File name, when the caller knows it (see `Error::in_file`).
+    pub file: Option<String>,
+    /// JSON path such as `profile_v2.phone_numbers[0]`; `.` for the top level.
+    pub path: String,
+    /// 1-based; 0 when unknown.
+    pub line: usize,
+    pub column: usize,
+}
+
+/// What an input that hatchling could not read seems to be instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LooksLike {
+    Empty,
+    HtmlExport,
+    ProfileFile,
+    FriendsFile,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input is not well-formed JSON.
+    Syntax { location: Location, message: String },
+    /// A key the export must contain is absent.
+    MissingField { location: Location, field: String },
+    /// A value has the wrong type or shape.
+    InvalidValue { location: Location, message: String },
+    /// Well-formed JSON, but not an export layout hatchling knows.
+    UnsupportedLayout {
+        location: Location,
+        looks_like: Option<LooksLike>,
+    },
+    /// No profile_information.json was given to the converter.
+    MissingProfile,
+}
+
+impl Error {
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Error::Syntax { location, .. }
+            | Error::MissingField { location, .. }
+            | Error::InvalidValue { location, .. }
+            | Error::UnsupportedLayout { location, .. } => Some(location),
+            Error::MissingProfile => None,
+        }
+    }
+
+    fn location_mut(&mut self) -> Option<&mut Location> {
+        match self {
+            Error::Syntax { location, .. }
+            | Error::MissingField { location, .. }
+            | Error::InvalidValue { location, .. }
+            | Error::UnsupportedLayout { location, .. } => Some(location),
+            Error::MissingProfile => None,
+        }
+    }
+
+    /// Attaches the name of the file the failing input was read from.
+    pub fn in_file(mut self, file: &str) -> Self {
+        if let Some(location) = self.location_mut() {
+            location.file = Some(file.to_string());
+        }
+        self
+    }
+
+    pub(crate) fn for_input(mut self, input: Input) -> Self {
+        if let Some(location) = self.location_mut() {
+            location.input = input;
+        }
+        self
+    }
+
+    /// The failing line of `contents`, cut to 80 characters around the error,
+    /// and the character offset of the error within it.
+    pub fn excerpt(&self, contents: &str) -> Option<(String, usize)> {
+        let location = self.location().filter(|l| l.line > 0)?;
+        // serde saw the repaired text and counts the column in bytes.
+        let fixed = fix_facebook_encoding(contents);
+        let line = fixed.lines().nth(location.line - 1)?;
+        let mut byte = location.column.saturating_sub(1).min(line.len());
+        while !line.is_char_boundary(byte) {
+            byte -= 1;
+        }
+        let column = line[..byte].chars().count();
+        let start = column.saturating_sub(40);
+        Some((line.chars().skip(start).take(80).collect(), column - start))
+    }
+
+    /// A hint on how to fix the input, if hatchling can guess one.
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            Error::UnsupportedLayout {
+                looks_like: Some(looks_like),
+                location,
+            } => Some(match (looks_like, location.input) {
+                (LooksLike::Empty, _) => "the file is empty; check that the download finished",
+                (LooksLike::HtmlExport, Input::Friends(_)) => {
+                    "this page links to no friends; save facebook.com/<you>/friends once it has scrolled to the end"
+                }
+                (LooksLike::HtmlExport, _) => {
+                    "this looks like an HTML export; request your information again with Format: JSON"
+                }
+                (LooksLike::FriendsFile, Input::Profile) => {
+                    "this looks like a friends file passed as the profile; pass it with --friends instead"
+                }
+                (LooksLike::ProfileFile, Input::Friends(_)) => {
+                    "this looks like profile_information.json passed as the friends file"
+                }
+                _ => return None,
+            }),
+            Error::MissingField { .. } | Error::InvalidValue { .. } => {
+                Some("convert leniently (without --strict) to skip the parts that cannot be read")
+            }
+            Error::Syntax { message, .. } if message.starts_with("EOF") => {
+                Some("the file ends unexpectedly; it may have been cut off during download")
+            }
+            Error::MissingProfile => Some("pass the contents of profile_information.json"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(location) = self.location() {
+            match &location.file {
+                Some(file) => write!(f, "{}", file)?,
+                None => write!(f, "{}", location.input)?,
+            }
+            if location.line > 0 {
+                write!(f, ":{}:{}", location.line, location.column)?;
+            }
+            f.write_str(": ")?;
+        }
+        match self {
+            Error::Syntax { message, .. } => write!(f, "invalid JSON: {}", message),
+            Error::MissingField { location, field } => {
+                write!(f, "missing field `{}` in {}", field, location.path)
+            }
+            Error::InvalidValue { location, message } => {
+                write!(f, "invalid value at {}: {}", location.path, message)
+            }
+            Error::UnsupportedLayout { location, .. } => {
+                write!(f, "not a supported Facebook export layout ({})", location.input)
+            }
+            Error::MissingProfile => f.write_str("no profile information to convert"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Deserializes `json`, turning serde's error into an `Error` with the JSON
+/// path of the failing value.
+pub(crate) fn from_json<T: DeserializeOwned>(json: &str, input: Input) -> Result<T, Error> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    serde_path_to_error::deserialize(&mut deserializer).map_err(|err| {
+        let path = err.path().to_string();
+        let inner = err.into_inner();
+        let location = Location {
+            input,
+            file: None,
+            path,
+            line: inner.line(),
+            column: inner.column(),
+        };
+        let message = strip_position(&inner.to_string());
+        match inner.classify() {
+            serde_json::error::Category::Data => match missing_field(&message) {
+                Some(field) => Error::MissingField { location, field },
+                None => Error::InvalidValue { location, message },
+            },
+            _ => Error::Syntax { location, message },
+        }
+    })
+}
+
+/// Parses `json` far enough to tell what kind of file it is.
+pub(crate) fn looks_like(json: &str) -> Option<LooksLike> {
+    let trimmed = json.trim_start();
+    if trimmed.is_empty() {
+        return Some(LooksLike::Empty);
+    }
+    if trimmed.starts_with('<') {
+        return Some(LooksLike::HtmlExport);
+    }
+    match serde_json::from_str::<Value>(json).ok()? {
+        Value::Array(_) => Some(LooksLike::FriendsFile),
+        Value::Object(map) if map.contains_key("friends_v2") || map.contains_key("friends") => {
+            Some(LooksLike::FriendsFile)
+        }
+        Value::Object(map) if map.contains_key("profile_v2") || map.contains_key("profile") => {
+            Some(LooksLike::ProfileFile)
+        }
+        _ => None,
+    }
+}
+
+/// serde_json appends " at line X column Y", which `Location` already carries.
+fn strip_position(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(i) => message[..i].to_string(),
+        None => message.to_string(),
+    }
+}
+
+fn missing_field(message: &str) -> Option<String> {
+    let rest = message.strip_prefix("missing field `")?;
+    Some(rest[..rest.find('`')?].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_json, looks_like, Error, Input, LooksLike};
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Outer {
+        inner: Vec<Inner>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Inner {
+        name: String,
+    }
+
+    #[test]
+    fn missing_fields_carry_path_and_position() {
+        let err = from_json::<Outer>("{\"inner\": [{\"name\": \"a\"},\n {}]}", Input::Profile)
+            .unwrap_err();
+        match err {
+            Error::MissingField { location, field } => {
+                assert_eq!(field, "name");
+                assert_eq!(location.path, "inner[1]");
+                assert_eq!(location.line, 2);
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn syntax_errors_are_told_apart_from_bad_values() {
+        assert!(matches!(
+            from_json::<Outer>("{\"inner\": [", Input::Profile),
+            Err(Error::Syntax { .. })
+        ));
+        assert!(matches!(
+            from_json::<Outer>("{\"inner\": 3}", Input::Profile),
+            Err(Error::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn excerpt_points_at_the_character_on_non_ascii_lines() {
+        let contents = "{\"inner\": [{\"name\": \"Z\\u00c3\\u00bcrich \u{e9}t\u{e9}\"}, ?]}";
+        let fixed = crate::facebook_parser::fix_facebook_encoding(contents);
+        let err = from_json::<Outer>(&fixed, Input::Profile).unwrap_err();
+        let (excerpt, caret) = err.excerpt(contents).unwrap();
+        assert!(excerpt.contains("Z\\u00fcrich"));
+        assert_eq!(excerpt.chars().nth(caret), Some('?'));
+    }
+
+    #[test]
+    fn looks_like_recognises_common_mix_ups() {
+        assert_eq!(looks_like("<!DOCTYPE html>"), Some(LooksLike::HtmlExport));
+        assert_eq!(looks_like("  "), Some(LooksLike::Empty));
+        assert_eq!(looks_like("{\"friends_v2\": []}"), Some(LooksLike::FriendsFile));
+        assert_eq!(looks_like("{\"profile_v2\": {}}"), Some(LooksLike::ProfileFile));
+    }
+}
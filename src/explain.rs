@@ -0,0 +1,103 @@
+// ---------------------------------------------------------------------------
+// Explain
+// Annotates every triple of a conversion with the place in the export it was
+// read from and the `Profile` method that mapped it, so "why does my card say
+// this?" can be answered from the tool instead of from the source code.
+// ---------------------------------------------------------------------------
+
+use crate::error::Input;
+use crate::profile_builder::{Origin, Profile};
+use serde::Serialize;
+
+/// Why one triple is in the card.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Explanation {
+    /// The triple as written in the Turtle output.
+    pub triple: String,
+    /// File the value was read from; absent for triples every card has.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// JSON pointer into `file`, e.g. `/profile_v2/hometown/name`.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub pointer: String,
+    /// The `Profile` method that produced the triple.
+    pub method: String,
+}
+
+/// Explains each triple of `profile`, in output order. `files` names the
+/// inputs; inputs without a name are described by their kind.
+pub fn explain(profile: &Profile, files: &[(Input, &str)]) -> Vec<Explanation> {
+    let graph = profile.graph();
+    graph
+        .triples()
+        .iter()
+        .zip(profile.origins())
+        .map(|(triple, origin)| Explanation {
+            triple: graph.format_triple(triple),
+            file: origin.input.map(|input| file_name(input, files)),
+            pointer: origin.pointer.clone(),
+            method: format!("Profile::{}", origin.setter),
+        })
+        .collect()
+}
+
+/// The Turtle output with a comment above each group of triples that share
+/// an origin.
+pub fn annotated_turtle(profile: &Profile, files: &[(Input, &str)]) -> String {
+    let graph = profile.graph();
+    let mut out = String::new();
+    for ns in graph.namespaces() {
+        out.push_str(&format!("@prefix {}: <{}> .\n", ns.prefix, ns.iri));
+    }
+    let mut previous: Option<&Origin> = None;
+    for (triple, origin) in graph.triples().iter().zip(profile.origins()) {
+        if previous != Some(origin) {
+            out.push('\n');
+            match origin.input {
+                Some(input) => out.push_str(&format!(
+                    "# {} {} via Profile::{}\n",
+                    file_name(input, files),
+                    origin.pointer,
+                    origin.setter
+                )),
+                None => out.push_str(&format!("# every card, via Profile::{}\n", origin.setter)),
+            }
+            previous = Some(origin);
+        }
+        out.push_str(&graph.format_triple(triple));
+        out.push('\n');
+    }
+    out
+}
+
+fn file_name(input: Input, files: &[(Input, &str)]) -> String {
+    files
+        .iter()
+        .find(|(named, _)| *named == input)
+        .map_or_else(|| input.to_string(), |(_, name)| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{annotated_turtle, explain};
+    use crate::error::Input;
+    use crate::profile_builder::Profile;
+
+    #[test]
+    fn triples_are_traced_to_their_pointer_and_method() {
+        let mut profile = Profile::new();
+        profile.set_source("hometown.name");
+        profile.set_location(Input::Profile, "/profile_v2/hometown/name");
+        profile.add_birth_place("Eugene");
+
+        let explained = explain(&profile, &[(Input::Profile, "profile_information.json")]);
+        let last = explained.last().unwrap();
+        assert_eq!(last.file.as_deref(), Some("profile_information.json"));
+        assert_eq!(last.pointer, "/profile_v2/hometown/name");
+        assert_eq!(last.method, "Profile::add_birth_place");
+        assert_eq!(explained[0].file, None);
+
+        let turtle = annotated_turtle(&profile, &[]);
+        assert!(turtle.contains("# profile information /profile_v2/hometown/name via Profile::add_birth_place\n"));
+    }
+}
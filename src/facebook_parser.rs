@@ -3,125 +3,1135 @@
 // This is a known proc-macro expansion artefact, not a real code issue.
 #![allow(non_local_definitions)]
 
+use crate::convert::Warning;
+use crate::error::{from_json, looks_like, Error, Input, LooksLike, Location};
+use crate::friends_page;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
-use serde_json::Value;
-use std::io;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+// --- Export versions ---
+
+/// Generation of a profile_information.json, named after its top-level key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfileVersion {
+    /// `{"profile": {...}}`, used until 2020.
+    V1,
+    /// `{"profile_v2": {...}}`
+    V2,
+}
+
+impl ProfileVersion {
+    pub fn detect(value: &Value) -> Option<ProfileVersion> {
+        let map = value.as_object()?;
+        if map.contains_key("profile_v2") {
+            Some(ProfileVersion::V2)
+        } else if map.contains_key("profile") {
+            Some(ProfileVersion::V1)
+        } else {
+            None
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            ProfileVersion::V1 => "profile",
+            ProfileVersion::V2 => "profile_v2",
+        }
+    }
+}
+
+impl fmt::Display for ProfileVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+/// Layout of a friends file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FriendsVersion {
+    /// Older official DYI format: `{"friends": [...]}`
+    V1,
+    /// Current official DYI format: `{"friends_v2": [...]}`
+    V2,
+    /// Browser-scraping format: `[{name, target}, ...]`
+    Scraped,
+    /// A "Save Page As" copy of `facebook.com/<user>/friends`
+    SavedPage,
+}
+
+impl FriendsVersion {
+    pub fn detect(value: &Value) -> Option<FriendsVersion> {
+        match value {
+            Value::Object(map) if map.contains_key("friends_v2") => Some(FriendsVersion::V2),
+            Value::Object(map) if map.contains_key("friends") => Some(FriendsVersion::V1),
+            Value::Array(_) => Some(FriendsVersion::Scraped),
+            _ => None,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            FriendsVersion::V1 => "friends",
+            FriendsVersion::V2 => "friends_v2",
+            FriendsVersion::Scraped | FriendsVersion::SavedPage => "",
+        }
+    }
+}
+
+impl fmt::Display for FriendsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FriendsVersion::Scraped => f.write_str("scraped"),
+            FriendsVersion::SavedPage => f.write_str("saved page"),
+            other => f.write_str(other.key()),
+        }
+    }
+}
 
 // --- Friends ---
 
-#[derive(Deserialize)]
+/// A friend as hatchling uses it, whichever file it came from.
+#[derive(Debug, Default)]
 pub struct FBFriend {
-    #[serde(default)]
     pub name: String,
-    /// Only present in the legacy browser-scraping format.
-    #[serde(default)]
+    /// Only present in the browser-scraping format and saved pages.
     pub target: String,
-    /// Present in the official "Download Your Information" export (friends_v2).
-    #[serde(default)]
+    /// Only present in the official "Download Your Information" export.
     pub timestamp: u64,
 }
 
+/// An entry of the official export, in both `friends` and `friends_v2`.
+#[derive(Deserialize)]
+struct DyiFriend {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    timestamp: u64,
+}
+
+#[derive(Deserialize)]
+struct ScrapedFriend {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    target: String,
+}
+
+impl From<DyiFriend> for FBFriend {
+    fn from(friend: DyiFriend) -> FBFriend {
+        FBFriend {
+            name: friend.name,
+            timestamp: friend.timestamp,
+            ..FBFriend::default()
+        }
+    }
+}
+
+impl From<ScrapedFriend> for FBFriend {
+    fn from(friend: ScrapedFriend) -> FBFriend {
+        FBFriend {
+            name: friend.name,
+            target: friend.target,
+            ..FBFriend::default()
+        }
+    }
+}
+
 #[derive(Deserialize)]
 struct FriendsV2Wrapper {
-    friends_v2: Vec<FBFriend>,
+    friends_v2: Vec<DyiFriend>,
 }
 
 #[derive(Deserialize)]
 struct FriendsWrapper {
-    friends: Vec<FBFriend>,
+    friends: Vec<DyiFriend>,
 }
 
-pub struct FBFriends;
+pub struct FBFriends {
+    pub version: FriendsVersion,
+    pub friends: Vec<FBFriend>,
+}
 
 impl FBFriends {
-    pub fn new(contents: &str) -> Result<Vec<FBFriend>, io::Error> {
+    pub fn new(contents: &str) -> Result<FBFriends, Error> {
+        if let Some(friends) = FBFriends::saved_page(contents) {
+            return Ok(friends);
+        }
         let fixed = fix_facebook_encoding(contents);
-        // Try current official DYI format: {"friends_v2": [...]}
-        if let Ok(w) = serde_json::from_str::<FriendsV2Wrapper>(&fixed) {
-            return Ok(w.friends_v2);
+        let input = Input::Friends(0);
+        let value: Value = serde_json::from_str(&fixed).map_err(|_| unreadable(&fixed, input))?;
+        let version = FriendsVersion::detect(&value).ok_or_else(|| unsupported(&fixed, input))?;
+        let friends = match version {
+            FriendsVersion::V2 => normalize(from_json::<FriendsV2Wrapper>(&fixed, input)?.friends_v2),
+            FriendsVersion::V1 => normalize(from_json::<FriendsWrapper>(&fixed, input)?.friends),
+            FriendsVersion::Scraped => normalize(from_json::<Vec<ScrapedFriend>>(&fixed, input)?),
+            FriendsVersion::SavedPage => unreachable!("saved pages are not JSON"),
+        };
+        Ok(FBFriends { version, friends })
+    }
+
+    /// Detects the layout of a friends file without reading the friends.
+    pub fn version(contents: &str) -> Option<FriendsVersion> {
+        if let Some(friends) = FBFriends::saved_page(contents) {
+            return Some(friends.version);
         }
-        // Try older official DYI format: {"friends": [...]}
-        if let Ok(w) = serde_json::from_str::<FriendsWrapper>(&fixed) {
-            return Ok(w.friends);
+        FriendsVersion::detect(&serde_json::from_str(contents).ok()?)
+    }
+
+    /// Like `new`, but friends that cannot be read are skipped and reported in
+    /// `warnings` instead of failing the whole file.
+    pub fn new_lenient(contents: &str, warnings: &mut Vec<Warning>) -> Result<FBFriends, Error> {
+        if let Some(friends) = FBFriends::saved_page(contents) {
+            return Ok(friends);
+        }
+        let fixed = fix_facebook_encoding(contents);
+        let input = Input::Friends(0);
+        let value: Value = serde_json::from_str(&fixed).map_err(|_| unreadable(&fixed, input))?;
+        let version = FriendsVersion::detect(&value).ok_or_else(|| unsupported(&fixed, input))?;
+        let items = match version {
+            FriendsVersion::Scraped => &value,
+            _ => &value[version.key()],
+        };
+        let Value::Array(items) = items else {
+            return Err(unsupported(&fixed, input));
+        };
+        let friends = match version {
+            FriendsVersion::Scraped => salvage_entries::<ScrapedFriend, _>(version.key(), items, warnings),
+            _ => salvage_entries::<DyiFriend, _>(version.key(), items, warnings),
+        };
+        Ok(FBFriends { version, friends })
+    }
+
+    /// The friends linked from a saved friends page, if `contents` is one.
+    fn saved_page(contents: &str) -> Option<FBFriends> {
+        if looks_like(contents) != Some(LooksLike::HtmlExport) {
+            return None;
         }
-        // Fall back to the legacy browser-scraping format: [{name, target}, ...]
-        let p: Vec<FBFriend> = serde_json::from_str(&fixed)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        Ok(p)
+        let friends = friends_page::scrape(contents);
+        (!friends.is_empty()).then_some(FBFriends {
+            version: FriendsVersion::SavedPage,
+            friends,
+        })
+    }
+}
+
+/// Keeps the entries of a list that read as `T`.
+fn salvage_entries<T: DeserializeOwned + Into<U>, U>(
+    key: &str,
+    items: &[Value],
+    warnings: &mut Vec<Warning>,
+) -> Vec<U> {
+    salvage_list(key, items, &accepts::<T>, warnings)
+        .into_iter()
+        .filter_map(|item| serde_json::from_value::<T>(item).ok())
+        .map(Into::into)
+        .collect()
+}
+
+fn normalize<T: Into<U>, U>(items: Vec<T>) -> Vec<U> {
+    items.into_iter().map(Into::into).collect()
+}
+
+// --- Connections ---
+
+/// The other lists in the export's `connections` folder. Every generation
+/// lists people the same way as the friends file, `{name, timestamp}`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionList {
+    Followers,
+    Following,
+    SentRequests,
+    ReceivedRequests,
+    Removed,
+}
+
+/// Top-level keys of each list, newest generation first.
+const CONNECTION_KEYS: &[(&str, ConnectionList)] = &[
+    ("followers_v2", ConnectionList::Followers),
+    ("followers", ConnectionList::Followers),
+    ("following_v3", ConnectionList::Following),
+    ("following_v2", ConnectionList::Following),
+    ("following", ConnectionList::Following),
+    ("sent_requests_v2", ConnectionList::SentRequests),
+    ("sent_requests", ConnectionList::SentRequests),
+    ("received_requests_v2", ConnectionList::ReceivedRequests),
+    ("received_requests", ConnectionList::ReceivedRequests),
+    ("deleted_friends_v2", ConnectionList::Removed),
+    ("deleted_friends", ConnectionList::Removed),
+];
+
+impl ConnectionList {
+    /// Finds the list in a connections file, returning it with its key.
+    pub fn detect(value: &Value) -> Option<(ConnectionList, &'static str)> {
+        let Value::Object(map) = value else {
+            return None;
+        };
+        CONNECTION_KEYS
+            .iter()
+            .find(|(key, _)| map.contains_key(*key))
+            .map(|(key, list)| (*list, *key))
+    }
+
+    /// Pending requests and removed friends are nobody else's business.
+    pub fn is_private(self) -> bool {
+        matches!(
+            self,
+            ConnectionList::SentRequests | ConnectionList::ReceivedRequests | ConnectionList::Removed
+        )
+    }
+}
+
+impl fmt::Display for ConnectionList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ConnectionList::Followers => "followers",
+            ConnectionList::Following => "following",
+            ConnectionList::SentRequests => "sent friend requests",
+            ConnectionList::ReceivedRequests => "received friend requests",
+            ConnectionList::Removed => "removed friends",
+        })
+    }
+}
+
+pub struct FBConnections {
+    pub list: ConnectionList,
+    /// The top-level key the list was found under, e.g. `following_v3`.
+    pub key: &'static str,
+    /// Only `name` and `timestamp` are filled in.
+    pub people: Vec<FBFriend>,
+}
+
+impl FBConnections {
+    pub fn new(contents: &str) -> Result<FBConnections, Error> {
+        let fixed = fix_facebook_encoding(contents);
+        let input = Input::Connections(0);
+        let value: Value = serde_json::from_str(&fixed).map_err(|_| unreadable(&fixed, input))?;
+        let (list, key) = ConnectionList::detect(&value).ok_or_else(|| unsupported(&fixed, input))?;
+        let mut lists = from_json::<HashMap<String, Vec<DyiFriend>>>(&fixed, input)?;
+        let people = normalize(lists.remove(key).unwrap_or_default());
+        Ok(FBConnections { list, key, people })
+    }
+
+    /// Detects which list a connections file holds, and its key.
+    pub fn detect(contents: &str) -> Option<(ConnectionList, &'static str)> {
+        ConnectionList::detect(&serde_json::from_str(contents).ok()?)
+    }
+
+    /// Like `new`, but entries that cannot be read are skipped and reported in
+    /// `warnings`.
+    pub fn new_lenient(contents: &str, warnings: &mut Vec<Warning>) -> Result<FBConnections, Error> {
+        let fixed = fix_facebook_encoding(contents);
+        let input = Input::Connections(0);
+        let value: Value = serde_json::from_str(&fixed).map_err(|_| unreadable(&fixed, input))?;
+        let (list, key) = ConnectionList::detect(&value).ok_or_else(|| unsupported(&fixed, input))?;
+        let Value::Array(items) = &value[key] else {
+            return Err(unsupported(&fixed, input));
+        };
+        let people = salvage_entries::<DyiFriend, _>(key, items, warnings);
+        Ok(FBConnections { list, key, people })
+    }
+}
+
+// --- Friend lists ---
+
+/// A list the user sorted friends into, such as Close Friends or a custom list.
+#[derive(Debug, Default)]
+pub struct FBFriendList {
+    pub name: String,
+    /// Names of the friends on the list.
+    pub members: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RawFriendList {
+    #[serde(default)]
+    name: String,
+    // Older exports call the members `data`
+    #[serde(default, alias = "data")]
+    members: Vec<RawListMember>,
+}
+
+#[derive(Deserialize)]
+struct RawListMember {
+    #[serde(default)]
+    name: String,
+}
+
+impl From<RawFriendList> for FBFriendList {
+    fn from(list: RawFriendList) -> FBFriendList {
+        FBFriendList {
+            name: list.name,
+            members: list.members.into_iter().map(|member| member.name).collect(),
+        }
+    }
+}
+
+/// Top-level keys of the friend lists file, newest generation first.
+const FRIEND_LIST_KEYS: &[&str] = &["friend_lists_v2", "friend_lists"];
+
+pub struct FBFriendLists {
+    /// The top-level key the lists were found under, e.g. `friend_lists_v2`.
+    pub key: &'static str,
+    pub lists: Vec<FBFriendList>,
+}
+
+impl FBFriendLists {
+    pub fn new(contents: &str) -> Result<FBFriendLists, Error> {
+        let fixed = fix_facebook_encoding(contents);
+        let input = Input::Connections(0);
+        let value: Value = serde_json::from_str(&fixed).map_err(|_| unreadable(&fixed, input))?;
+        let key = FBFriendLists::key(&value).ok_or_else(|| unsupported(&fixed, input))?;
+        let mut lists = from_json::<HashMap<String, Vec<RawFriendList>>>(&fixed, input)?;
+        let lists = normalize(lists.remove(key).unwrap_or_default());
+        Ok(FBFriendLists { key, lists })
+    }
+
+    /// Detects a friend lists file, returning its top-level key.
+    pub fn detect(contents: &str) -> Option<&'static str> {
+        FBFriendLists::key(&serde_json::from_str(contents).ok()?)
+    }
+
+    /// Like `new`, but lists that cannot be read are skipped and reported in
+    /// `warnings`.
+    pub fn new_lenient(contents: &str, warnings: &mut Vec<Warning>) -> Result<FBFriendLists, Error> {
+        let fixed = fix_facebook_encoding(contents);
+        let input = Input::Connections(0);
+        let value: Value = serde_json::from_str(&fixed).map_err(|_| unreadable(&fixed, input))?;
+        let key = FBFriendLists::key(&value).ok_or_else(|| unsupported(&fixed, input))?;
+        let Value::Array(items) = &value[key] else {
+            return Err(unsupported(&fixed, input));
+        };
+        let lists = salvage_entries::<RawFriendList, _>(key, items, warnings);
+        Ok(FBFriendLists { key, lists })
+    }
+
+    fn key(value: &Value) -> Option<&'static str> {
+        let Value::Object(map) = value else {
+            return None;
+        };
+        FRIEND_LIST_KEYS.iter().find(|key| map.contains_key(**key)).copied()
+    }
+}
+
+// --- Posts ---
+
+/// A post from `your_posts_*.json`: a status update, a shared link, a check-in
+/// or any mix of them.
+#[derive(Clone, Debug, Default)]
+pub struct FBPost {
+    pub timestamp: u64,
+    /// What the user wrote; empty for posts that only share something.
+    pub text: String,
+    /// Facebook's summary line, e.g. "Jane Marie Doe shared a link."
+    pub title: String,
+    /// Links the post shares.
+    pub urls: Vec<String>,
+    pub places: Vec<FBPlace>,
+    /// Names of the people tagged in the post.
+    pub tags: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FBPlace {
+    pub name: String,
+    pub address: String,
+    /// The place's Facebook page, if it has one.
+    pub url: String,
+    /// Latitude and longitude.
+    pub coordinate: Option<(f64, f64)>,
+}
+
+#[derive(Deserialize)]
+struct RawPost {
+    #[serde(default)]
+    timestamp: u64,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    data: Vec<RawPostData>,
+    #[serde(default)]
+    attachments: Vec<RawAttachment>,
+    #[serde(default)]
+    tags: Vec<RawTag>,
+}
+
+/// One of the entries of a post's `data`; besides the text they hold update
+/// and backdating timestamps.
+#[derive(Deserialize)]
+struct RawPostData {
+    #[serde(default)]
+    post: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawAttachment {
+    #[serde(default)]
+    data: Vec<RawAttachmentData>,
+}
+
+#[derive(Deserialize)]
+struct RawAttachmentData {
+    #[serde(default)]
+    external_context: Option<RawExternalContext>,
+    #[serde(default)]
+    place: Option<RawPlace>,
+}
+
+#[derive(Deserialize)]
+struct RawExternalContext {
+    #[serde(default)]
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct RawPlace {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    address: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    coordinate: Option<RawCoordinate>,
+}
+
+#[derive(Deserialize)]
+struct RawCoordinate {
+    latitude: f64,
+    longitude: f64,
+}
+
+/// Tagged people are objects in current exports and plain names in old ones.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawTag {
+    Person { name: String },
+    Name(String),
+}
+
+impl From<RawPost> for FBPost {
+    fn from(post: RawPost) -> FBPost {
+        let attached = || post.attachments.iter().flat_map(|attachment| &attachment.data);
+        FBPost {
+            timestamp: post.timestamp,
+            text: post
+                .data
+                .iter()
+                .filter_map(|data| data.post.as_deref())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            urls: attached()
+                .filter_map(|data| data.external_context.as_ref())
+                .map(|context| context.url.clone())
+                .filter(|url| !url.is_empty())
+                .collect(),
+            places: attached()
+                .filter_map(|data| data.place.as_ref())
+                .map(|place| FBPlace {
+                    name: place.name.clone(),
+                    address: place.address.clone(),
+                    url: place.url.clone(),
+                    coordinate: place.coordinate.as_ref().map(|c| (c.latitude, c.longitude)),
+                })
+                .collect(),
+            tags: post
+                .tags
+                .into_iter()
+                .map(|tag| match tag {
+                    RawTag::Person { name } | RawTag::Name(name) => name,
+                })
+                .collect(),
+            title: post.title,
+        }
+    }
+}
+
+pub struct FBPosts {
+    pub posts: Vec<FBPost>,
+}
+
+impl FBPosts {
+    pub fn new(contents: &str) -> Result<FBPosts, Error> {
+        let fixed = fix_facebook_encoding(contents);
+        let input = Input::Posts(0);
+        let value: Value = serde_json::from_str(&fixed).map_err(|_| unreadable(&fixed, input))?;
+        if !value.is_array() {
+            return Err(unsupported(&fixed, input));
+        }
+        let posts = normalize(from_json::<Vec<RawPost>>(&fixed, input)?);
+        Ok(FBPosts { posts })
+    }
+
+    /// Like `new`, but posts that cannot be read are skipped and reported in
+    /// `warnings`.
+    pub fn new_lenient(contents: &str, warnings: &mut Vec<Warning>) -> Result<FBPosts, Error> {
+        let fixed = fix_facebook_encoding(contents);
+        let input = Input::Posts(0);
+        let value: Value = serde_json::from_str(&fixed).map_err(|_| unreadable(&fixed, input))?;
+        let Value::Array(items) = &value else {
+            return Err(unsupported(&fixed, input));
+        };
+        let posts = salvage_entries::<RawPost, _>("your_posts", items, warnings);
+        Ok(FBPosts { posts })
+    }
+}
+
+// --- Photos ---
+
+/// EXIF fields of `media_metadata` worth keeping. Others, such as the IP
+/// address the photo was uploaded from, are left out.
+const EXIF_KEYS: &[&str] = &[
+    "camera_make",
+    "camera_model",
+    "exposure",
+    "f_stop",
+    "focal_length",
+    "iso_speed",
+    "orientation",
+    "original_height",
+    "original_width",
+];
+
+/// An album from `photos_and_videos/album/*.json`.
+#[derive(Clone, Debug, Default)]
+pub struct FBAlbum {
+    pub name: String,
+    pub description: String,
+    pub last_modified: u64,
+    pub photos: Vec<FBPhoto>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FBPhoto {
+    /// Path of the media file, relative to the root of the export.
+    pub uri: String,
+    pub creation_timestamp: u64,
+    /// The caption.
+    pub description: String,
+    /// When the photo was taken, according to its EXIF data.
+    pub taken_timestamp: Option<u64>,
+    /// Where the photo was taken: latitude and longitude.
+    pub coordinate: Option<(f64, f64)>,
+    /// The `EXIF_KEYS` present, with their values as text.
+    pub exif: Vec<(String, String)>,
+}
+
+#[derive(Deserialize)]
+struct RawAlbum {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    last_modified_timestamp: u64,
+    #[serde(default)]
+    photos: Vec<RawPhoto>,
+}
+
+#[derive(Deserialize)]
+struct RawPhoto {
+    #[serde(default)]
+    uri: String,
+    #[serde(default)]
+    creation_timestamp: u64,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    media_metadata: Option<RawMediaMetadata>,
+}
+
+#[derive(Deserialize)]
+struct RawMediaMetadata {
+    #[serde(default)]
+    photo_metadata: Option<RawPhotoMetadata>,
+}
+
+/// Current exports list the EXIF data under `exif_data`, older ones put the
+/// fields straight into `photo_metadata`.
+#[derive(Deserialize)]
+struct RawPhotoMetadata {
+    #[serde(default)]
+    exif_data: Vec<Map<String, Value>>,
+    #[serde(flatten)]
+    fields: Map<String, Value>,
+}
+
+impl From<RawPhoto> for FBPhoto {
+    fn from(photo: RawPhoto) -> FBPhoto {
+        let mut exif = Map::new();
+        if let Some(metadata) = photo.media_metadata.and_then(|m| m.photo_metadata) {
+            exif.extend(metadata.fields);
+            metadata.exif_data.into_iter().for_each(|data| exif.extend(data));
+        }
+        let number = |key: &str| exif.get(key).and_then(Value::as_f64);
+        FBPhoto {
+            uri: photo.uri,
+            creation_timestamp: photo.creation_timestamp,
+            description: photo.description,
+            taken_timestamp: exif.get("taken_timestamp").and_then(Value::as_u64),
+            coordinate: number("latitude").zip(number("longitude")),
+            exif: EXIF_KEYS
+                .iter()
+                .filter_map(|key| match exif.get(*key)? {
+                    Value::String(value) if !value.is_empty() => Some((key.to_string(), value.clone())),
+                    Value::Number(value) => Some((key.to_string(), value.to_string())),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<RawAlbum> for FBAlbum {
+    fn from(album: RawAlbum) -> FBAlbum {
+        FBAlbum {
+            name: album.name,
+            description: album.description,
+            last_modified: album.last_modified_timestamp,
+            photos: normalize(album.photos),
+        }
+    }
+}
+
+impl FBAlbum {
+    pub fn new(contents: &str) -> Result<FBAlbum, Error> {
+        let fixed = fix_facebook_encoding(contents);
+        let input = Input::Album(0);
+        let value: Value = serde_json::from_str(&fixed).map_err(|_| unreadable(&fixed, input))?;
+        if !value["photos"].is_array() {
+            return Err(unsupported(&fixed, input));
+        }
+        Ok(from_json::<RawAlbum>(&fixed, input)?.into())
+    }
+
+    /// Like `new`, but photos that cannot be read are skipped and reported in
+    /// `warnings`.
+    pub fn new_lenient(contents: &str, warnings: &mut Vec<Warning>) -> Result<FBAlbum, Error> {
+        let fixed = fix_facebook_encoding(contents);
+        let input = Input::Album(0);
+        let mut value: Value = serde_json::from_str(&fixed).map_err(|_| unreadable(&fixed, input))?;
+        let Value::Array(items) = value["photos"].take() else {
+            return Err(unsupported(&fixed, input));
+        };
+        let photos = salvage_entries::<RawPhoto, _>("photos", &items, warnings);
+        let text = |key: &str| value[key].as_str().unwrap_or_default().to_string();
+        Ok(FBAlbum {
+            name: text("name"),
+            description: text("description"),
+            last_modified: value["last_modified_timestamp"].as_u64().unwrap_or_default(),
+            photos,
+        })
     }
 }
 
 // --- Profile ---
 
-#[derive(Deserialize, Debug)]
 pub struct FBProfileInformation {
-    /// Accepts both the old "profile" key and the current "profile_v2" key.
-    #[serde(alias = "profile_v2")]
+    pub version: ProfileVersion,
     pub profile: Profile,
 }
 
+#[derive(Deserialize)]
+struct ProfileV1Wrapper {
+    profile: ProfileV1,
+}
+
+#[derive(Deserialize)]
+struct ProfileV2Wrapper {
+    profile_v2: ProfileV2,
+}
+
 impl FBProfileInformation {
-    pub fn new(contents: &str) -> Result<FBProfileInformation, io::Error> {
+    pub fn new(contents: &str) -> Result<FBProfileInformation, Error> {
         let fixed = fix_facebook_encoding(contents);
-        let p: FBProfileInformation = serde_json::from_str(&fixed)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        Ok(p)
+        let value: Value =
+            serde_json::from_str(&fixed).map_err(|_| unreadable(&fixed, Input::Profile))?;
+        let version =
+            ProfileVersion::detect(&value).ok_or_else(|| unsupported(&fixed, Input::Profile))?;
+        let profile = match version {
+            ProfileVersion::V1 => from_json::<ProfileV1Wrapper>(&fixed, Input::Profile)?.profile.into(),
+            ProfileVersion::V2 => from_json::<ProfileV2Wrapper>(&fixed, Input::Profile)?.profile_v2.into(),
+        };
+        Ok(FBProfileInformation { version, profile })
+    }
+
+    /// Best-effort parsing: sections and list entries that do not deserialize
+    /// are coerced where only a value's type is off, and otherwise skipped.
+    /// Everything that was changed or dropped is reported in `warnings`.
+    pub fn new_lenient(
+        contents: &str,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<FBProfileInformation, Error> {
+        let fixed = fix_facebook_encoding(contents);
+        let value: Value =
+            serde_json::from_str(&fixed).map_err(|_| unreadable(&fixed, Input::Profile))?;
+        let version =
+            ProfileVersion::detect(&value).ok_or_else(|| unsupported(&fixed, Input::Profile))?;
+        let fields = value[version.key()]
+            .as_object()
+            .ok_or_else(|| unsupported(&fixed, Input::Profile))?;
+        let profile = match version {
+            ProfileVersion::V1 => salvage_profile::<ProfileV1>(fields, warnings)?.into(),
+            ProfileVersion::V2 => salvage_profile::<ProfileV2>(fields, warnings)?.into(),
+        };
+        Ok(FBProfileInformation { version, profile })
     }
 }
 
-#[derive(Deserialize, Debug)]
-pub struct Profile {
-    pub name: Name,
-    pub emails: Emails,
+/// Sections every profile must have; `new` fails without them.
+const REQUIRED_SECTIONS: &[&str] = &["name", "emails"];
+
+/// Keeps the sections of a profile that read as `P`. Required sections that
+/// are missing or skipped are converted as empty.
+fn salvage_profile<P: DeserializeOwned>(
+    fields: &Map<String, Value>,
+    warnings: &mut Vec<Warning>,
+) -> Result<P, Error> {
+    let empty: Map<String, Value> = REQUIRED_SECTIONS
+        .iter()
+        .map(|section| (section.to_string(), json!({})))
+        .collect();
+    let mut kept = Map::new();
+    for (field, value) in fields {
+        let accepts_field = |v: &Value| {
+            let mut section = empty.clone();
+            section.insert(field.clone(), v.clone());
+            accepts::<P>(&Value::Object(section))
+        };
+        if let Some(value) = salvage(field, value, &accepts_field, warnings) {
+            kept.insert(field.clone(), value);
+        }
+    }
+    for section in REQUIRED_SECTIONS {
+        if !kept.contains_key(*section) {
+            if !fields.contains_key(*section) {
+                warnings.push(Warning {
+                    field: section.to_string(),
+                    message: "missing section; converted as empty".to_string(),
+                });
+            }
+            kept.insert(section.to_string(), json!({}));
+        }
+    }
+    if let Some(Value::Array(schools)) = kept.get("education_experiences") {
+        check_school_types(schools, warnings);
+    }
+    serde_json::from_value(Value::Object(kept)).map_err(|err| Error::InvalidValue {
+        location: Location {
+            input: Input::Profile,
+            file: None,
+            path: ".".to_string(),
+            line: 0,
+            column: 0,
+        },
+        message: err.to_string(),
+    })
+}
+
+/// An error for JSON that is not an export layout we know.
+fn unsupported(json: &str, input: Input) -> Error {
+    Error::UnsupportedLayout {
+        location: Location {
+            input,
+            file: None,
+            path: ".".to_string(),
+            line: 0,
+            column: 0,
+        },
+        looks_like: looks_like(json),
+    }
+}
+
+/// An error for input that does not parse as JSON: either something that is
+/// recognisably the wrong kind of file, or a plain syntax error.
+fn unreadable(json: &str, input: Input) -> Error {
+    match looks_like(json) {
+        Some(LooksLike::Empty | LooksLike::HtmlExport) => unsupported(json, input),
+        _ => match from_json::<Value>(json, input) {
+            Err(err) => err,
+            Ok(_) => unsupported(json, input),
+        },
+    }
+}
+
+/// The profile section in the shape of one export generation. Only the
+/// fields whose layout changed between generations are type parameters.
+#[derive(Deserialize)]
+struct RawProfile<Work, Language, Bio> {
+    name: Name,
+    emails: Emails,
     #[serde(default)]
-    pub birthday: Date,
+    birthday: Date,
     #[serde(default)]
-    pub gender: Gender,
+    gender: Gender,
     #[serde(default)]
-    pub previous_names: Vec<Value>,
+    previous_names: Vec<TimestampedString>,
     #[serde(default)]
-    pub current_city: TimestampedString,
+    current_city: TimestampedString,
     #[serde(default)]
-    pub hometown: TimestampedString,
+    hometown: TimestampedString,
     #[serde(default)]
-    pub relationship: Relationship,
+    relationship: Relationship,
     #[serde(default)]
-    pub family_members: Vec<FamilyMember>,
+    family_members: Vec<FamilyMember>,
     #[serde(default)]
-    pub education_experiences: Vec<EducationExperience>,
-    /// Stored as raw JSON because the field structure changed between export versions.
+    education_experiences: Vec<EducationExperience>,
+    #[serde(default = "Vec::new")]
+    work_experiences: Vec<Work>,
+    #[serde(default = "Vec::new")]
+    languages: Vec<Language>,
     #[serde(default)]
-    pub work_experiences: Vec<Value>,
-    /// Stored as raw JSON because the field structure changed between export versions
-    /// (was Vec<String>, now Vec<{name, timestamp}>).
+    political_view: View,
     #[serde(default)]
-    pub languages: Vec<Value>,
+    religious_view: View,
     #[serde(default)]
-    pub political_view: View,
+    professional_skills: Vec<String>,
     #[serde(default)]
-    pub religious_view: View,
+    address: Address,
     #[serde(default)]
-    pub professional_skills: Vec<String>,
+    phone_numbers: Vec<PhoneNumber>,
     #[serde(default)]
-    pub address: Address,
+    username: String,
     #[serde(default)]
-    pub phone_numbers: Vec<PhoneNumber>,
+    places_lived: Vec<PlaceLived>,
     #[serde(default)]
-    pub username: String,
-    /// Stored as raw JSON because the nested structure changed between export versions.
+    name_pronunciation: String,
     #[serde(default)]
-    pub places_lived: Vec<Value>,
+    profile_uri: String,
     #[serde(default)]
-    pub name_pronunciation: String,
+    intro_bio: Option<Bio>,
+    #[serde(default)]
+    registration_timestamp: u64,
+}
+
+/// The keys `RawProfile` reads, as serde knows them.
+#[cfg(test)]
+pub(crate) fn profile_keys() -> &'static [&'static str] {
+    use serde::de::{self, Deserializer, Visitor};
+
+    /// Records the field names serde asks for, then gives up.
+    struct Keys<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> Deserializer<'de> for Keys<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _: &'static str,
+            fields: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("keys recorded"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut keys: &'static [&'static str] = &[];
+    let _ = ProfileV2::deserialize(Keys(&mut keys));
+    keys
+}
+
+/// `profile`: employers, languages and the intro are plain strings.
+type ProfileV1 = RawProfile<WorkExperienceV1, String, String>;
+
+/// `profile_v2`: employers are objects, and languages and the intro are
+/// timestamped.
+type ProfileV2 = RawProfile<WorkExperienceV2, TimestampedString, TimestampedString>;
+
+#[derive(Deserialize)]
+struct WorkExperienceV1 {
+    #[serde(default)]
+    employer: String,
+    #[serde(flatten)]
+    details: WorkDetails,
+}
+
+#[derive(Deserialize)]
+struct WorkExperienceV2 {
+    #[serde(default)]
+    employer: Employer,
+    #[serde(flatten)]
+    details: WorkDetails,
+}
+
+#[derive(Deserialize, Default)]
+struct Employer {
     #[serde(default)]
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct WorkDetails {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    location: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    start_timestamp: u64,
+    #[serde(default)]
+    end_timestamp: u64,
+}
+
+/// The profile section, normalized across export generations.
+#[derive(Debug, Default)]
+pub struct Profile {
+    pub name: Name,
+    pub emails: Emails,
+    pub birthday: Date,
+    pub gender: Gender,
+    pub previous_names: Vec<TimestampedString>,
+    pub current_city: TimestampedString,
+    pub hometown: TimestampedString,
+    pub relationship: Relationship,
+    pub family_members: Vec<FamilyMember>,
+    pub education_experiences: Vec<EducationExperience>,
+    pub work_experiences: Vec<WorkExperience>,
+    pub languages: Vec<String>,
+    pub political_view: View,
+    pub religious_view: View,
+    pub professional_skills: Vec<String>,
+    pub address: Address,
+    pub phone_numbers: Vec<PhoneNumber>,
+    pub username: String,
+    pub places_lived: Vec<PlaceLived>,
+    pub name_pronunciation: String,
     pub profile_uri: String,
-    /// Stored as raw JSON because the field changed from a plain string to
-    /// an object {name, timestamp} in newer exports.
+    pub intro_bio: String,
+    /// When the Facebook account was created.
+    pub registration_timestamp: u64,
+}
+
+impl Profile {
+    /// The most recent change the profile records a time for, if any.
+    pub fn last_change(&self) -> Option<u64> {
+        let schools = self.education_experiences.iter().flat_map(|school| match school {
+            EducationExperience::HighSchool { start_timestamp, end_timestamp, .. }
+            | EducationExperience::College { start_timestamp, end_timestamp, .. }
+            | EducationExperience::GraduateSchool { start_timestamp, end_timestamp, .. } => {
+                vec![*start_timestamp, *end_timestamp]
+            }
+            EducationExperience::Other => Vec::new(),
+        });
+        let jobs = self
+            .work_experiences
+            .iter()
+            .flat_map(|work| [work.start_timestamp, work.end_timestamp]);
+        [
+            self.current_city.timestamp,
+            self.hometown.timestamp,
+            self.relationship.timestamp,
+        ]
+        .into_iter()
+        .chain(self.previous_names.iter().map(|name| name.timestamp))
+        .chain(self.family_members.iter().map(|member| member.timestamp))
+        .chain(self.places_lived.iter().map(|place| place.start_timestamp))
+        .chain(schools)
+        .chain(jobs)
+        .filter(|timestamp| *timestamp > 0)
+        .max()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct WorkExperience {
+    pub employer: String,
+    pub title: String,
+    pub location: String,
+    pub description: String,
+    pub start_timestamp: u64,
+    pub end_timestamp: u64,
+}
+
+impl WorkExperience {
+    fn new(employer: String, details: WorkDetails) -> WorkExperience {
+        WorkExperience {
+            employer,
+            title: details.title,
+            location: details.location,
+            description: details.description,
+            start_timestamp: details.start_timestamp,
+            end_timestamp: details.end_timestamp,
+        }
+    }
+}
+
+impl From<WorkExperienceV1> for WorkExperience {
+    fn from(work: WorkExperienceV1) -> WorkExperience {
+        WorkExperience::new(work.employer, work.details)
+    }
+}
+
+impl From<WorkExperienceV2> for WorkExperience {
+    fn from(work: WorkExperienceV2) -> WorkExperience {
+        WorkExperience::new(work.employer.name, work.details)
+    }
+}
+
+impl From<TimestampedString> for String {
+    fn from(value: TimestampedString) -> String {
+        value.name
+    }
+}
+
+impl<Work, Language, Bio> From<RawProfile<Work, Language, Bio>> for Profile
+where
+    Work: Into<WorkExperience>,
+    Language: Into<String>,
+    Bio: Into<String>,
+{
+    fn from(raw: RawProfile<Work, Language, Bio>) -> Profile {
+        Profile {
+            name: raw.name,
+            emails: raw.emails,
+            birthday: raw.birthday,
+            gender: raw.gender,
+            previous_names: raw.previous_names,
+            current_city: raw.current_city,
+            hometown: raw.hometown,
+            relationship: raw.relationship,
+            family_members: raw.family_members,
+            education_experiences: raw.education_experiences,
+            work_experiences: normalize(raw.work_experiences),
+            languages: normalize(raw.languages),
+            political_view: raw.political_view,
+            religious_view: raw.religious_view,
+            professional_skills: raw.professional_skills,
+            address: raw.address,
+            phone_numbers: raw.phone_numbers,
+            username: raw.username,
+            places_lived: raw.places_lived,
+            name_pronunciation: raw.name_pronunciation,
+            profile_uri: raw.profile_uri,
+            intro_bio: raw.intro_bio.map(Into::into).unwrap_or_default(),
+            registration_timestamp: raw.registration_timestamp,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct PlaceLived {
+    #[serde(default)]
+    pub place: String,
     #[serde(default)]
-    pub intro_bio: Value,
+    pub start_timestamp: u64,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
 pub struct Name {
     #[serde(default)]
     pub full_name: String,
@@ -133,7 +1143,7 @@ pub struct Name {
     pub last_name: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
 pub struct Emails {
     #[serde(default)]
     pub emails: Vec<String>,
@@ -281,6 +1291,118 @@ pub struct PhoneNumber {
     pub verified: bool,
 }
 
+// --- Lenient parsing ---
+
+const SCHOOL_TYPES: &[&str] = &["High School", "College", "Graduate School"];
+
+/// Checks whether `value` deserializes as `T`, returning serde's message if not.
+fn accepts<T: DeserializeOwned>(value: &Value) -> Result<(), String> {
+    serde_json::from_value::<T>(value.clone())
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+/// Returns `value` if `check` accepts it, a coerced copy if that is accepted,
+/// the readable entries if it is a list, or nothing.
+fn salvage(
+    field: &str,
+    value: &Value,
+    check: &dyn Fn(&Value) -> Result<(), String>,
+    warnings: &mut Vec<Warning>,
+) -> Option<Value> {
+    let err = match check(value) {
+        Ok(()) => return Some(value.clone()),
+        Err(err) => err,
+    };
+    if let Some(coerced) = coerce(value, check) {
+        warnings.push(Warning {
+            field: field.to_string(),
+            message: "converted values of the wrong type (e.g. numbers written as text)"
+                .to_string(),
+        });
+        return Some(coerced);
+    }
+    if let Value::Array(items) = value {
+        let check_item = |item: &Value| check(&Value::Array(vec![item.clone()]));
+        return Some(Value::Array(salvage_list(field, items, &check_item, warnings)));
+    }
+    warnings.push(Warning {
+        field: field.to_string(),
+        message: format!("section skipped: {}", err),
+    });
+    None
+}
+
+/// Keeps the entries of a list that `check` accepts, coercing where possible.
+fn salvage_list(
+    field: &str,
+    items: &[Value],
+    check: &dyn Fn(&Value) -> Result<(), String>,
+    warnings: &mut Vec<Warning>,
+) -> Vec<Value> {
+    let mut kept = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        let path = format!("{}[{}]", field, i);
+        match check(item) {
+            Ok(()) => kept.push(item.clone()),
+            Err(err) => match coerce(item, check) {
+                Some(coerced) => {
+                    warnings.push(Warning {
+                        field: path,
+                        message: "converted values of the wrong type (e.g. numbers written as text)"
+                            .to_string(),
+                    });
+                    kept.push(coerced);
+                }
+                None => warnings.push(Warning {
+                    field: path,
+                    message: format!("entry skipped: {}", err),
+                }),
+            },
+        }
+    }
+    kept
+}
+
+/// Tries turning numeric strings into numbers, and numbers into strings.
+fn coerce(value: &Value, check: &dyn Fn(&Value) -> Result<(), String>) -> Option<Value> {
+    let to_numbers = map_scalars(value, &|v| match v {
+        Value::String(s) => s.trim().parse::<u64>().map(Value::from).unwrap_or(v.clone()),
+        _ => v.clone(),
+    });
+    let to_strings = map_scalars(value, &|v| match v {
+        Value::Number(n) => Value::String(n.to_string()),
+        _ => v.clone(),
+    });
+    [to_numbers, to_strings]
+        .into_iter()
+        .find(|candidate| candidate != value && check(candidate).is_ok())
+}
+
+fn map_scalars(value: &Value, f: &dyn Fn(&Value) -> Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.iter().map(|v| map_scalars(v, f)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), map_scalars(v, f)))
+                .collect(),
+        ),
+        scalar => f(scalar),
+    }
+}
+
+fn check_school_types(schools: &[Value], warnings: &mut Vec<Warning>) {
+    for (i, school) in schools.iter().enumerate() {
+        let school_type = school.get("school_type").and_then(Value::as_str).unwrap_or("");
+        if !SCHOOL_TYPES.contains(&school_type) {
+            warnings.push(Warning {
+                field: format!("education_experiences[{}].school_type", i),
+                message: format!("unknown school_type '{}'; school not converted", school_type),
+            });
+        }
+    }
+}
+
 // --- Encoding fix ---
 
 /// Facebook's JSON encoder has a bug: UTF-8 multi-byte characters are output as
@@ -288,7 +1410,7 @@ pub struct PhoneNumber {
 /// sequence. For example, 'é' (U+00E9, UTF-8: 0xC3 0xA9) appears as Ã©
 /// instead of é. This function detects and corrects those sequences in the
 /// raw JSON string before it is handed to serde_json.
-fn fix_facebook_encoding(input: &str) -> String {
+pub(crate) fn fix_facebook_encoding(input: &str) -> String {
     let s = input.as_bytes();
     let len = s.len();
     let mut result = String::with_capacity(len);
@@ -377,7 +1499,7 @@ fn fix_facebook_encoding(input: &str) -> String {
 }
 
 fn is_hex(b: u8) -> bool {
-    matches!(b, b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F')
+    b.is_ascii_hexdigit()
 }
 
 fn hex_digit(b: u8) -> u8 {
@@ -0,0 +1,139 @@
+// ---------------------------------------------------------------------------
+// Facebook profile URLs
+// The same profile can be linked in many ways: a vanity URL or
+// `profile.php?id=`, on `www.`, `m.` or `web.facebook.com`, with tracking
+// parameters and trailing slashes. Every profile URL hatchling writes goes
+// through `FacebookId` first, so one person always gets one account IRI.
+// ---------------------------------------------------------------------------
+
+use std::fmt;
+
+/// First path segments of facebook.com that are not profiles.
+const NOT_PROFILES: &[&str] = &[
+    "bookmarks", "events", "friends", "gaming", "groups", "hashtag", "help", "home.php", "login",
+    "marketplace", "me", "messages", "notifications", "pages", "photo", "photo.php", "permalink.php",
+    "policies", "reel", "search", "settings", "sharer.php", "stories", "story.php", "watch",
+];
+
+/// A Facebook profile, as identified by its URL.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FacebookId {
+    /// The numeric id of `profile.php?id=`.
+    Numeric(String),
+    /// A vanity user name, lowercased; Facebook ignores its case.
+    Username(String),
+}
+
+impl FacebookId {
+    /// Reads a link to a Facebook profile in any of its shapes. Returns `None`
+    /// for anything that is not a profile, including links to other sites.
+    pub fn from_url(url: &str) -> Option<FacebookId> {
+        let url = url.trim();
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .unwrap_or(url);
+        let (host, rest) = rest.split_once('/').unwrap_or((rest, ""));
+        let host = host.to_ascii_lowercase();
+        if host != "facebook.com" && !host.ends_with(".facebook.com") {
+            return None;
+        }
+        let rest = rest.split('#').next().unwrap_or("");
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        match segments.as_slice() {
+            ["profile.php"] => query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("id="))
+                .and_then(FacebookId::numeric),
+            // facebook.com/people/Alice-Nguyen/100012345678
+            ["people", .., id] => FacebookId::numeric(id),
+            [name, ..] if NOT_PROFILES.contains(name) => None,
+            [name, ..] => FacebookId::from_username(name),
+            [] => None,
+        }
+    }
+
+    /// Reads a user name as found in the export's `username` field.
+    pub fn from_username(name: &str) -> Option<FacebookId> {
+        let name = name.trim().trim_start_matches('@');
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '.') {
+            return None;
+        }
+        FacebookId::numeric(name).or_else(|| Some(FacebookId::Username(name.to_ascii_lowercase())))
+    }
+
+    fn numeric(id: &str) -> Option<FacebookId> {
+        (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then(|| FacebookId::Numeric(id.to_string()))
+    }
+
+    /// The user name or numeric id, e.g. `alice.nguyen.503`.
+    pub fn account_name(&self) -> &str {
+        match self {
+            FacebookId::Numeric(id) | FacebookId::Username(id) => id,
+        }
+    }
+
+    /// The canonical URL of the profile.
+    pub fn url(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for FacebookId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FacebookId::Numeric(id) => write!(f, "https://www.facebook.com/profile.php?id={}", id),
+            FacebookId::Username(name) => write!(f, "https://www.facebook.com/{}", name),
+        }
+    }
+}
+
+/// The canonical form of `url` if it links to a Facebook profile, or `url`
+/// unchanged.
+pub fn canonical_url(url: &str) -> String {
+    FacebookId::from_url(url).map_or_else(|| url.to_string(), |id| id.url())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical_url, FacebookId};
+
+    #[test]
+    fn every_shape_of_a_profile_url_is_canonicalized() {
+        for url in [
+            "https://www.facebook.com/alice.nguyen.503",
+            "https://www.facebook.com/Alice.Nguyen.503/",
+            "http://m.facebook.com/alice.nguyen.503?ref=bookmarks",
+            "https://web.facebook.com/alice.nguyen.503#about",
+            "facebook.com/alice.nguyen.503/friends",
+        ] {
+            assert_eq!(canonical_url(url), "https://www.facebook.com/alice.nguyen.503", "{}", url);
+        }
+        for url in [
+            "https://www.facebook.com/profile.php?id=100012345678",
+            "https://m.facebook.com/profile.php?sk=friends&id=100012345678&refid=17",
+            "https://www.facebook.com/people/Jos%C3%A9-V%C3%A1zquez/100012345678/",
+            "https://www.facebook.com/100012345678",
+        ] {
+            assert_eq!(
+                canonical_url(url),
+                "https://www.facebook.com/profile.php?id=100012345678",
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn other_links_are_not_profiles() {
+        assert_eq!(FacebookId::from_url("https://example.com/alice"), None);
+        assert_eq!(FacebookId::from_url("https://www.facebook.com/groups/12345"), None);
+        assert_eq!(FacebookId::from_url("https://notfacebook.com/alice"), None);
+        assert_eq!(canonical_url("https://example.com/alice/"), "https://example.com/alice/");
+        assert_eq!(
+            FacebookId::from_url("https://www.facebook.com/profile.php?id=100012345").map(|id| id.account_name().to_string()),
+            Some("100012345".to_string())
+        );
+    }
+}
@@ -0,0 +1,203 @@
+// ---------------------------------------------------------------------------
+// Saved friends page
+// Reads a "Save Page As" copy of facebook.com/<user>/friends the way the
+// DevTools snippet in the README reads the live page: every link to a
+// profile with a name, except links to the page owner, each profile once.
+// ---------------------------------------------------------------------------
+
+use crate::facebook_parser::FBFriend;
+use crate::facebook_url::FacebookId;
+
+/// The friends linked from the saved page `html`, in page order.
+pub fn scrape(html: &str) -> Vec<FBFriend> {
+    let owner = owner(html);
+    let mut seen: Vec<FacebookId> = Vec::new();
+    let mut friends = Vec::new();
+    for (attributes, inner) in elements(html, "a") {
+        let Some(href) = attribute(attributes, "href") else {
+            continue;
+        };
+        let Some(id) = profile_link(&href) else {
+            continue;
+        };
+        let name = text(inner);
+        if name.chars().count() <= 1 || owner.as_ref() == Some(&id) || seen.contains(&id) {
+            continue;
+        }
+        friends.push(FBFriend {
+            name,
+            target: id.url(),
+            ..FBFriend::default()
+        });
+        seen.push(id);
+    }
+    friends
+}
+
+/// The profile the page belongs to, from the URL the browser saved it from.
+fn owner(html: &str) -> Option<FacebookId> {
+    let lower = html.to_ascii_lowercase();
+    // Chromium: <!-- saved from url=(0044)https://www.facebook.com/jane/friends -->
+    let saved_from = lower.find("saved from url=(").and_then(|i| {
+        let rest = &html[i..];
+        let url = rest[rest.find(')')? + 1..].split_whitespace().next()?;
+        Some(url.trim_end_matches("-->").to_string())
+    });
+    let canonical = elements(html, "link")
+        .find(|(attributes, _)| attribute(attributes, "rel").as_deref() == Some("canonical"))
+        .and_then(|(attributes, _)| attribute(attributes, "href"));
+    let og_url = elements(html, "meta")
+        .find(|(attributes, _)| attribute(attributes, "property").as_deref() == Some("og:url"))
+        .and_then(|(attributes, _)| attribute(attributes, "content"));
+    [saved_from, canonical, og_url]
+        .into_iter()
+        .flatten()
+        .find_map(|url| FacebookId::from_url(&url))
+}
+
+/// A link to a profile itself, not to one of its pages such as photos.
+fn profile_link(href: &str) -> Option<FacebookId> {
+    let href = match href.strip_prefix('/') {
+        Some(path) if !path.starts_with('/') => format!("https://www.facebook.com/{}", path),
+        _ => href.to_string(),
+    };
+    let path = href.split(['?', '#']).next().unwrap_or("");
+    let path = path.split_once("facebook.com").map_or("", |(_, path)| path);
+    if path.split('/').filter(|segment| !segment.is_empty()).count() > 1 {
+        return None;
+    }
+    FacebookId::from_url(&href)
+}
+
+/// The attributes and contents of every `<name ...>...</name>` element. The
+/// contents of void elements run up to the next closing tag and are unused.
+fn elements<'a>(html: &'a str, name: &str) -> impl Iterator<Item = (&'a str, &'a str)> {
+    let lower = html.to_ascii_lowercase();
+    let open = format!("<{}", name);
+    let close = format!("</{}", name);
+    let mut from = 0;
+    std::iter::from_fn(move || loop {
+        let start = from + lower[from..].find(&open)? + open.len();
+        from = start;
+        if !lower[start..].starts_with(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/') {
+            continue;
+        }
+        let tag_end = start + tag_end(&html[start..])?;
+        let inner_end = lower[tag_end..].find(&close).map_or(tag_end, |i| tag_end + i);
+        from = tag_end;
+        return Some((&html[start..tag_end - 1], &html[tag_end..inner_end]));
+    })
+}
+
+/// The length of the rest of an opening tag, including its `>`.
+fn tag_end(rest: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in rest.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The decoded value of the attribute `name`.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let lower = attributes.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(i) = lower[from..].find(name) {
+        let start = from + i;
+        from = start + name.len();
+        let before = lower[..start].chars().last();
+        if !before.is_some_and(|c| c.is_ascii_whitespace()) {
+            continue;
+        }
+        let Some(rest) = attributes[from..].trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let value = match rest.chars().next() {
+            Some(q @ ('"' | '\'')) => rest[1..].split(q).next().unwrap_or(""),
+            _ => rest.split(|c: char| c.is_ascii_whitespace()).next().unwrap_or(""),
+        };
+        return Some(decode_entities(value));
+    }
+    None
+}
+
+/// The text of `html` as a browser shows it on one line.
+fn text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    decode_entities(&text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+    while let Some(i) = rest.find('&') {
+        decoded.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let c = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => {
+                let code = entity.strip_prefix('#')?;
+                let code = match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => code.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (c, entity) {
+            (Some(c), Some(entity)) => {
+                decoded.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{attribute, decode_entities, text};
+
+    #[test]
+    fn attributes_and_text_are_decoded() {
+        assert_eq!(
+            attribute(r#" class="x" href='/profile.php?id=1&amp;sk=about'"#, "href").as_deref(),
+            Some("/profile.php?id=1&sk=about")
+        );
+        assert_eq!(attribute(r#" data-href="x" href=y"#, "href").as_deref(), Some("y"));
+        assert_eq!(text("<span>Ren&eacute;e</span>\n <b>O&#39;Brien</b>"), "Ren&eacute;e O'Brien");
+        assert_eq!(decode_entities("Fran&#xE7;ois &amp; co"), "François & co");
+    }
+}
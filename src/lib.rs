@@ -1,139 +1,586 @@
+pub mod access;
+pub mod convert;
+pub mod diff;
+pub mod drift;
+pub mod error;
+pub mod explain;
 pub mod facebook_parser;
+pub mod facebook_url;
+pub mod friends_page;
+pub mod lint;
+pub mod patch;
+pub mod photos;
+pub mod pod;
+pub mod posts;
 pub mod profile_builder;
+pub mod provenance;
+pub mod push;
+pub mod rdf;
+pub mod selection;
+pub mod serve;
+pub mod timestamps;
+pub mod turtle;
+pub mod webid_map;
 
-use facebook_parser::{EducationExperience, FBFriends, FBProfileInformation};
-use profile_builder::Profile;
-use std::error;
+use convert::{Converter, ExportVersion, Mode, Warning};
+use diff::GraphDiff;
+use facebook_parser::{
+    ConnectionList, EducationExperience, FBConnections, FBFriend, FBFriendLists, FBFriends, FBProfileInformation, FriendsVersion,
+};
+use patch::PatchFormat;
+use profile_builder::{clean_string, facebook_account_name, Profile};
+use facebook_url::{canonical_url, FacebookId};
+use provenance::{format_date, ExportTimes};
+use rdf::Graph;
+use selection::Selection;
+pub use error::Error;
+use error::Input;
 
+/// Converts with the default options. Use `convert::Converter` to configure
+/// the conversion or to get the report along with the output.
 pub fn convert_facebook_to_solid(
     profile: &str,
     friends: Option<&str>,
-) -> Result<String, Box<dyn error::Error>> {
-    let my_fb_profile = FBProfileInformation::new(profile)?;
+) -> Result<String, Error> {
+    Ok(converter(profile, friends).convert()?.serialize())
+}
+
+pub fn convert_facebook_to_graph(
+    profile: &str,
+    friends: Option<&str>,
+) -> Result<Graph, Error> {
+    Ok(converter(profile, friends).convert()?.graph)
+}
+
+fn converter<'a>(profile: &'a str, friends: Option<&'a str>) -> Converter<'a> {
+    friends
+        .into_iter()
+        .fold(Converter::new().profile(profile), Converter::friends)
+}
+
+/// Maps the export onto a `Profile`, which also records the setter and export
+/// field behind every triple (see `lint`).
+#[deprecated(note = "use `convert::Converter::into_profile`")]
+pub fn convert_facebook_to_profile(
+    profile: &str,
+    friends: Option<&str>,
+) -> Result<Profile, Error> {
+    converter(profile, friends).into_profile()
+}
+
+/// Like `convert_facebook_to_profile`, but only maps the parts of the export
+/// picked by `selection`.
+#[deprecated(note = "use `convert::Converter::into_profile`")]
+pub fn convert_facebook_with_selection(
+    profile: &str,
+    friends: Option<&str>,
+    selection: &Selection,
+) -> Result<Profile, Error> {
+    converter(profile, friends).selection(selection.clone()).into_profile()
+}
+
+/// Like `convert_facebook_with_selection`, but with any number of friends
+/// files, whose entries are joined by name, and of other connections lists
+/// (followers, friend requests, ...).
+#[deprecated(note = "use `convert::Converter::into_profile`")]
+pub fn convert_facebook_exports(
+    profile: &str,
+    friends: &[&str],
+    connections: &[&str],
+    selection: &Selection,
+) -> Result<Profile, Error> {
+    let converter = friends.iter().fold(Converter::new().profile(profile), |c, json| c.friends(json));
+    connections
+        .iter()
+        .fold(converter, |c, json| c.connections(json))
+        .selection(selection.clone())
+        .into_profile()
+}
+
+/// What `map_export` learned from the export besides the profile itself.
+pub(crate) struct Mapped {
+    pub profile: Profile,
+    pub version: ExportVersion,
+    pub times: ExportTimes,
+}
+
+pub(crate) fn map_export(
+    profile: &str,
+    friends: &[&str],
+    connections: &[&str],
+    selection: &Selection,
+    mode: Mode,
+    warnings: &mut Vec<Warning>,
+) -> Result<Mapped, Error> {
+    let my_fb_profile = match mode {
+        Mode::Strict => FBProfileInformation::new(profile)?,
+        Mode::Lenient => FBProfileInformation::new_lenient(profile, warnings)?,
+    };
+    let mut version = ExportVersion {
+        profile: my_fb_profile.version,
+        ..ExportVersion::default()
+    };
+    let times = ExportTimes {
+        registered: Some(my_fb_profile.profile.registration_timestamp).filter(|t| *t > 0),
+        last_change: my_fb_profile.profile.last_change(),
+    };
 
     let mut profile = Profile::new();
+    let key = my_fb_profile.version.to_string();
+    let key = key.as_str();
 
-    if !my_fb_profile.profile.name.full_name.is_empty() {
+    if selection.is_selected("name.full") && !my_fb_profile.profile.name.full_name.is_empty() {
+        locate(&mut profile, key, "name.full_name");
         profile.set_name(&my_fb_profile.profile.name.full_name);
     }
 
-    if !my_fb_profile.profile.name.last_name.is_empty() {
+    if selection.is_selected("name.last") && !my_fb_profile.profile.name.last_name.is_empty() {
+        locate(&mut profile, key, "name.last_name");
         profile.set_last_name(&my_fb_profile.profile.name.last_name);
     }
 
-    if !my_fb_profile.profile.name.first_name.is_empty() {
+    if selection.is_selected("name.first") && !my_fb_profile.profile.name.first_name.is_empty() {
+        locate(&mut profile, key, "name.first_name");
         profile.set_first_name(&my_fb_profile.profile.name.first_name);
     }
 
-    if !my_fb_profile.profile.gender.gender_option.is_empty() {
+    if selection.is_selected("gender") && !my_fb_profile.profile.gender.gender_option.is_empty() {
+        locate(&mut profile, key, "gender.gender_option");
         profile.set_gender(&my_fb_profile.profile.gender.gender_option);
     }
 
-    if my_fb_profile.profile.birthday.month > 0
+    if selection.is_selected("birthday")
+        && my_fb_profile.profile.birthday.month > 0
         && my_fb_profile.profile.birthday.day > 0
-        && my_fb_profile.profile.birthday.year > 0
     {
-        profile.set_birthday_and_age(
-            my_fb_profile.profile.birthday.month.into(),
-            my_fb_profile.profile.birthday.day.into(),
-            my_fb_profile.profile.birthday.year.into(),
-        );
+        locate(&mut profile, key, "birthday");
+        if selection.is_selected("birthday.year") && my_fb_profile.profile.birthday.year > 0 {
+            profile.set_birthday_and_age(
+                my_fb_profile.profile.birthday.month.into(),
+                my_fb_profile.profile.birthday.day.into(),
+                my_fb_profile.profile.birthday.year.into(),
+            );
+        } else {
+            profile.set_birthday(
+                my_fb_profile.profile.birthday.month.into(),
+                my_fb_profile.profile.birthday.day.into(),
+            );
+        }
     }
 
-    for elem in my_fb_profile.profile.phone_numbers.iter() {
-        profile.add_phone_number(&elem.phone_number);
+    if selection.is_selected("phone") {
+        for (i, elem) in my_fb_profile.profile.phone_numbers.iter().enumerate() {
+            locate(&mut profile, key, &format!("phone_numbers[{}].phone_number", i));
+            profile.add_phone_number(&elem.phone_number);
+        }
     }
 
-    if !my_fb_profile.profile.username.is_empty() {
+    if selection.is_selected("username") && !my_fb_profile.profile.username.is_empty() {
+        locate(&mut profile, key, "username");
+        let username = &my_fb_profile.profile.username;
         profile.add_account(
-            &format!(
-                "https://www.facebook.com/{}",
-                my_fb_profile.profile.username
-            ),
+            &FacebookId::from_username(username)
+                .map_or_else(|| format!("https://www.facebook.com/{}", username), |id| id.url()),
             None,
         );
     }
 
-    if !my_fb_profile.profile.profile_uri.is_empty() {
+    if selection.is_selected("profile_page") && !my_fb_profile.profile.profile_uri.is_empty() {
+        locate(&mut profile, key, "profile_uri");
         profile.add_profile_page(&my_fb_profile.profile.profile_uri);
     }
 
-    for work in &my_fb_profile.profile.work_experiences {
-        let employer = work
-            .get("employer")
-            .and_then(|e| e.get("name"))
-            .and_then(|n| n.as_str())
-            .unwrap_or("");
-        let title = work
-            .get("title")
-            .and_then(|t| t.as_str())
-            .unwrap_or("");
-        if !employer.is_empty() {
-            profile.add_work_experience(employer, title);
+    if selection.is_selected("work") {
+        for (i, work) in my_fb_profile.profile.work_experiences.iter().enumerate() {
+            if !work.employer.is_empty() {
+                locate(&mut profile, key, &format!("work_experiences[{}]", i));
+                let title = if selection.is_selected("work.title") { work.title.as_str() } else { "" };
+                profile.add_work_experience(&work.employer, title);
+            }
         }
     }
 
-    for email in my_fb_profile.profile.emails.emails {
-        profile.add_email(&email);
-    }
-
-    for edu in my_fb_profile.profile.education_experiences {
-        match edu {
-            EducationExperience::GraduateSchool {
-                name,
-                graduated,
-                start_timestamp: _,
-                end_timestamp: _,
-                description: _,
-                concentrations: _,
-                degree: _,
-            } => {
-                if graduated {
-                    profile.add_alumni_relationship(&name)
-                };
-            }
-            EducationExperience::College {
-                name,
-                graduated,
-                start_timestamp: _,
-                end_timestamp: _,
-                description: _,
-                concentrations: _,
-            } => {
-                if graduated {
-                    profile.add_alumni_relationship(&name)
-                };
-            }
-            EducationExperience::HighSchool {
-                name,
-                graduated,
-                start_timestamp: _,
-                end_timestamp: _,
-                description: _,
-            } => {
-                if graduated {
-                    profile.add_alumni_relationship(&name)
-                };
+    if selection.is_selected("email") {
+        for (i, email) in my_fb_profile.profile.emails.emails.iter().enumerate() {
+            locate(&mut profile, key, &format!("emails.emails[{}]", i));
+            profile.add_email(email);
+        }
+    }
+
+    if selection.is_selected("education") {
+        for (i, edu) in my_fb_profile.profile.education_experiences.into_iter().enumerate() {
+            locate(&mut profile, key, &format!("education_experiences[{}]", i));
+            match edu {
+                EducationExperience::GraduateSchool {
+                    name,
+                    graduated,
+                    start_timestamp: _,
+                    end_timestamp: _,
+                    description: _,
+                    concentrations: _,
+                    degree: _,
+                } => {
+                    if graduated {
+                        profile.add_alumni_relationship(&name)
+                    };
+                }
+                EducationExperience::College {
+                    name,
+                    graduated,
+                    start_timestamp: _,
+                    end_timestamp: _,
+                    description: _,
+                    concentrations: _,
+                } => {
+                    if graduated {
+                        profile.add_alumni_relationship(&name)
+                    };
+                }
+                EducationExperience::HighSchool {
+                    name,
+                    graduated,
+                    start_timestamp: _,
+                    end_timestamp: _,
+                    description: _,
+                } => {
+                    if graduated {
+                        profile.add_alumni_relationship(&name)
+                    };
+                }
+                EducationExperience::Other => {}
             }
-            EducationExperience::Other => {}
         }
     }
 
-    if !my_fb_profile.profile.current_city.name.is_empty() {
+    if selection.is_selected("current_city") && !my_fb_profile.profile.current_city.name.is_empty() {
+        locate(&mut profile, key, "current_city.name");
+        profile.set_timestamp(my_fb_profile.profile.current_city.timestamp);
         profile.add_home_location(&my_fb_profile.profile.current_city.name)
     }
 
     // I assume FB "hometown" maps clearly to birthPlace. This is potentially not, true
-    if !my_fb_profile.profile.hometown.name.is_empty() {
+    if selection.is_selected("hometown") && !my_fb_profile.profile.hometown.name.is_empty() {
+        locate(&mut profile, key, "hometown.name");
+        profile.set_timestamp(my_fb_profile.profile.hometown.timestamp);
         profile.add_birth_place(&my_fb_profile.profile.hometown.name)
     }
 
-    if let Some(friends) = friends {
-        let my_fb_friends = FBFriends::new(friends)?;
-        for friend_raw in my_fb_friends.iter() {
-            profile.add_facebook_friend(&friend_raw.name, &friend_raw.target)
+    if selection.is_selected("friends") {
+        let with_urls = selection.is_selected("friends.urls");
+        let mut entries: Vec<(FBFriend, String, (Input, String))> = Vec::new();
+        // Friends are numbered across all files so every one has its own field
+        let mut i = 0;
+        for (n, friends) in friends.iter().enumerate() {
+            let friends = match mode {
+                Mode::Strict => FBFriends::new(friends),
+                Mode::Lenient => FBFriends::new_lenient(friends, warnings),
+            }
+            .map_err(|err| err.for_input(Input::Friends(n)))?;
+            version.friends.push(friends.version);
+            let list = match friends.version {
+                FriendsVersion::Scraped | FriendsVersion::SavedPage => String::new(),
+                other => format!("/{}", other),
+            };
+            for (j, mut friend_raw) in friends.friends.into_iter().enumerate() {
+                let field = format!("friends[{}]", i);
+                i += 1;
+                if friend_raw.name.trim().is_empty() {
+                    warnings.push(Warning {
+                        field,
+                        message: "friend without a name skipped".to_string(),
+                    });
+                    continue;
+                }
+                if with_urls {
+                    friend_raw.target = canonical_url(&friend_raw.target);
+                } else {
+                    friend_raw.target.clear();
+                }
+                entries.push((friend_raw, field, (Input::Friends(n), format!("{}/{}", list, j))));
+            }
         }
-    };
-    let profile_string = profile.write_to_string();
-    Ok(profile_string)
+        let merged = merge_friends(entries, warnings);
+        let nodes = friend_nodes(&merged);
+        for (friend, node) in merged.into_iter().zip(nodes) {
+            profile.set_source(&friend.field);
+            profile.set_location(friend.found_at.0, &friend.found_at.1);
+            profile.set_timestamp(friend.since.as_ref().map_or(0, |(since, _)| *since));
+            profile.add_facebook_friend_as(&node, &friend.name, "");
+            if let Some((url, (input, pointer))) = &friend.url {
+                profile.set_location(*input, pointer);
+                profile.add_online_account(&node, url);
+            }
+            if let Some((since, (input, pointer))) = &friend.since {
+                profile.set_location(*input, pointer);
+                profile.add_friendship_start_of(&node, &format_date(*since));
+            }
+        }
+    } else {
+        // The files are not read, but their layout is still worth reporting
+        version.friends.extend(friends.iter().map_while(|json| FBFriends::version(json)));
+    }
+
+    if selection.is_selected("connections") {
+        for (n, connections) in connections.iter().enumerate() {
+            // Friend lists are a list of lists and are read on their own
+            if FBFriendLists::detect(connections).is_some() {
+                let lists = match mode {
+                    Mode::Strict => FBFriendLists::new(connections),
+                    Mode::Lenient => FBFriendLists::new_lenient(connections, warnings),
+                }
+                .map_err(|err| err.for_input(Input::Connections(n)))?;
+                version.connections.push(lists.key.to_string());
+                if selection.is_selected("connections.lists") {
+                    map_friend_lists(&mut profile, lists, Input::Connections(n), warnings);
+                }
+                continue;
+            }
+            let connections = match mode {
+                Mode::Strict => FBConnections::new(connections),
+                Mode::Lenient => FBConnections::new_lenient(connections, warnings),
+            }
+            .map_err(|err| err.for_input(Input::Connections(n)))?;
+            let key = connections.key;
+            version.connections.push(key.to_string());
+            let collection = match connections.list {
+                ConnectionList::Followers => "followers",
+                ConnectionList::Following => "following",
+                ConnectionList::SentRequests => "sent_friend_requests",
+                ConnectionList::ReceivedRequests => "received_friend_requests",
+                ConnectionList::Removed => "removed_friends",
+            };
+            // Followers and followed accounts are public on Facebook too. The
+            // other lists hang off `as:streams`, which the conversion always
+            // moves out of the card (see `access::PRIVATE_LISTS`)
+            let link = if connections.list.is_private() { "streams" } else { collection };
+            profile.set_source(key);
+            profile.set_location(Input::Connections(n), &format!("/{}", key));
+            profile.add_collection(link, collection, &connections.list.to_string());
+            for (j, person) in connections.people.into_iter().enumerate() {
+                let field = format!("{}[{}]", key, j);
+                if person.name.trim().is_empty() {
+                    warnings.push(Warning {
+                        field,
+                        message: "person without a name skipped".to_string(),
+                    });
+                    continue;
+                }
+                profile.set_source(&field);
+                profile.set_location(Input::Connections(n), &format!("/{}/{}", key, j));
+                profile.add_collection_member(collection, &collapse_whitespace(&person.name));
+            }
+        }
+    } else {
+        version.connections.extend(
+            connections
+                .iter()
+                .map_while(|json| {
+                    FBFriendLists::detect(json).or_else(|| Some(FBConnections::detect(json)?.1))
+                })
+                .map(str::to_string),
+        );
+    }
+    Ok(Mapped {
+        profile,
+        version,
+        times,
+    })
+}
+
+/// Writes each friend list as a group of the friends on it.
+fn map_friend_lists(profile: &mut Profile, lists: FBFriendLists, input: Input, warnings: &mut Vec<Warning>) {
+    let key = lists.key;
+    for (j, list) in lists.lists.into_iter().enumerate() {
+        let field = format!("{}[{}]", key, j);
+        let name = collapse_whitespace(&list.name);
+        if name.is_empty() {
+            warnings.push(Warning {
+                field,
+                message: "friend list without a name skipped".to_string(),
+            });
+            continue;
+        }
+        profile.set_source(&field);
+        profile.set_location(input, &format!("/{}/{}", key, j));
+        profile.add_group(&name);
+        for (k, member) in list.members.iter().enumerate() {
+            let field = format!("{}.members[{}]", field, k);
+            if member.trim().is_empty() {
+                warnings.push(Warning {
+                    field,
+                    message: "friend without a name skipped".to_string(),
+                });
+                continue;
+            }
+            profile.set_source(&field);
+            profile.set_location(input, &format!("/{}/{}/members/{}", key, j, k));
+            profile.add_group_member(&name, &collapse_whitespace(member));
+        }
+    }
+}
+
+/// A friend, possibly put together from entries in several friends files.
+struct MergedFriend {
+    name: String,
+    field: String,
+    found_at: (Input, String),
+    url: Option<(String, (Input, String))>,
+    since: Option<(u64, (Input, String))>,
+}
+
+/// Joins the entries of all friends files by normalized name: the DYI export
+/// knows when a friendship started, a scraped list knows the profile URL.
+/// Entries within one file are never joined, as two friends may share a name,
+/// and a name that is not unique on both sides is reported instead of guessed.
+/// Entries without a partner are kept as they are.
+fn merge_friends(
+    mut entries: Vec<(FBFriend, String, (Input, String))>,
+    warnings: &mut Vec<Warning>,
+) -> Vec<MergedFriend> {
+    // The same profile listed twice in one file, e.g. under two URL shapes
+    let mut seen: Vec<(Input, String)> = Vec::new();
+    entries.retain(|(friend, _, found_at)| {
+        if friend.target.is_empty() {
+            return true;
+        }
+        let profile = (found_at.0, friend.target.clone());
+        let first = !seen.contains(&profile);
+        seen.push(profile);
+        first
+    });
+    let keys: Vec<(Input, String)> = entries
+        .iter()
+        .map(|(friend, _, found_at)| (found_at.0, normalize_name(&friend.name)))
+        .collect();
+    let mut ambiguous: Vec<String> = Vec::new();
+    let mut merged: Vec<(String, MergedFriend)> = Vec::new();
+    for ((friend, field, found_at), (input, key)) in entries.into_iter().zip(&keys) {
+        let url = Some(friend.target).filter(|url| !url.is_empty());
+        let since = Some(friend.timestamp).filter(|since| *since > 0);
+        let namesakes = keys.iter().filter(|k| k.0 == *input && k.1 == *key).count();
+        let mut partners = merged
+            .iter_mut()
+            .filter(|(k, m)| k == key && m.found_at.0 != *input)
+            .map(|(_, m)| m);
+        let partner = match (partners.next(), partners.next()) {
+            (Some(partner), None) if namesakes == 1 => Some(partner),
+            (None, _) => None,
+            _ => {
+                if !ambiguous.contains(key) {
+                    ambiguous.push(key.clone());
+                    warnings.push(Warning {
+                        field: field.clone(),
+                        message: format!(
+                            "several friends are named \"{}\"; their entries were not matched across friends files",
+                            friend.name.trim()
+                        ),
+                    });
+                }
+                None
+            }
+        };
+        match partner.filter(|m| agrees(&m.url, &url) && agrees(&m.since, &since)) {
+            Some(partner) => {
+                if let (None, Some(url)) = (&partner.url, url) {
+                    partner.url = Some((url, found_at.clone()));
+                }
+                if let (None, Some(since)) = (&partner.since, since) {
+                    partner.since = Some((since, found_at));
+                }
+            }
+            None => merged.push((
+                key.clone(),
+                MergedFriend {
+                    name: collapse_whitespace(&friend.name),
+                    field,
+                    url: url.map(|url| (url, found_at.clone())),
+                    since: since.map(|since| (since, found_at.clone())),
+                    found_at,
+                },
+            )),
+        }
+    }
+    merged.into_iter().map(|(_, friend)| friend).collect()
+}
+
+/// The node of each friend, named after them. Friends who share a name are
+/// told apart by their Facebook account name, or else numbered.
+fn friend_nodes(friends: &[MergedFriend]) -> Vec<String> {
+    let names: Vec<String> = friends.iter().map(|friend| clean_string(&friend.name)).collect();
+    let mut nodes: Vec<String> = Vec::new();
+    for (friend, name) in friends.iter().zip(&names) {
+        if names.iter().filter(|other| *other == name).count() == 1 {
+            nodes.push(format!("#{}", name));
+            continue;
+        }
+        let account = friend.url.as_ref().and_then(|(url, _)| facebook_account_name(url));
+        let mut n = 0;
+        let node = loop {
+            n += 1;
+            let node = match &account {
+                Some(account) if n == 1 => format!("#{}_{}", name, clean_string(account)),
+                _ => format!("#{}_{}", name, n),
+            };
+            // Neither another namesake's node nor a friend's own name
+            if !nodes.contains(&node) && !names.iter().any(|other| node[1..] == *other) {
+                break node;
+            }
+        };
+        nodes.push(node);
+    }
+    nodes
+}
+
+/// Whether an entry with `ours` can complete a friend who has `theirs`.
+fn agrees<T: PartialEq>(theirs: &Option<(T, (Input, String))>, ours: &Option<T>) -> bool {
+    match (theirs, ours) {
+        (Some((theirs, _)), Some(ours)) => theirs == ours,
+        _ => true,
+    }
+}
+
+/// The form names are compared in: case and runs of whitespace are ignored.
+pub(crate) fn normalize_name(name: &str) -> String {
+    collapse_whitespace(name).to_lowercase()
+}
+
+fn collapse_whitespace(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Points `profile` at `field` of the profile section stored under `key`.
+fn locate(profile: &mut Profile, key: &str, field: &str) {
+    profile.set_source(field);
+    profile.set_location(Input::Profile, &json_pointer(key, field));
+}
+
+/// Turns a path like `phone_numbers[0].phone_number` within the section under
+/// `key` into a JSON pointer such as `/profile_v2/phone_numbers/0/phone_number`.
+fn json_pointer(key: &str, field: &str) -> String {
+    let mut pointer = format!("/{}", key);
+    for segment in field.split('.') {
+        for part in segment.split('[') {
+            pointer.push('/');
+            pointer.push_str(part.trim_end_matches(']'));
+        }
+    }
+    pointer
+}
+
+/// Compares two Turtle documents, treating blank nodes up to isomorphism.
+pub fn diff_turtle(old: &str, new: &str) -> Result<GraphDiff, Box<dyn std::error::Error>> {
+    let old = turtle::parse(old)?;
+    let new = turtle::parse(new)?;
+    Ok(diff::diff(&old, &new))
+}
+
+/// Builds a patch that turns the previously published `old` card into `new`,
+/// touching only the triples that differ between the two.
+pub fn patch_turtle(
+    old: &str,
+    new: &str,
+    format: PatchFormat,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let changes = diff_turtle(old, new)?;
+    Ok(patch::write_patch(&changes, format))
 }
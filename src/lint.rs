@@ -0,0 +1,148 @@
+// ---------------------------------------------------------------------------
+// Privacy lint
+// Flags triples that are risky to publish on a public card. Each finding is
+// traced back through `Profile`'s origin records to the setter that produced
+// it and the field of the Facebook export it came from, so the user knows
+// exactly what to remove (or exclude) before publishing.
+// ---------------------------------------------------------------------------
+
+use crate::profile_builder::{Origin, Profile};
+use crate::rdf::Triple;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: &'static str,
+    /// Field of the export the data came from, e.g. `phone_numbers[0].phone_number`.
+    pub field: String,
+    /// The `Profile` setter that turned the field into triples.
+    pub setter: &'static str,
+    pub triples: Vec<Triple>,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} (from {} via {})",
+            self.severity, self.message, self.field, self.setter
+        )
+    }
+}
+
+/// Checks every triple of `profile`, producing one finding per setter call
+/// that published something sensitive. Findings are ordered most severe first.
+pub fn lint(profile: &Profile) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = Vec::new();
+    for (triple, origin) in profile.graph().triples().iter().zip(profile.origins()) {
+        let Some((severity, message)) = classify(origin) else {
+            continue;
+        };
+        match findings
+            .iter_mut()
+            .find(|f| f.field == origin.field && f.setter == origin.setter)
+        {
+            Some(finding) => {
+                if severity > finding.severity {
+                    finding.severity = severity;
+                    finding.message = message;
+                }
+                finding.triples.push(triple.clone());
+            }
+            None => findings.push(Finding {
+                severity,
+                message,
+                field: origin.field.clone(),
+                setter: origin.setter,
+                triples: vec![triple.clone()],
+            }),
+        }
+    }
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    findings
+}
+
+fn classify(origin: &Origin) -> Option<(Severity, &'static str)> {
+    match origin.setter {
+        "set_birthday_and_age" => Some((Severity::High, "full birth date including the year")),
+        "set_birthday" => Some((Severity::Low, "birthday")),
+        "add_phone_number" => Some((Severity::High, "phone number")),
+        "add_email" => Some((Severity::Medium, "email address")),
+        "add_facebook_friend" | "add_online_account" | "add_friendship_start" => Some((
+            Severity::Medium,
+            "contact of a friend who has not agreed to be published",
+        )),
+        "add_collection_member" | "add_group_member" => Some((Severity::Medium, "someone who has not agreed to be published")),
+        _ => classify_section(&origin.field),
+    }
+}
+
+/// Sensitive sections of the export that no setter converts yet. They are
+/// checked by field, so whatever is made of them later gets flagged too.
+fn classify_section(field: &str) -> Option<(Severity, &'static str)> {
+    match field.split(['.', '[']).next().unwrap_or_default() {
+        "address" => Some((Severity::High, "street address")),
+        "political_view" | "religious_view" => Some((Severity::High, "political or religious view")),
+        "family_members" => Some((Severity::Medium, "family member who has not agreed to be published")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lint, Severity};
+    use crate::profile_builder::Profile;
+
+    #[test]
+    fn findings_are_grouped_per_setter_call_and_sorted_by_severity() {
+        let mut profile = Profile::new();
+        profile.set_source("emails.emails[0]");
+        profile.add_email("jane@example.com");
+        profile.set_source("birthday");
+        profile.set_birthday_and_age(3, 14, 1985);
+        profile.set_source("name.full_name");
+        profile.set_name("Jane");
+
+        let findings = lint(&profile);
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].field, "birthday");
+        assert_eq!(findings[0].triples.len(), 1);
+        assert_eq!(findings[1].severity, Severity::Medium);
+        assert_eq!(findings[1].setter, "add_email");
+        assert_eq!(findings[2].severity, Severity::Low);
+        assert_eq!(findings[2].setter, "set_birthday");
+    }
+
+    #[test]
+    fn sections_not_converted_yet_are_still_checked() {
+        let mut profile = Profile::new();
+        profile.set_source("address.street");
+        profile.set_name("12 Main St");
+        profile.set_source("family_members[0].name");
+        profile.set_name("Bob");
+
+        let findings = lint(&profile);
+        assert_eq!(findings.len(), 2);
+        assert_eq!((findings[0].severity, findings[0].message), (Severity::High, "street address"));
+        assert_eq!(findings[1].field, "family_members[0].name");
+    }
+}
@@ -1,48 +1,731 @@
-use clap::Parser;
-use hatchling::convert_facebook_to_solid;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use hatchling::patch::PatchFormat;
+use hatchling::serve::{Server, Site};
+use hatchling::rdf::Graph;
+use hatchling::turtle;
+use hatchling::push::{Anonymous, BearerToken, DpopToken, PodClient, PushOutcome};
+use hatchling::access::{self, AccessControl, VisibilityPolicy};
+use hatchling::pod::{build_pod_with, copy_media, read_pod, write_pod, PodOptions};
+use hatchling::selection::{Selection, UnknownSelector};
+use hatchling::convert::{Converter, Mode, ProvenanceOptions, Timestamps};
+use hatchling::error::{Error, Input};
+use hatchling::explain;
+use hatchling::timestamps;
+use hatchling::webid_map::WebIdMap;
+use hatchling::{diff_turtle, patch_turtle};
+use std::path::Path;
 use std::fs;
+use std::io;
 use std::process;
 
 #[derive(Parser)]
 #[command(
     version,
     about = "A tool to convert Facebook data to Linked Data",
-    author = "Sean McBride"
+    author = "Sean McBride",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
-struct Args {
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    convert: Option<ConvertArgs>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Show the triples added and removed between two Turtle files
+    ///
+    /// Blank nodes are matched by their surroundings, not their labels. Nodes
+    /// with the same surroundings are paired up one at a time, choosing the
+    /// pairing that leaves the fewest changes, so unusual graphs may report
+    /// more changes than the smallest possible.
+    Diff(DiffArgs),
+    /// Write an N3 Patch or SPARQL UPDATE that turns one Turtle file into another
+    Patch(PatchArgs),
+    /// Upload a Turtle file, or a whole pod layout, to a Solid pod
+    Push(PushArgs),
+    /// Preview a Turtle file on a local LDP server
+    Serve(ServeArgs),
+    /// Show which part of the export and which mapping produced each triple
+    Explain(ExplainArgs),
+}
+
+#[derive(Args)]
+struct ConvertArgs {
     /// Path to the Facebook profile_information.json file
     input: String,
 
-    /// Path for the resulting Turtle file
+    /// Path for the resulting Turtle file (a directory with --layout pod)
     output: String,
 
-    /// Path to an optional friends file (DYI export or browser-scraped JSON)
+    /// Path to a friends file (DYI export or browser-scraped JSON); repeat to
+    /// combine several, e.g. the DYI export with a scraped list
     #[arg(short, long)]
-    friends: Option<String>,
+    friends: Vec<String>,
+
+    /// Path to another list from the export's connections folder (followers,
+    /// following, friend requests, removed friends); may be repeated
+    #[arg(long)]
+    connections: Vec<String>,
+
+    /// Path to a your_posts_*.json file of the export; may be repeated. Posts
+    /// are written to a posts/ container of the pod layout
+    #[arg(long)]
+    posts: Vec<String>,
+
+    /// Path to an album from the export's photos_and_videos/album folder; may
+    /// be repeated. Albums and their photos are copied to a photos/ container
+    /// of the pod layout
+    #[arg(long)]
+    album: Vec<String>,
+
+    /// Keep where photos were taken (latitude and longitude) in the albums
+    #[arg(long)]
+    photo_locations: bool,
+
+    /// What to write: a single profile card, or a whole pod skeleton
+    #[arg(long, value_enum, default_value_t = Layout::Card)]
+    layout: Layout,
+
+    /// Move private fields (phone, email, birthday, friends) into a separate
+    /// private.ttl next to the card
+    #[arg(long)]
+    split: bool,
+
+    /// CSV (facebook,webid) or Turtle file of the WebIDs of friends, by
+    /// Facebook profile URL or name
+    #[arg(long, value_name = "FILE")]
+    webid_map: Option<String>,
+
+    /// JSON file overriding which predicates are public or private (implies --split)
+    #[arg(long, value_name = "FILE")]
+    privacy_policy: Option<String>,
+
+    /// Write access control resources for the pod layout
+    #[arg(long, value_enum)]
+    access_control: Option<AccessControlArg>,
+
+    /// Only convert these fields, e.g. --include name,work,education
+    #[arg(long, value_delimiter = ',', value_name = "FIELDS")]
+    include: Vec<String>,
+
+    /// Leave these fields out, e.g. --exclude phone,birthday.year,friends.urls
+    #[arg(long, value_delimiter = ',', value_name = "FIELDS")]
+    exclude: Vec<String>,
+
+    /// Write a JSON report of export data hatchling does not convert
+    #[arg(long, value_name = "FILE")]
+    report_unmapped: Option<String>,
+
+    /// Record when each fact was recorded in the export, as RDF-star
+    /// annotations or (for tools without RDF-star support) reified statements.
+    /// The pod layout always uses reification
+    #[arg(long, value_enum)]
+    timestamps: Option<TimestampsArg>,
+
+    /// Record where the data came from (software, time, source files and
+    /// their hashes) in the output
+    #[arg(long)]
+    provenance: bool,
+
+    /// Fail on any part of the export that cannot be read or has to be skipped,
+    /// instead of converting the rest with warnings, and refuse to write while
+    /// the privacy lint flags anything in the public card
+    #[arg(long)]
+    strict: bool,
+
+    /// Refuse to write while the privacy lint flags anything in the public
+    /// card, but convert leniently
+    #[arg(long)]
+    fail_on_lint: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Layout {
+    /// A single Turtle file holding the profile card
+    Card,
+    /// A directory tree with the card, preferences, type indexes and inbox
+    Pod,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AccessControlArg {
+    /// Web Access Control (.acl files)
+    Wac,
+    /// Access Control Policy (.acr files)
+    Acp,
+}
+
+impl From<AccessControlArg> for AccessControl {
+    fn from(arg: AccessControlArg) -> Self {
+        match arg {
+            AccessControlArg::Wac => AccessControl::Wac,
+            AccessControlArg::Acp => AccessControl::Acp,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TimestampsArg {
+    /// Turtle-star annotations on quoted triples
+    Star,
+    /// rdf:Statement reification
+    Reification,
+}
+
+impl From<TimestampsArg> for Timestamps {
+    fn from(arg: TimestampsArg) -> Self {
+        match arg {
+            TimestampsArg::Star => Timestamps::Star,
+            TimestampsArg::Reification => Timestamps::Reification,
+        }
+    }
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// Previously generated Turtle file
+    old: String,
+
+    /// Newly generated Turtle file
+    new: String,
+}
+
+#[derive(Args)]
+struct PatchArgs {
+    /// Turtle file currently published on the pod
+    old: String,
+
+    /// Newly generated Turtle file
+    new: String,
+
+    /// Patch language to write
+    #[arg(long, value_enum, default_value_t = PatchFormatArg::N3)]
+    format: PatchFormatArg,
+
+    /// Path for the patch document (defaults to standard output)
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+#[derive(Args)]
+struct PushArgs {
+    /// Turtle file to upload, or with --pod a directory written by --layout pod
+    file: String,
+
+    /// URL of the resource on the pod, e.g. https://you.example/profile/card;
+    /// with --pod the URL of the pod root, e.g. https://you.example/
+    #[arg(long)]
+    url: String,
+
+    /// Upload every resource of a pod layout below --url, creating its containers
+    #[arg(long, conflicts_with = "previous")]
+    pod: bool,
+
+    /// Previously pushed version of the file; sends a PATCH with only the changes instead of a PUT
+    #[arg(long)]
+    previous: Option<String>,
+
+    /// Patch language used with --previous
+    #[arg(long, value_enum, default_value_t = PatchFormatArg::N3)]
+    patch_format: PatchFormatArg,
+
+    /// Bearer access token for the pod
+    #[arg(long, env = "HATCHLING_TOKEN", hide_env_values = true, conflicts_with = "dpop_token")]
+    token: Option<String>,
+
+    /// DPoP-bound access token for the pod (requires --dpop-key)
+    #[arg(long, env = "HATCHLING_DPOP_TOKEN", hide_env_values = true, requires = "dpop_key")]
+    dpop_token: Option<String>,
+
+    /// PEM file with the P-256 private key --dpop-token is bound to; a fresh
+    /// DPoP proof is signed with it for every request
+    #[arg(long, env = "HATCHLING_DPOP_KEY", value_name = "FILE", requires = "dpop_token")]
+    dpop_key: Option<String>,
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    /// Turtle file to serve
+    file: String,
+
+    /// Port to listen on
+    #[arg(long, default_value_t = 8000)]
+    port: u16,
+
+    /// Path the document is served at
+    #[arg(long, default_value = "/profile/card")]
+    path: String,
+}
+
+#[derive(Args)]
+struct ExplainArgs {
+    /// Path to the Facebook profile_information.json file
+    input: String,
+
+    /// Path to a friends file (DYI export or browser-scraped JSON); repeat to
+    /// combine several, e.g. the DYI export with a scraped list
+    #[arg(short, long)]
+    friends: Vec<String>,
+
+    /// Path to another list from the export's connections folder (followers,
+    /// following, friend requests, removed friends); may be repeated
+    #[arg(long)]
+    connections: Vec<String>,
+
+    /// Only convert these fields, as for the conversion itself
+    #[arg(long, value_delimiter = ',', value_name = "FIELDS")]
+    include: Vec<String>,
+
+    /// Leave these fields out, as for the conversion itself
+    #[arg(long, value_delimiter = ',', value_name = "FIELDS")]
+    exclude: Vec<String>,
+
+    /// Turtle with a comment above each triple, or a JSON list of triples
+    #[arg(long, value_enum, default_value_t = ExplainFormat::Turtle)]
+    format: ExplainFormat,
+
+    /// Path for the explanation (defaults to standard output)
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExplainFormat {
+    Turtle,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PatchFormatArg {
+    /// Solid N3 Patch (text/n3)
+    N3,
+    /// SPARQL UPDATE (application/sparql-update)
+    Sparql,
+}
+
+impl From<PatchFormatArg> for PatchFormat {
+    fn from(arg: PatchFormatArg) -> Self {
+        match arg {
+            PatchFormatArg::N3 => PatchFormat::N3,
+            PatchFormatArg::Sparql => PatchFormat::SparqlUpdate,
+        }
+    }
 }
 
 fn main() {
-    let args = Args::parse();
+    let cli = Cli::parse();
 
-    let profile = fs::read_to_string(&args.input).unwrap_or_else(|err| {
-        eprintln!("Error reading {}: {}", args.input, err);
+    match cli.command {
+        Some(Command::Diff(args)) => diff(args),
+        Some(Command::Patch(args)) => patch(args),
+        Some(Command::Push(args)) => push(args),
+        Some(Command::Serve(args)) => serve(args),
+        Some(Command::Explain(args)) => explain(args),
+        None => convert(cli.convert.expect("clap requires the convert arguments")),
+    }
+}
+
+fn read_file(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Error reading {}: {}", path, err);
+        process::exit(1);
+    })
+}
+
+/// Reads every input of a conversion: the profile first, then each friends,
+/// each connections and each posts file, and each album.
+fn read_inputs<'a>(
+    profile: &'a str,
+    friends: &'a [String],
+    connections: &'a [String],
+    posts: &'a [String],
+    albums: &'a [String],
+) -> Vec<(Input, &'a str, String)> {
+    let friends = friends.iter().enumerate().map(|(n, path)| (Input::Friends(n), path.as_str()));
+    let connections = connections
+        .iter()
+        .enumerate()
+        .map(|(n, path)| (Input::Connections(n), path.as_str()));
+    let posts = posts.iter().enumerate().map(|(n, path)| (Input::Posts(n), path.as_str()));
+    let albums = albums.iter().enumerate().map(|(n, path)| (Input::Album(n), path.as_str()));
+    std::iter::once((Input::Profile, profile))
+        .chain(friends)
+        .chain(connections)
+        .chain(posts)
+        .chain(albums)
+        .map(|(input, path)| (input, path, read_file(path)))
+        .collect()
+}
+
+fn convert(args: ConvertArgs) {
+    let inputs = read_inputs(&args.input, &args.friends, &args.connections, &args.posts, &args.album);
+
+    let selection = select(&args.include, &args.exclude).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
         process::exit(1);
     });
 
-    let friends = args.friends.as_deref().map(|path| {
-        fs::read_to_string(path).unwrap_or_else(|err| {
-            eprintln!("Error reading {}: {}", path, err);
+    let mut converter = Converter::new()
+        .selection(selection)
+        .mode(if args.strict { Mode::Strict } else { Mode::Lenient })
+        .timestamps(args.timestamps.map_or(Timestamps::Off, Timestamps::from))
+        .photo_locations(args.photo_locations)
+        .on_warning(|warning| eprintln!("Warning: {}", warning));
+    for (input, _, contents) in &inputs {
+        converter = match input {
+            Input::Profile => converter.profile(contents),
+            Input::Friends(_) => converter.friends(contents),
+            Input::Connections(_) => converter.connections(contents),
+            Input::Posts(_) => converter.posts(contents),
+            Input::Album(_) => converter.album(contents),
+        };
+    }
+    if args.provenance {
+        // Only the file names, so local directory layouts are not published
+        let file_names = inputs
+            .iter()
+            .map(|(_, path, _)| {
+                Path::new(path)
+                    .file_name()
+                    .map_or(path.to_string(), |name| name.to_string_lossy().into_owned())
+            })
+            .collect();
+        converter = converter.provenance(ProvenanceOptions {
+            file_names,
+            ..ProvenanceOptions::default()
+        });
+    }
+    if let Some(path) = &args.webid_map {
+        let contents = read_file(path);
+        let map = if path.ends_with(".ttl") {
+            WebIdMap::from_turtle(&contents)
+        } else {
+            WebIdMap::from_csv(&contents)
+        };
+        converter = converter.webid_map(map.unwrap_or_else(|err| {
+            eprintln!("Error in WebID map {}: {}", path, err);
+            process::exit(1);
+        }));
+    }
+    let conversion = converter
+        .convert()
+        .unwrap_or_else(|err| conversion_failed(err, &inputs));
+    let without_webid = &conversion.report.without_webid;
+    if !without_webid.is_empty() {
+        eprintln!(
+            "No WebID for {} friend(s), who could be invited to Solid: {}",
+            without_webid.len(),
+            without_webid.join(", ")
+        );
+    }
+
+    let split = match &args.privacy_policy {
+        Some(path) => Some(VisibilityPolicy::from_json(&read_file(path)).unwrap_or_else(|err| {
+            eprintln!("Error in privacy policy {}: {}", path, err);
             process::exit(1);
-        })
+        })),
+        None if args.split => Some(VisibilityPolicy::default()),
+        None => None,
+    };
+
+    let unmapped = &conversion.report.unmapped;
+    match &args.report_unmapped {
+        Some(path) => {
+            let json = serde_json::to_string_pretty(unmapped).unwrap();
+            fs::write(path, json).unwrap_or_else(|err| {
+                eprintln!("Error writing {}: {}", path, err);
+                process::exit(1);
+            });
+        }
+        None if !unmapped.is_empty() => eprintln!(
+            "Note: {} field(s) of the export are not converted; list them with --report-unmapped FILE",
+            unmapped.len()
+        ),
+        None => {}
+    }
+
+    let mut findings = conversion.report.findings;
+    let graph = conversion.graph;
+    // Only what stays in the public card is worth warning about
+    let public = match &split {
+        Some(policy) => access::split(&graph, policy, "card", "private.ttl").0,
+        None => graph.clone(),
+    };
+    findings.retain(|f| f.triples.iter().any(|t| public.triples().contains(t)));
+    for finding in &findings {
+        eprintln!("Privacy {}", finding);
+    }
+    let warnings = conversion.report.warnings.len();
+    if args.strict && warnings > 0 {
+        eprintln!("Refusing to write {}: {} warning(s) with --strict", args.output, warnings);
+        process::exit(1);
+    }
+    if (args.strict || args.fail_on_lint) && !findings.is_empty() {
+        let flag = if args.strict { "--strict" } else { "--fail-on-lint" };
+        eprintln!(
+            "Refusing to write {}: {} privacy finding(s) with {}",
+            args.output,
+            findings.len(),
+            flag
+        );
+        process::exit(1);
+    }
+
+    let written = match args.layout {
+        Layout::Card => {
+            if args.access_control.is_some() {
+                eprintln!("--access-control requires --layout pod");
+                process::exit(1);
+            }
+            if !args.posts.is_empty() {
+                eprintln!("--posts requires --layout pod");
+                process::exit(1);
+            }
+            if !args.album.is_empty() {
+                eprintln!("--album requires --layout pod");
+                process::exit(1);
+            }
+            let serialize = match args.timestamps {
+                Some(TimestampsArg::Star) => timestamps::serialize_turtle_star,
+                _ => Graph::serialize_turtle,
+            };
+            write_card(&args.output, graph, conversion.private, split.as_ref(), serialize)
+        }
+        Layout::Pod => {
+            let options = PodOptions {
+                split,
+                private: conversion.private,
+                access_control: args.access_control.map(AccessControl::from),
+                posts: conversion.posts,
+                photos: conversion.photos,
+            };
+            let dir = Path::new(&args.output);
+            let albums: Vec<&Path> = args.album.iter().map(Path::new).collect();
+            write_pod(dir, &build_pod_with(graph, &options))
+                .and_then(|()| copy_media(dir, &options.photos, &albums))
+                .map(|missing| {
+                    for file in missing {
+                        eprintln!("Warning: {} is not a file of the export; the photo is described but not copied", file.uri);
+                    }
+                })
+        }
+    };
+    written.unwrap_or_else(|err| {
+        eprintln!("Error writing {}: {}", args.output, err);
+        process::exit(1);
     });
+}
+
+/// Reports `err` against the input (see `read_inputs`) it refers to and exits.
+fn conversion_failed(err: Error, inputs: &[(Input, &str, String)]) -> ! {
+    let input = err.location().map_or(Input::Profile, |l| l.input);
+    match inputs.iter().find(|(named, _, _)| *named == input) {
+        Some((_, file, contents)) => report_conversion_error(&err.in_file(file), contents),
+        None => report_conversion_error(&err, ""),
+    }
+    process::exit(1);
+}
+
+fn report_conversion_error(err: &Error, contents: &str) {
+    eprintln!("Conversion error: {}", err);
+    if let Some((excerpt, caret)) = err.excerpt(contents) {
+        eprintln!("  | {}", excerpt);
+        eprintln!("  | {}^", " ".repeat(caret));
+    }
+    if let Some(suggestion) = err.suggestion() {
+        eprintln!("hint: {}", suggestion);
+    }
+}
+
+fn select(include: &[String], exclude: &[String]) -> Result<Selection, UnknownSelector> {
+    let mut selection = Selection::all();
+    for selector in include {
+        selection = selection.include(selector)?;
+    }
+    for selector in exclude {
+        selection = selection.exclude(selector)?;
+    }
+    Ok(selection)
+}
+
+/// Writes the card, and `private` along with the card's private half under a
+/// policy as `private.ttl` in the same directory. Both are meant to be
+/// published side by side as `profile/card` and `profile/private.ttl`.
+fn write_card(
+    output: &str,
+    mut graph: Graph,
+    mut private: Graph,
+    policy: Option<&VisibilityPolicy>,
+    serialize: fn(&Graph) -> String,
+) -> io::Result<()> {
+    if let Some(policy) = policy {
+        let (public, split) = access::split(&graph, policy, "card", "private.ttl");
+        graph = public;
+        private.extend(&split);
+    }
+    fs::write(output, serialize(&graph))?;
+    if policy.is_none() && private.triples().is_empty() {
+        return Ok(());
+    }
+    let private_path = Path::new(output).with_file_name("private.ttl");
+    fs::write(private_path, serialize(&private))
+}
+
+fn diff(args: DiffArgs) {
+    let old = read_file(&args.old);
+    let new = read_file(&args.new);
 
-    let ttl = convert_facebook_to_solid(&profile, friends.as_deref()).unwrap_or_else(|err| {
-        eprintln!("Conversion error: {}", err);
+    let changes = diff_turtle(&old, &new).unwrap_or_else(|err| {
+        eprintln!("Diff error: {}", err);
         process::exit(1);
     });
 
-    fs::write(&args.output, ttl).unwrap_or_else(|err| {
-        eprintln!("Error writing {}: {}", args.output, err);
+    print!("{}", changes);
+}
+
+fn patch(args: PatchArgs) {
+    let old = read_file(&args.old);
+    let new = read_file(&args.new);
+
+    let body = patch_turtle(&old, &new, args.format.into()).unwrap_or_else(|err| {
+        eprintln!("Patch error: {}", err);
+        process::exit(1);
+    });
+
+    match args.output {
+        Some(path) => fs::write(&path, body).unwrap_or_else(|err| {
+            eprintln!("Error writing {}: {}", path, err);
+            process::exit(1);
+        }),
+        None => print!("{}", body),
+    }
+}
+
+fn explain(args: ExplainArgs) {
+    let inputs = read_inputs(&args.input, &args.friends, &args.connections, &[], &[]);
+    let selection = select(&args.include, &args.exclude).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    });
+
+    let mut converter = Converter::new().selection(selection);
+    for (input, _, contents) in &inputs {
+        converter = match input {
+            Input::Profile => converter.profile(contents),
+            Input::Friends(_) => converter.friends(contents),
+            _ => converter.connections(contents),
+        };
+    }
+    let mapped = converter
+        .into_profile()
+        .unwrap_or_else(|err| conversion_failed(err, &inputs));
+    let files: Vec<(Input, &str)> = inputs.iter().map(|(input, path, _)| (*input, *path)).collect();
+    let body = match args.format {
+        ExplainFormat::Turtle => explain::annotated_turtle(&mapped, &files),
+        ExplainFormat::Json => {
+            serde_json::to_string_pretty(&explain::explain(&mapped, &files)).unwrap() + "\n"
+        }
+    };
+
+    match args.output {
+        Some(path) => fs::write(&path, body).unwrap_or_else(|err| {
+            eprintln!("Error writing {}: {}", path, err);
+            process::exit(1);
+        }),
+        None => print!("{}", body),
+    }
+}
+
+fn push(args: PushArgs) {
+    let client = match (args.token, args.dpop_token, args.dpop_key) {
+        (Some(token), _, _) => PodClient::new(BearerToken(token)),
+        (_, Some(access_token), Some(key)) => {
+            let token = DpopToken::from_pem(&access_token, &read_file(&key)).unwrap_or_else(|err| {
+                eprintln!("Error in DPoP key {}: {}", key, err);
+                process::exit(1);
+            });
+            PodClient::new(token)
+        }
+        _ => PodClient::new(Anonymous),
+    };
+
+    if args.pod {
+        return push_pod(&client, &args.file, &args.url);
+    }
+    let body = read_file(&args.file);
+    let result = match &args.previous {
+        Some(previous) => {
+            let format = args.patch_format.into();
+            patch_turtle(&read_file(previous), &body, format)
+                .map_err(|err| err.to_string())
+                .and_then(|patch| {
+                    client
+                        .patch(&args.url, &patch, format)
+                        .map_err(|err| err.to_string())
+                })
+        }
+        None => client
+            .put(&args.url, &body, "text/turtle")
+            .map_err(|err| err.to_string()),
+    };
+
+    match result {
+        Ok(PushOutcome::Created) => println!("Created {}", args.url),
+        Ok(PushOutcome::Updated) => println!("Updated {}", args.url),
+        Ok(PushOutcome::Patched) => println!("Patched {}", args.url),
+        Err(err) => {
+            eprintln!("Push error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn push_pod(client: &PodClient, dir: &str, base: &str) {
+    let files = read_pod(Path::new(dir)).unwrap_or_else(|err| {
+        eprintln!("Error reading {}: {}", dir, err);
+        process::exit(1);
+    });
+    match client.put_pod(base, &files) {
+        Ok(outcomes) => {
+            for (url, outcome) in outcomes {
+                match outcome {
+                    PushOutcome::Created => println!("Created {}", url),
+                    _ => println!("Updated {}", url),
+                }
+            }
+        }
+        Err(err) => {
+            eprintln!("Push error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn serve(args: ServeArgs) {
+    let graph = turtle::parse(&read_file(&args.file)).unwrap_or_else(|err| {
+        eprintln!("Error parsing {}: {}", args.file, err);
+        process::exit(1);
+    });
+
+    let path = format!("/{}", args.path.trim_start_matches('/'));
+    let mut site = Site::new();
+    site.add(&path, graph);
+
+    let server = Server::bind(&format!("127.0.0.1:{}", args.port), site).unwrap_or_else(|err| {
+        eprintln!("Error listening on port {}: {}", args.port, err);
+        process::exit(1);
+    });
+
+    let address = server.local_addr().expect("bound listener has an address");
+    println!("Serving http://{}{}", address, path);
+    println!("WebID: http://{}{}#me", address, path);
+
+    server.run().unwrap_or_else(|err| {
+        eprintln!("Server error: {}", err);
         process::exit(1);
     });
 }
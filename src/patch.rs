@@ -0,0 +1,143 @@
+// ---------------------------------------------------------------------------
+// Patch documents for updating a live pod
+// A diff between the previously published conversion and a fresh one is
+// turned into a Solid N3 Patch or a SPARQL UPDATE body. Only the triples
+// hatchling produced are ever deleted, so data written by other apps survives.
+// Blank nodes cannot be deleted by label, so removed blank nodes become
+// variables that are bound through a WHERE clause.
+// ---------------------------------------------------------------------------
+
+use crate::diff::GraphDiff;
+use crate::rdf::{Node, Triple};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatchFormat {
+    /// `solid:InsertDeletePatch` in N3, sent as `text/n3`.
+    N3,
+    /// SPARQL 1.1 Update, sent as `application/sparql-update`.
+    SparqlUpdate,
+}
+
+impl PatchFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            PatchFormat::N3 => "text/n3",
+            PatchFormat::SparqlUpdate => "application/sparql-update",
+        }
+    }
+}
+
+pub fn write_patch(diff: &GraphDiff, format: PatchFormat) -> String {
+    let mut variables = HashMap::new();
+    let deletes: Vec<String> = diff
+        .removed
+        .iter()
+        .map(|t| format_deletion(diff, t, &mut variables))
+        .collect();
+    let inserts: Vec<String> = diff
+        .added
+        .iter()
+        .map(|t| diff.formatter().format_triple(t))
+        .collect();
+    // Every pattern that mentions a variable must also be matched in WHERE.
+    let wheres: Vec<String> = diff
+        .removed
+        .iter()
+        .zip(&deletes)
+        .filter(|(t, _)| t.subject.is_blank() || t.object.is_blank())
+        .map(|(_, d)| d.clone())
+        .collect();
+
+    match format {
+        PatchFormat::N3 => write_n3(diff, &wheres, &deletes, &inserts),
+        PatchFormat::SparqlUpdate => write_sparql(diff, &wheres, &deletes, &inserts),
+    }
+}
+
+fn write_n3(diff: &GraphDiff, wheres: &[String], deletes: &[String], inserts: &[String]) -> String {
+    let mut out = String::new();
+    out.push_str("@prefix solid: <http://www.w3.org/ns/solid/terms#> .\n");
+    for ns in diff.formatter().namespaces() {
+        if ns.prefix != "solid" {
+            out.push_str(&format!("@prefix {}: <{}> .\n", ns.prefix, ns.iri));
+        }
+    }
+    out.push_str("\n_:patch a solid:InsertDeletePatch");
+    for (keyword, patterns) in [
+        ("solid:where", wheres),
+        ("solid:deletes", deletes),
+        ("solid:inserts", inserts),
+    ] {
+        if !patterns.is_empty() {
+            out.push_str(&format!(" ;\n  {} {}", keyword, block(patterns, "  ")));
+        }
+    }
+    out.push_str(" .\n");
+    out
+}
+
+fn write_sparql(
+    diff: &GraphDiff,
+    wheres: &[String],
+    deletes: &[String],
+    inserts: &[String],
+) -> String {
+    let mut out = String::new();
+    for ns in diff.formatter().namespaces() {
+        out.push_str(&format!("PREFIX {}: <{}>\n", ns.prefix, ns.iri));
+    }
+    out.push('\n');
+    // Ground triples go through DELETE DATA so they are removed even when the
+    // blank node patterns no longer match anything on the pod.
+    let ground: Vec<String> = deletes
+        .iter()
+        .filter(|d| !wheres.contains(d))
+        .cloned()
+        .collect();
+    let mut operations = Vec::new();
+    if !ground.is_empty() {
+        operations.push(format!("DELETE DATA {}", block(&ground, "")));
+    }
+    if !wheres.is_empty() {
+        operations.push(format!("DELETE WHERE {}", block(wheres, "")));
+    }
+    if !inserts.is_empty() {
+        operations.push(format!("INSERT DATA {}", block(inserts, "")));
+    }
+    out.push_str(&operations.join(";\n"));
+    out.push('\n');
+    out
+}
+
+fn block(patterns: &[String], indent: &str) -> String {
+    let mut out = String::from("{\n");
+    for p in patterns {
+        out.push_str(&format!("{}  {}\n", indent, p));
+    }
+    out.push_str(&format!("{}}}", indent));
+    out
+}
+
+/// Formats a triple to delete, replacing blank nodes with variables.
+fn format_deletion(
+    diff: &GraphDiff,
+    t: &Triple,
+    variables: &mut HashMap<String, String>,
+) -> String {
+    let mut term = |node: &Node| match node {
+        Node::Blank(id) => {
+            let next = format!("?b{}", variables.len());
+            variables.entry(id.clone()).or_insert(next).clone()
+        }
+        other => diff.formatter().format_node(other),
+    };
+    let subject = term(&t.subject);
+    let object = term(&t.object);
+    format!(
+        "{} {} {} .",
+        subject,
+        diff.formatter().format_predicate(&t.predicate),
+        object
+    )
+}
@@ -0,0 +1,220 @@
+// ---------------------------------------------------------------------------
+// Photos
+// Every album becomes a document in the `photos/` container, e.g.
+// `photos/profile_pictures`, describing a `schema:ImageGallery` and its
+// `schema:ImageObject`s. The media files are copied next to it into
+// `photos/profile_pictures/`, under the names they have in the export. The
+// newest of the profile pictures also becomes the card's picture.
+// ---------------------------------------------------------------------------
+
+use crate::facebook_parser::{FBAlbum, FBPhoto};
+use crate::pod::{relative_iri, PodResource, CARD_PATH, PHOTOS_PATH};
+use crate::profile_builder::clean_string;
+use crate::provenance::format_date_time;
+use crate::rdf::{Graph, Namespace, Node, Triple, Uri, RDF_TYPE, XSD};
+use std::collections::HashSet;
+
+const FOAF: &str = "http://xmlns.com/foaf/0.1/";
+const SCHEMA: &str = "http://schema.org/";
+
+/// A media file of the export to copy into the pod.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MediaFile {
+    /// Where the file goes, relative to the pod root.
+    pub path: String,
+    /// Where the export keeps it, relative to the export's root.
+    pub uri: String,
+}
+
+#[derive(Clone, Default)]
+pub struct Photos {
+    /// One document per album.
+    pub albums: Vec<PodResource>,
+    pub media: Vec<MediaFile>,
+    /// Path of the current profile picture, which anyone may read.
+    pub profile_picture: Option<String>,
+}
+
+impl Photos {
+    pub fn is_empty(&self) -> bool {
+        self.albums.is_empty()
+    }
+}
+
+/// Lays `albums` out in the `photos/` container. Where the photos were taken
+/// is only kept with `locations`.
+pub fn photos(albums: &[FBAlbum], locations: bool) -> Photos {
+    let mut photos = Photos::default();
+    let mut slugs = HashSet::new();
+    let mut newest_profile_picture = None;
+    for album in albums {
+        let mut slug = clean_string(&album.name).to_lowercase();
+        if slug.is_empty() {
+            slug = "album".to_string();
+        }
+        let base = slug.clone();
+        for n in 2.. {
+            if slugs.insert(slug.clone()) {
+                break;
+            }
+            slug = format!("{}_{}", base, n);
+        }
+
+        let path = format!("{}{}", PHOTOS_PATH, slug);
+        let mut document = Document::new();
+        let gallery = Node::Uri("#album".to_string());
+        document.add(&gallery, RDF_TYPE, &Node::Uri(format!("{}ImageGallery", SCHEMA)));
+        document.add_text(&gallery, "name", &album.name);
+        document.add_text(&gallery, "description", &album.description);
+        if album.last_modified > 0 {
+            document.add_date(&gallery, "dateModified", album.last_modified);
+        }
+        let mut names = HashSet::new();
+        for (i, photo) in album.photos.iter().enumerate() {
+            if photo.uri.is_empty() {
+                continue;
+            }
+            let file = photo.uri.rsplit('/').next().unwrap_or(&photo.uri);
+            let file = if names.insert(file.to_string()) {
+                file.to_string()
+            } else {
+                format!("{}_{}", i + 1, file)
+            };
+            let media = format!("{}/{}", path, file);
+            let id = format!("photo{}", i + 1);
+            document.add(&gallery, &format!("{}hasPart", SCHEMA), &Node::Uri(format!("#{}", id)));
+            document.add_photo(&id, photo, &format!("{}/{}", slug, file), locations);
+            photos.media.push(MediaFile {
+                path: media.clone(),
+                uri: photo.uri.clone(),
+            });
+            if album.name.eq_ignore_ascii_case("profile pictures")
+                && newest_profile_picture
+                    .as_ref()
+                    .is_none_or(|(newest, _)| photo.creation_timestamp >= *newest)
+            {
+                newest_profile_picture = Some((photo.creation_timestamp, media));
+            }
+        }
+        photos.albums.push(PodResource {
+            path,
+            graph: document.graph,
+        });
+    }
+    photos.profile_picture = newest_profile_picture.map(|(_, path)| path);
+    photos
+}
+
+/// Adds `picture`, a path in the pod, to the card as `foaf:img` and
+/// `schema:image`.
+pub fn add_profile_picture(card: &mut Graph, picture: &str) {
+    let me = Node::Uri("#me".to_string());
+    let image = Node::Uri(relative_iri(CARD_PATH, picture));
+    for (prefix, ns, local) in [("foaf", FOAF, "img"), ("schema", SCHEMA, "image")] {
+        card.add_namespace(&Namespace::new(prefix.to_string(), Uri::new(ns.to_string())));
+        card.add_triple(&Triple::new(&me, &Node::Uri(format!("{}{}", ns, local)), &image));
+    }
+}
+
+struct Document {
+    graph: Graph,
+}
+
+impl Document {
+    fn new() -> Document {
+        let mut graph = Graph::new();
+        for (prefix, iri) in [("schema", SCHEMA), ("xsd", XSD)] {
+            graph.add_namespace(&Namespace::new(prefix.to_string(), Uri::new(iri.to_string())));
+        }
+        Document { graph }
+    }
+
+    fn add(&mut self, subject: &Node, predicate: &str, object: &Node) {
+        self.graph
+            .add_triple(&Triple::new(subject, &Node::Uri(predicate.to_string()), object));
+    }
+
+    fn add_text(&mut self, subject: &Node, local: &str, value: &str) {
+        if !value.is_empty() {
+            let value = self.graph.create_literal_node(value.to_string());
+            self.add(subject, &format!("{}{}", SCHEMA, local), &value);
+        }
+    }
+
+    fn add_date(&mut self, subject: &Node, local: &str, timestamp: u64) {
+        let date = self
+            .graph
+            .create_typed_literal_node(format_date_time(timestamp), &Uri::new(format!("{}dateTime", XSD)));
+        self.add(subject, &format!("{}{}", SCHEMA, local), &date);
+    }
+
+    fn add_photo(&mut self, id: &str, photo: &FBPhoto, content_url: &str, locations: bool) {
+        let schema = |local: &str| format!("{}{}", SCHEMA, local);
+        let image = &Node::Uri(format!("#{}", id));
+        self.add(image, RDF_TYPE, &Node::Uri(schema("ImageObject")));
+        self.add(image, &schema("contentUrl"), &Node::Uri(content_url.to_string()));
+        self.add_text(image, "caption", &photo.description);
+        if photo.creation_timestamp > 0 {
+            self.add_date(image, "uploadDate", photo.creation_timestamp);
+        }
+        if let Some(taken) = photo.taken_timestamp.filter(|taken| *taken > 0) {
+            self.add_date(image, "dateCreated", taken);
+        }
+        if let Some((latitude, longitude)) = photo.coordinate.filter(|_| locations) {
+            let place = Node::Blank(format!("{}_place", id));
+            let geo = Node::Blank(format!("{}_geo", id));
+            self.add(image, &schema("locationCreated"), &place);
+            self.add(&place, RDF_TYPE, &Node::Uri(schema("Place")));
+            self.add(&place, &schema("geo"), &geo);
+            self.add(&geo, RDF_TYPE, &Node::Uri(schema("GeoCoordinates")));
+            for (local, value) in [("latitude", latitude), ("longitude", longitude)] {
+                let value = self
+                    .graph
+                    .create_typed_literal_node(value.to_string(), &Uri::new(format!("{}double", XSD)));
+                self.add(&geo, &schema(local), &value);
+            }
+        }
+        for (name, value) in &photo.exif {
+            let property = Node::Blank(format!("{}_{}", id, name));
+            self.add(image, &schema("exifData"), &property);
+            self.add(&property, RDF_TYPE, &Node::Uri(schema("PropertyValue")));
+            self.add_text(&property, "name", name);
+            self.add_text(&property, "value", value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::photos;
+    use crate::facebook_parser::{FBAlbum, FBPhoto};
+
+    #[test]
+    fn albums_with_the_same_name_get_their_own_folders() {
+        let photo = |uri: &str, timestamp| FBPhoto {
+            uri: uri.to_string(),
+            creation_timestamp: timestamp,
+            ..FBPhoto::default()
+        };
+        let album = |name: &str, photos| FBAlbum {
+            name: name.to_string(),
+            photos,
+            ..FBAlbum::default()
+        };
+        let laid_out = photos(&[
+            album("Profile Pictures", vec![photo("a/1.jpg", 10), photo("b/1.jpg", 30), photo("a/2.jpg", 20)]),
+            album("Profile pictures", vec![photo("c/3.jpg", 5)]),
+        ], false);
+        let paths: Vec<&str> = laid_out.media.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "photos/profile_pictures/1.jpg",
+                "photos/profile_pictures/2_1.jpg",
+                "photos/profile_pictures/2.jpg",
+                "photos/profile_pictures_2/3.jpg",
+            ]
+        );
+        assert_eq!(laid_out.profile_picture.as_deref(), Some("photos/profile_pictures/2_1.jpg"));
+    }
+}
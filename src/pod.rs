@@ -0,0 +1,462 @@
+// ---------------------------------------------------------------------------
+// Pod skeleton
+// Lays a converted card out as the full set of resources a fresh Solid pod
+// starts with: the profile document, a preferences file, public and private
+// type indexes and an inbox. Files are named the way the Community Solid
+// Server's file backend stores them, so the tree can be copied into its data
+// folder unchanged. Optionally the card is split into public and private
+// documents, posts and photos get containers of their own and every document
+// gets an access control resource.
+// ---------------------------------------------------------------------------
+
+use crate::access::{self, AccessControl, PublicAccess, VisibilityPolicy};
+use crate::photos::{MediaFile, Photos};
+use crate::rdf::{Graph, Namespace, Node, Triple, Uri, RDF_TYPE};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+const LDP: &str = "http://www.w3.org/ns/ldp#";
+const PIM: &str = "http://www.w3.org/ns/pim/space#";
+const SOLID: &str = "http://www.w3.org/ns/solid/terms#";
+const DCTERMS: &str = "http://purl.org/dc/terms/";
+
+pub const CARD_PATH: &str = "profile/card";
+pub const PRIVATE_PROFILE_PATH: &str = "profile/private.ttl";
+pub const PREFERENCES_PATH: &str = "settings/prefs.ttl";
+pub const PUBLIC_TYPE_INDEX_PATH: &str = "settings/publicTypeIndex.ttl";
+pub const PRIVATE_TYPE_INDEX_PATH: &str = "settings/privateTypeIndex.ttl";
+pub const INBOX_PATH: &str = "inbox/";
+pub const POSTS_PATH: &str = "posts/";
+pub const PHOTOS_PATH: &str = "photos/";
+
+/// A resource in the pod, addressed by its path relative to the pod root.
+/// Paths ending in '/' are containers and carry the container's metadata.
+#[derive(Clone)]
+pub struct PodResource {
+    pub path: String,
+    pub graph: Graph,
+}
+
+#[derive(Clone, Default)]
+pub struct PodOptions {
+    /// Moves private predicates of the card into `profile/private.ttl`.
+    pub split: Option<VisibilityPolicy>,
+    /// Triples that go into `profile/private.ttl` either way, such as
+    /// `Conversion::private`.
+    pub private: Graph,
+    /// Writes an ACL or ACR next to every document that needs its own rules.
+    pub access_control: Option<AccessControl>,
+    /// Documents of the `posts/` container (see `posts`), registered in the
+    /// private type index and readable by the owner only.
+    pub posts: Vec<PodResource>,
+    /// Albums of the `photos/` container (see `photos`), registered and
+    /// protected the same way. Only the profile picture is public.
+    pub photos: Photos,
+}
+
+/// Builds every resource of the skeleton, linking `card` to the others.
+pub fn build_pod(card: Graph) -> Vec<PodResource> {
+    build_pod_with(card, &PodOptions::default())
+}
+
+pub fn build_pod_with(mut card: Graph, options: &PodOptions) -> Vec<PodResource> {
+    link_card(&mut card);
+    let mut private = options.private.clone();
+    if let Some(policy) = &options.split {
+        let (public, split) = access::split(&card, policy, "card", "private.ttl");
+        card = public;
+        private.extend(&split);
+    }
+
+    let mut private_type_index = type_index("UnlistedDocument");
+    if !options.posts.is_empty() {
+        register(&mut private_type_index, "posts", "http://schema.org/SocialMediaPosting", POSTS_PATH);
+    }
+    if !options.photos.is_empty() {
+        register(&mut private_type_index, "photos", "http://schema.org/ImageGallery", PHOTOS_PATH);
+    }
+
+    let mut resources = vec![
+        PodResource {
+            path: String::new(),
+            graph: storage_root(),
+        },
+        PodResource {
+            path: CARD_PATH.to_string(),
+            graph: card,
+        },
+    ];
+    if !private.triples().is_empty() {
+        resources.push(PodResource {
+            path: PRIVATE_PROFILE_PATH.to_string(),
+            graph: private,
+        });
+    }
+    resources.extend([
+        PodResource {
+            path: PREFERENCES_PATH.to_string(),
+            graph: preferences(),
+        },
+        PodResource {
+            path: PUBLIC_TYPE_INDEX_PATH.to_string(),
+            graph: type_index("ListedDocument"),
+        },
+        PodResource {
+            path: PRIVATE_TYPE_INDEX_PATH.to_string(),
+            graph: private_type_index,
+        },
+        PodResource {
+            path: INBOX_PATH.to_string(),
+            graph: container(),
+        },
+    ]);
+    if !options.posts.is_empty() {
+        resources.push(PodResource {
+            path: POSTS_PATH.to_string(),
+            graph: container(),
+        });
+        resources.extend(options.posts.iter().cloned());
+    }
+    if !options.photos.is_empty() {
+        resources.push(PodResource {
+            path: PHOTOS_PATH.to_string(),
+            graph: container(),
+        });
+        resources.extend(options.photos.albums.iter().cloned());
+    }
+
+    if let Some(kind) = options.access_control {
+        let rules = [
+            ("", PublicAccess::None),
+            (CARD_PATH, PublicAccess::Read),
+            (PRIVATE_PROFILE_PATH, PublicAccess::None),
+            (PUBLIC_TYPE_INDEX_PATH, PublicAccess::Read),
+            (INBOX_PATH, PublicAccess::Append),
+            (POSTS_PATH, PublicAccess::None),
+            (PHOTOS_PATH, PublicAccess::None),
+        ];
+        // The profile picture is a media file, not one of the resources
+        let picture = options.photos.profile_picture.as_deref();
+        let rules = rules.into_iter().chain(picture.map(|path| (path, PublicAccess::Read)));
+        let mut controls = Vec::new();
+        for (path, public) in rules {
+            if !resources.iter().any(|r| r.path == path) && Some(path) != picture {
+                continue;
+            }
+            let acl_path = format!("{}{}", path, kind.extension());
+            let owner = format!("{}#me", relative_iri(&acl_path, CARD_PATH));
+            let (path, graph) = access::access_resource(kind, path, public, &owner);
+            controls.push(PodResource { path, graph });
+        }
+        resources.extend(controls);
+    }
+    resources
+}
+
+/// Writes `resources` below `dir` using the file backend's naming scheme.
+pub fn write_pod(dir: &Path, resources: &[PodResource]) -> io::Result<()> {
+    for resource in resources {
+        let file = dir.join(file_name(&resource.path));
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&file, resource.graph.serialize_turtle())?;
+    }
+    Ok(())
+}
+
+/// Copies the media files of `photos` into the pod at `dir`. Their URIs are
+/// relative to the root of the export, which is looked for above the album
+/// files `albums`. Returns the files that could not be found, including any
+/// whose URI or path is not a plain relative path. The public profile picture
+/// is copied without the metadata a JPEG may carry, such as where it was taken.
+pub fn copy_media<'a>(dir: &Path, photos: &'a Photos, albums: &[&Path]) -> io::Result<Vec<&'a MediaFile>> {
+    let mut missing = Vec::new();
+    for file in &photos.media {
+        if !is_plain(&file.uri) || !is_plain(&file.path) {
+            missing.push(file);
+            continue;
+        }
+        let source = albums
+            .iter()
+            .flat_map(|album| album.ancestors().skip(1))
+            .map(|root| root.join(&file.uri))
+            .find(|source| source.is_file());
+        let Some(source) = source else {
+            missing.push(file);
+            continue;
+        };
+        let target = dir.join(&file.path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if photos.profile_picture.as_ref() == Some(&file.path) {
+            fs::write(target, without_metadata(fs::read(source)?))?;
+        } else {
+            fs::copy(source, target)?;
+        }
+    }
+    Ok(missing)
+}
+
+/// Whether `path` stays below the directory it is joined to.
+fn is_plain(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Drops the APP1 (Exif, XMP) and APP13 (IPTC) segments of a JPEG. Anything
+/// else is returned unchanged.
+fn without_metadata(jpeg: Vec<u8>) -> Vec<u8> {
+    if !jpeg.starts_with(&[0xFF, 0xD8]) {
+        return jpeg;
+    }
+    let mut stripped = jpeg[..2].to_vec();
+    let mut at = 2;
+    while at + 4 <= jpeg.len() && jpeg[at] == 0xFF {
+        let marker = jpeg[at + 1];
+        // The image data follows the start of scan
+        if marker == 0xDA {
+            break;
+        }
+        let end = at + 2 + u16::from_be_bytes([jpeg[at + 2], jpeg[at + 3]]) as usize;
+        if end > jpeg.len() {
+            return jpeg;
+        }
+        if marker != 0xE1 && marker != 0xED {
+            stripped.extend_from_slice(&jpeg[at..end]);
+        }
+        at = end;
+    }
+    stripped.extend_from_slice(&jpeg[at..]);
+    stripped
+}
+
+/// Maps a resource path to the file that stores it: container metadata lives
+/// in `.meta`, and documents without an extension get a `$.ttl` suffix so the
+/// server knows their content type.
+pub fn file_name(path: &str) -> PathBuf {
+    if path.is_empty() || path.ends_with('/') {
+        return PathBuf::from(format!("{}.meta", path));
+    }
+    let name = path.rsplit('/').next().unwrap_or(path);
+    if name.contains('.') {
+        PathBuf::from(path)
+    } else {
+        PathBuf::from(format!("{}$.ttl", path))
+    }
+}
+
+/// A file of a pod tree written by `write_pod` and `copy_media`, addressed by
+/// the path of the resource it stores.
+pub struct PodFile {
+    pub path: String,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+/// Reads the pod tree below `dir` back into resources, undoing `file_name`.
+/// Containers come before everything inside them.
+pub fn read_pod(dir: &Path) -> io::Result<Vec<PodFile>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let entry = entry?;
+            let file = entry.path();
+            if entry.file_type()?.is_dir() {
+                pending.push(file);
+                continue;
+            }
+            let relative = file.strip_prefix(dir).unwrap_or(&file);
+            let name: Vec<_> = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+            let name = name.join("/");
+            files.push(PodFile {
+                path: resource_path(&name),
+                content_type: content_type(&name),
+                body: fs::read(&file)?,
+            });
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// The inverse of `file_name`.
+fn resource_path(file: &str) -> String {
+    if file == ".meta" || file.ends_with("/.meta") {
+        file.trim_end_matches(".meta").to_string()
+    } else {
+        file.trim_end_matches("$.ttl").to_string()
+    }
+}
+
+fn content_type(file: &str) -> &'static str {
+    let extension = file.rsplit_once('.').map_or("", |(_, extension)| extension);
+    match extension.to_ascii_lowercase().as_str() {
+        "ttl" | "meta" | "acl" | "acr" => "text/turtle",
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "mp4" => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Returns `target` relative to the document at `from`, both pod-root paths.
+pub fn relative_iri(from: &str, target: &str) -> String {
+    let depth = from.matches('/').count();
+    format!("{}{}", "../".repeat(depth), target)
+}
+
+fn add_namespaces(graph: &mut Graph, prefixes: &[(&str, &str)]) {
+    for (prefix, iri) in prefixes {
+        graph.add_namespace(&Namespace::new(
+            prefix.to_string(),
+            Uri::new(iri.to_string()),
+        ));
+    }
+}
+
+fn link_card(card: &mut Graph) {
+    add_namespaces(card, &[("ldp", LDP), ("pim", PIM), ("solid", SOLID)]);
+    let me = Node::Uri("#me".to_string());
+    let links = [
+        (format!("{}inbox", LDP), INBOX_PATH),
+        (format!("{}storage", PIM), ""),
+        (format!("{}preferencesFile", PIM), PREFERENCES_PATH),
+        (format!("{}publicTypeIndex", SOLID), PUBLIC_TYPE_INDEX_PATH),
+    ];
+    for (predicate, target) in links {
+        card.add_triple(&Triple::new(
+            &me,
+            &Node::Uri(predicate),
+            &Node::Uri(relative_iri(CARD_PATH, target)),
+        ));
+    }
+}
+
+fn storage_root() -> Graph {
+    let mut graph = Graph::new();
+    add_namespaces(&mut graph, &[("pim", PIM)]);
+    graph.add_triple(&Triple::new(
+        &Node::Uri(String::new()),
+        &Node::Uri(RDF_TYPE.to_string()),
+        &Node::Uri(format!("{}Storage", PIM)),
+    ));
+    graph
+}
+
+fn container() -> Graph {
+    let mut graph = Graph::new();
+    add_namespaces(&mut graph, &[("ldp", LDP)]);
+    graph.add_triple(&Triple::new(
+        &Node::Uri(String::new()),
+        &Node::Uri(RDF_TYPE.to_string()),
+        &Node::Uri(format!("{}BasicContainer", LDP)),
+    ));
+    graph
+}
+
+/// The preferences file also links the private type index, which must not be
+/// advertised from the public card.
+fn preferences() -> Graph {
+    let mut graph = Graph::new();
+    add_namespaces(
+        &mut graph,
+        &[("pim", PIM), ("solid", SOLID), ("dcterms", DCTERMS)],
+    );
+    let this = Node::Uri(String::new());
+    let me = Node::Uri(format!("{}#me", relative_iri(PREFERENCES_PATH, CARD_PATH)));
+    graph.add_triple(&Triple::new(
+        &this,
+        &Node::Uri(RDF_TYPE.to_string()),
+        &Node::Uri(format!("{}ConfigurationFile", PIM)),
+    ));
+    graph.add_triple(&Triple::new(
+        &this,
+        &Node::Uri(format!("{}title", DCTERMS)),
+        &graph.create_literal_node("Preferences file".to_string()),
+    ));
+    graph.add_triple(&Triple::new(
+        &me,
+        &Node::Uri(format!("{}privateTypeIndex", SOLID)),
+        &Node::Uri(relative_iri(PREFERENCES_PATH, PRIVATE_TYPE_INDEX_PATH)),
+    ));
+    graph.add_triple(&Triple::new(
+        &me,
+        &Node::Uri(format!("{}publicTypeIndex", SOLID)),
+        &Node::Uri(relative_iri(PREFERENCES_PATH, PUBLIC_TYPE_INDEX_PATH)),
+    ));
+    graph
+}
+
+fn type_index(visibility: &str) -> Graph {
+    let mut graph = Graph::new();
+    add_namespaces(&mut graph, &[("solid", SOLID)]);
+    let this = Node::Uri(String::new());
+    for class in ["TypeIndex", visibility] {
+        graph.add_triple(&Triple::new(
+            &this,
+            &Node::Uri(RDF_TYPE.to_string()),
+            &Node::Uri(format!("{}{}", SOLID, class)),
+        ));
+    }
+    graph
+}
+
+/// Registers `container` in a type index as where instances of `class` live.
+fn register(index: &mut Graph, id: &str, class: &str, container: &str) {
+    add_namespaces(index, &[("schema", "http://schema.org/")]);
+    let registration = Node::Uri(format!("#{}", id));
+    let solid = |local: &str| Node::Uri(format!("{}{}", SOLID, local));
+    index.add_triple(&Triple::new(
+        &registration,
+        &Node::Uri(RDF_TYPE.to_string()),
+        &solid("TypeRegistration"),
+    ));
+    index.add_triple(&Triple::new(&registration, &solid("forClass"), &Node::Uri(class.to_string())));
+    index.add_triple(&Triple::new(
+        &registration,
+        &solid("instanceContainer"),
+        &Node::Uri(relative_iri(PRIVATE_TYPE_INDEX_PATH, container)),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_name, relative_iri, resource_path, without_metadata};
+    use std::path::PathBuf;
+
+    #[test]
+    fn file_name_follows_file_backend_conventions() {
+        assert_eq!(file_name("profile/card"), PathBuf::from("profile/card$.ttl"));
+        assert_eq!(file_name("settings/prefs.ttl"), PathBuf::from("settings/prefs.ttl"));
+        assert_eq!(file_name("inbox/"), PathBuf::from("inbox/.meta"));
+        assert_eq!(file_name(""), PathBuf::from(".meta"));
+        for path in ["profile/card", "settings/prefs.ttl", "inbox/", "", "profile/card.acl", "photos/a/1.jpg"] {
+            assert_eq!(resource_path(file_name(path).to_str().unwrap()), path);
+        }
+    }
+
+    #[test]
+    fn relative_iri_climbs_to_pod_root() {
+        assert_eq!(relative_iri("profile/card", "inbox/"), "../inbox/");
+        assert_eq!(relative_iri("profile/card", ""), "../");
+        assert_eq!(relative_iri("settings/prefs.ttl", "profile/card"), "../profile/card");
+    }
+
+    #[test]
+    fn jpeg_metadata_is_dropped() {
+        let app0 = [0xFF, 0xE0, 0x00, 0x04, b'J', b'F'];
+        let exif = [0xFF, 0xE1, 0x00, 0x06, b'E', b'x', b'i', b'f'];
+        let scan = [0xFF, 0xDA, 0x00, 0x02, 0xFF, 0xE1, 0xFF, 0xD9];
+        let jpeg = [&[0xFF, 0xD8][..], &app0, &exif, &scan].concat();
+        assert_eq!(without_metadata(jpeg), [&[0xFF, 0xD8][..], &app0, &scan].concat());
+        assert_eq!(without_metadata(b"not a jpeg".to_vec()), b"not a jpeg");
+    }
+}
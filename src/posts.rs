@@ -0,0 +1,212 @@
+// ---------------------------------------------------------------------------
+// Posts
+// Posts do not belong in the card: there can be thousands of them, and the
+// card is fetched every time someone looks up the WebID. They are written to
+// a `posts/` container instead, one document per year, with every post a
+// `schema:SocialMediaPosting` and `sioc:Post`. Tagged friends are linked to
+// their nodes in the card.
+// ---------------------------------------------------------------------------
+
+use crate::facebook_parser::{FBPlace, FBPost};
+use crate::normalize_name;
+use crate::pod::{relative_iri, PodResource, CARD_PATH, POSTS_PATH};
+use crate::profile_builder::clean_string;
+use crate::provenance::format_date_time;
+use crate::rdf::{Graph, Namespace, Node, Triple, Uri, RDF_TYPE, XSD};
+use std::collections::{BTreeMap, HashMap};
+
+const FOAF: &str = "http://xmlns.com/foaf/0.1/";
+const SCHEMA: &str = "http://schema.org/";
+const SIOC: &str = "http://rdfs.org/sioc/ns#";
+
+/// Where the posts link to: the card and the person who wrote them.
+#[derive(Clone, Debug, Default)]
+pub struct PostsOptions {
+    /// IRI the card is published at; defaults to `profile/card` in the pod.
+    pub card: Option<String>,
+    /// The author's WebID; defaults to `#me` in the card.
+    pub webid: Option<String>,
+}
+
+/// The documents of the posts container, e.g. `posts/2019`. `card` is the
+/// converted card, in which tagged people are looked up by name.
+pub fn posts_resources(posts: &[FBPost], card: &Graph, options: &PostsOptions) -> Vec<PodResource> {
+    let friends = friends_by_name(card);
+    let mut years: BTreeMap<String, Vec<&FBPost>> = BTreeMap::new();
+    for post in posts {
+        let year = match post.timestamp {
+            0 => "undated".to_string(),
+            timestamp => format_date_time(timestamp)[..4].to_string(),
+        };
+        years.entry(year).or_default().push(post);
+    }
+
+    let mut resources = Vec::new();
+    for (year, posts) in years {
+        let path = format!("{}{}", POSTS_PATH, year);
+        let card = options.card.clone().unwrap_or_else(|| relative_iri(&path, CARD_PATH));
+        let in_card = |iri: &str| match iri.strip_prefix('#') {
+            Some(fragment) => format!("{}#{}", card, fragment),
+            None => iri.to_string(),
+        };
+        let author = Node::Uri(options.webid.clone().unwrap_or_else(|| in_card("#me")));
+        let mut document = Document::new();
+        let mut ids: HashMap<String, usize> = HashMap::new();
+        for post in posts {
+            let id = post.timestamp.to_string();
+            let n = ids.entry(id.clone()).or_insert(0);
+            *n += 1;
+            let id = if *n == 1 { id } else { format!("{}_{}", id, n) };
+            let tagged = |name: &str| friends.get(&normalize_name(name)).map(|iri| in_card(iri));
+            document.add_post(&id, post, &author, tagged);
+        }
+        resources.push(PodResource {
+            path,
+            graph: document.graph,
+        });
+    }
+    resources
+}
+
+/// The friends of the card by normalized name. Names shared by several
+/// friends are left out, as a tag cannot tell them apart.
+fn friends_by_name(card: &Graph) -> HashMap<String, String> {
+    let knows = Node::Uri(format!("{}knows", FOAF));
+    let names = [Node::Uri(format!("{}name", FOAF)), Node::Uri(format!("{}name", SCHEMA))];
+    let mut friends: HashMap<String, Option<String>> = HashMap::new();
+    for t in card.triples().iter().filter(|t| t.predicate == knows) {
+        let Node::Uri(iri) = &t.object else { continue };
+        let name = card.triples().iter().find_map(|n| match &n.object {
+            Node::Literal { value, .. } if n.subject == t.object && names.contains(&n.predicate) => Some(value),
+            _ => None,
+        });
+        let Some(name) = name else { continue };
+        friends
+            .entry(normalize_name(name))
+            .and_modify(|known| {
+                if known.as_deref() != Some(iri.as_str()) {
+                    *known = None;
+                }
+            })
+            .or_insert_with(|| Some(iri.clone()));
+    }
+    friends
+        .into_iter()
+        .filter_map(|(name, iri)| Some((name, iri?)))
+        .collect()
+}
+
+struct Document {
+    graph: Graph,
+}
+
+impl Document {
+    fn new() -> Document {
+        let mut graph = Graph::new();
+        for (prefix, iri) in [("schema", SCHEMA), ("sioc", SIOC), ("xsd", XSD)] {
+            graph.add_namespace(&Namespace::new(prefix.to_string(), Uri::new(iri.to_string())));
+        }
+        Document { graph }
+    }
+
+    fn add(&mut self, subject: &Node, predicate: &str, object: &Node) {
+        self.graph
+            .add_triple(&Triple::new(subject, &Node::Uri(predicate.to_string()), object));
+    }
+
+    fn literal(&self, value: &str) -> Node {
+        self.graph.create_literal_node(value.to_string())
+    }
+
+    fn typed(&self, value: String, datatype: &str) -> Node {
+        self.graph
+            .create_typed_literal_node(value, &Uri::new(format!("{}{}", XSD, datatype)))
+    }
+
+    fn add_post(&mut self, id: &str, post: &FBPost, author: &Node, tagged: impl Fn(&str) -> Option<String>) {
+        let schema = |local: &str| format!("{}{}", SCHEMA, local);
+        let subject = Node::Uri(format!("#{}", id));
+        self.add(&subject, RDF_TYPE, &Node::Uri(schema("SocialMediaPosting")));
+        self.add(&subject, RDF_TYPE, &Node::Uri(format!("{}Post", SIOC)));
+        self.add(&subject, &schema("author"), author);
+        if post.timestamp > 0 {
+            let created = self.typed(format_date_time(post.timestamp), "dateTime");
+            self.add(&subject, &schema("dateCreated"), &created);
+        }
+        if !post.title.is_empty() {
+            self.add(&subject, &schema("headline"), &self.literal(&post.title));
+        }
+        if !post.text.is_empty() {
+            let text = self.literal(&post.text);
+            self.add(&subject, &schema("text"), &text);
+            self.add(&subject, &format!("{}content", SIOC), &text);
+        }
+        for url in &post.urls {
+            let link = Node::Uri(url.clone());
+            self.add(&subject, &schema("sharedContent"), &link);
+            self.add(&subject, &format!("{}links_to", SIOC), &link);
+        }
+        for (i, place) in post.places.iter().enumerate() {
+            let node = self.add_place(&format!("{}_place{}", id, i + 1), place);
+            self.add(&subject, &schema("contentLocation"), &node);
+        }
+        for name in &post.tags {
+            let person = match tagged(name) {
+                Some(iri) => Node::Uri(iri),
+                None => {
+                    let person = Node::Blank(format!("{}_{}", id, clean_string(name)));
+                    self.add(&person, RDF_TYPE, &Node::Uri(schema("Person")));
+                    self.add(&person, &schema("name"), &self.literal(name));
+                    person
+                }
+            };
+            self.add(&subject, &schema("mentions"), &person);
+        }
+    }
+
+    fn add_place(&mut self, id: &str, place: &FBPlace) -> Node {
+        let schema = |local: &str| format!("{}{}", SCHEMA, local);
+        let node = Node::Blank(id.to_string());
+        self.add(&node, RDF_TYPE, &Node::Uri(schema("Place")));
+        if !place.name.is_empty() {
+            self.add(&node, &schema("name"), &self.literal(&place.name));
+        }
+        if !place.address.is_empty() {
+            self.add(&node, &schema("address"), &self.literal(&place.address));
+        }
+        if !place.url.is_empty() {
+            self.add(&node, &schema("url"), &Node::Uri(place.url.clone()));
+        }
+        if let Some((latitude, longitude)) = place.coordinate {
+            let geo = Node::Blank(format!("{}_geo", id));
+            self.add(&node, &schema("geo"), &geo);
+            self.add(&geo, RDF_TYPE, &Node::Uri(schema("GeoCoordinates")));
+            let latitude = self.typed(latitude.to_string(), "double");
+            let longitude = self.typed(longitude.to_string(), "double");
+            self.add(&geo, &schema("latitude"), &latitude);
+            self.add(&geo, &schema("longitude"), &longitude);
+        }
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::friends_by_name;
+    use crate::turtle;
+
+    #[test]
+    fn friends_sharing_a_name_are_not_linked() {
+        let card = turtle::parse(
+            "@prefix foaf: <http://xmlns.com/foaf/0.1/> .\n\
+             <#me> foaf:knows <#Alice_Nguyen>, <#Sam_Lee>, <#Sam_Lee_2> .\n\
+             <#Alice_Nguyen> foaf:name \"Alice Nguyen\" .\n\
+             <#Sam_Lee> foaf:name \"Sam Lee\" .\n\
+             <#Sam_Lee_2> foaf:name \"Sam  Lee\" .\n",
+        )
+        .unwrap();
+        let friends = friends_by_name(&card);
+        assert_eq!(friends.get("alice nguyen").map(String::as_str), Some("#Alice_Nguyen"));
+        assert_eq!(friends.get("sam lee"), None);
+    }
+}
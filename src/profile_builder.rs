@@ -1,3 +1,15 @@
+use crate::error::Input;
+use crate::facebook_url::{canonical_url, FacebookId};
+use crate::rdf::{Graph, Namespace, Node, Triple, Uri, RDF_TYPE, XSD};
+
+const AS: &str = "https://www.w3.org/ns/activitystreams#";
+const VCARD: &str = "http://www.w3.org/2006/vcard/ns#";
+
+/// The IRI of the group for the friend list called `name`.
+fn group_iri(name: &str) -> String {
+    format!("#{}_group", clean_string(name))
+}
+
 pub fn clean_string(src: &str) -> String {
     // Turtle blank-node identifiers (and local names used after '#') must match
     // the PN_CHARS production.  Characters that are not allowed include
@@ -8,176 +20,61 @@ pub fn clean_string(src: &str) -> String {
     //   • ampersands (&)  → replaced with "_and_"
     //   • any remaining character that is not alphanumeric or '_' → removed
     src.replace('&', "_and_")
-        .replace(' ', "_")
-        .replace('.', "_")
-        .replace('-', "_")
-        .replace(',', "")
-        .replace('\'', "")
+        .replace([' ', '.', '-'], "_")
+        .replace([',', '\''], "")
         .chars()
         .filter(|c| c.is_alphanumeric() || *c == '_')
         .collect()
 }
 
-// ---------------------------------------------------------------------------
-// Minimal RDF graph + Turtle serializer — replaces the unmaintained `rdf` crate.
-// oxrdf/oxttl require absolute IRIs and cannot represent the relative-IRI
-// semantics that Solid profile documents require (e.g. <>, <#me>, <./>, <#>).
-// ---------------------------------------------------------------------------
-
-struct Uri(String);
-
-impl Uri {
-    fn new(s: String) -> Self {
-        Uri(s)
-    }
-}
-
-#[derive(Clone)]
-struct Namespace {
-    prefix: String,
-    iri: String,
-}
-
-impl Namespace {
-    fn new(prefix: String, uri: Uri) -> Self {
-        Namespace { prefix, iri: uri.0 }
-    }
-}
-
-#[derive(Clone)]
-enum NodeKind {
-    Uri,
-    Blank,
-    Literal,
-}
-
-#[derive(Clone)]
-struct Node {
-    kind: NodeKind,
-    value: String,
-}
-
-struct Triple {
-    subject: Node,
-    predicate: Node,
-    object: Node,
-}
-
-impl Triple {
-    fn new(s: &Node, p: &Node, o: &Node) -> Self {
-        Triple {
-            subject: s.clone(),
-            predicate: p.clone(),
-            object: o.clone(),
-        }
-    }
-}
-
-struct Graph {
-    triples: Vec<Triple>,
-    namespaces: Vec<Namespace>,
-}
-
-impl Graph {
-    fn new() -> Self {
-        Graph {
-            triples: Vec::new(),
-            namespaces: Vec::new(),
-        }
-    }
-
-    fn add_namespace(&mut self, ns: &Namespace) {
-        self.namespaces.push(ns.clone());
-    }
-
-    fn create_uri_node(&self, uri: &Uri) -> Node {
-        Node { kind: NodeKind::Uri, value: uri.0.clone() }
-    }
-
-    fn create_literal_node(&self, s: String) -> Node {
-        Node { kind: NodeKind::Literal, value: s }
-    }
-
-    fn create_blank_node_with_id(&self, id: String) -> Node {
-        Node { kind: NodeKind::Blank, value: id }
-    }
-
-    fn add_triple(&mut self, t: &Triple) {
-        self.triples.push(Triple {
-            subject: t.subject.clone(),
-            predicate: t.predicate.clone(),
-            object: t.object.clone(),
-        });
-    }
-
-    fn format_node(&self, node: &Node) -> String {
-        match node.kind {
-            NodeKind::Uri => {
-                let iri = &node.value;
-                // "a" is the Turtle shorthand for rdf:type
-                if iri == "a" {
-                    return "a".to_string();
-                }
-                // Try namespace prefix compression
-                for ns in &self.namespaces {
-                    if !ns.iri.is_empty() && iri.starts_with(ns.iri.as_str()) {
-                        let local = &iri[ns.iri.len()..];
-                        return if ns.prefix.is_empty() {
-                            format!(":{}", local)
-                        } else {
-                            format!("{}:{}", ns.prefix, local)
-                        };
-                    }
-                }
-                // Fall back to angle-bracket form; empty IRI means <> (this document)
-                if iri.is_empty() {
-                    "<>".to_string()
-                } else {
-                    format!("<{}>", iri)
-                }
-            }
-            NodeKind::Blank => format!("_:{}", node.value),
-            NodeKind::Literal => {
-                let escaped = node
-                    .value
-                    .replace('\\', "\\\\")
-                    .replace('"', "\\\"")
-                    .replace('\n', "\\n")
-                    .replace('\r', "\\r");
-                format!("\"{}\"", escaped)
-            }
-        }
-    }
-
-    fn serialize_turtle(&self) -> String {
-        let mut out = String::new();
-        for ns in &self.namespaces {
-            out.push_str(&format!("@prefix {}: <{}> .\n", ns.prefix, ns.iri));
-        }
-        out.push('\n');
-        for t in &self.triples {
-            out.push_str(&format!(
-                "{} {} {} .\n",
-                self.format_node(&t.subject),
-                self.format_node(&t.predicate),
-                self.format_node(&t.object),
-            ));
-        }
-        out
-    }
+/// The user name or numeric id in a Facebook profile URL, e.g. `alice.nguyen.503`
+/// for https://www.facebook.com/alice.nguyen.503.
+pub(crate) fn facebook_account_name(profile_url: &str) -> Option<String> {
+    FacebookId::from_url(profile_url).map(|id| id.account_name().to_string())
 }
 
 // ---------------------------------------------------------------------------
 // Profile builder
 // ---------------------------------------------------------------------------
 
+/// Where a triple came from: the `Profile` setter that added it and the field
+/// of the Facebook export the value was read from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Origin {
+    pub setter: &'static str,
+    pub field: String,
+    /// The input the value was read from; `None` for triples every card has.
+    pub input: Option<Input>,
+    /// JSON pointer to the value within `input`, e.g. `/profile_v2/birthday`.
+    pub pointer: String,
+    /// When the export says the fact was recorded, as a Unix timestamp.
+    pub timestamp: Option<u64>,
+}
+
 pub struct Profile {
     graph: Graph,
+    /// One entry per triple of `graph`, in the same order.
+    origins: Vec<Origin>,
+    source: String,
+    location: Option<(Input, String)>,
+    timestamp: Option<u64>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Profile {
     pub fn new() -> Profile {
-        let mut new_profile = Profile { graph: Graph::new() };
+        let mut new_profile = Profile {
+            graph: Graph::new(),
+            origins: Vec::new(),
+            source: String::new(),
+            location: None,
+            timestamp: None,
+        };
 
         new_profile
             .graph
@@ -218,7 +115,7 @@ impl Profile {
             .create_uri_node(&Uri::new("#me".to_string()));
         let is_a = new_profile
             .graph
-            .create_uri_node(&Uri::new("a".to_string()));
+            .create_uri_node(&Uri::new(RDF_TYPE.to_string()));
 
         new_profile.graph.add_triple(&Triple::new(
             &solid_card,
@@ -237,9 +134,44 @@ impl Profile {
         new_profile
             .graph
             .add_triple(&Triple::new(&me, &is_a, &foaf_person));
+        new_profile.attribute("new");
         new_profile
     }
 
+    /// Names the export field the following setter calls read their values from.
+    pub fn set_source(&mut self, field: &str) {
+        self.source = field.to_string();
+        self.timestamp = None;
+    }
+
+    /// Sets when the export says the following values were recorded; 0 means
+    /// unknown. Cleared by `set_source`.
+    pub fn set_timestamp(&mut self, timestamp: u64) {
+        self.timestamp = Some(timestamp).filter(|t| *t > 0);
+    }
+
+    /// Sets the exact place in the inputs the following values are read from.
+    pub fn set_location(&mut self, input: Input, pointer: &str) {
+        self.location = Some((input, pointer.to_string()));
+    }
+
+    /// Tags every triple added since the last call with `setter` and the current source.
+    fn attribute(&mut self, setter: &'static str) {
+        while self.origins.len() < self.graph.triples().len() {
+            let (input, pointer) = match &self.location {
+                Some((input, pointer)) => (Some(*input), pointer.clone()),
+                None => (None, String::new()),
+            };
+            self.origins.push(Origin {
+                setter,
+                field: self.source.clone(),
+                input,
+                pointer,
+                timestamp: self.timestamp,
+            });
+        }
+    }
+
     pub fn set_name(&mut self, name: &str) {
         self.graph.add_triple(&Triple::new(
             &self.graph.create_uri_node(&Uri::new("#me".to_string())),
@@ -255,6 +187,7 @@ impl Profile {
                 .create_uri_node(&Uri::new("http://schema.org/name".to_string())),
             &self.graph.create_literal_node(name.to_string()),
         ));
+        self.attribute("set_name");
     }
 
     pub fn set_last_name(&mut self, lastname: &str) {
@@ -272,6 +205,7 @@ impl Profile {
                 .create_uri_node(&Uri::new("http://schema.org/familyName".to_string())),
             &self.graph.create_literal_node(lastname.to_string()),
         ));
+        self.attribute("set_last_name");
     }
 
     pub fn set_first_name(&mut self, firstname: &str) {
@@ -289,6 +223,7 @@ impl Profile {
                 .create_uri_node(&Uri::new("http://schema.org/givenName".to_string())),
             &self.graph.create_literal_node(firstname.to_string()),
         ));
+        self.attribute("set_first_name");
     }
 
     pub fn set_gender(&mut self, gender: &str) {
@@ -306,12 +241,13 @@ impl Profile {
                 .create_uri_node(&Uri::new("http://schema.org/gender".to_string())),
             &self.graph.create_literal_node(gender.to_string()),
         ));
+        self.attribute("set_gender");
     }
 
-    pub fn set_birthday_and_age(&mut self, month: u32, day: u32, year: i32) {
-        let me = self.graph.create_uri_node(&Uri::new("#me".to_string()));
+    /// Records the birthday without the year, which gives away the age.
+    pub fn set_birthday(&mut self, month: u32, day: u32) {
         self.graph.add_triple(&Triple::new(
-            &me,
+            &self.graph.create_uri_node(&Uri::new("#me".to_string())),
             &self
                 .graph
                 .create_uri_node(&Uri::new("http://xmlns.com/foaf/0.1/birthday".to_string())),
@@ -319,8 +255,13 @@ impl Profile {
                 .graph
                 .create_literal_node(format!("--{:02}-{:02}", &month, &day)),
         ));
+        self.attribute("set_birthday");
+    }
+
+    pub fn set_birthday_and_age(&mut self, month: u32, day: u32, year: i32) {
+        self.set_birthday(month, day);
         self.graph.add_triple(&Triple::new(
-            &me,
+            &self.graph.create_uri_node(&Uri::new("#me".to_string())),
             &self
                 .graph
                 .create_uri_node(&Uri::new("http://schema.org/birthDate".to_string())),
@@ -328,6 +269,7 @@ impl Profile {
                 .graph
                 .create_literal_node(format!("{:04}-{:02}-{:02}", &year, &month, &day)),
         ));
+        self.attribute("set_birthday_and_age");
     }
 
     pub fn add_phone_number(&mut self, phonenum: &str) {
@@ -349,16 +291,17 @@ impl Profile {
                 .graph
                 .create_uri_node(&Uri::new(format!("tel:{}", phonenum))),
         ));
+        self.attribute("add_phone_number");
     }
 
     pub fn add_birth_place(&mut self, birth_place: &str) {
-        let birth_place_node = &self
+        let birth_place_node = self
             .graph
             .create_blank_node_with_id(clean_string(birth_place));
 
         self.graph.add_triple(&Triple::new(
             &birth_place_node,
-            &self.graph.create_uri_node(&Uri::new("a".to_string())),
+            &self.graph.create_uri_node(&Uri::new(RDF_TYPE.to_string())),
             &self
                 .graph
                 .create_uri_node(&Uri::new("http://schema.org/Place".to_string())),
@@ -378,16 +321,17 @@ impl Profile {
                 .create_uri_node(&Uri::new("http://schema.org/birthPlace".to_string())),
             &birth_place_node,
         ));
+        self.attribute("add_birth_place");
     }
 
     pub fn add_home_location(&mut self, home_location: &str) {
-        let home_location_node = &self
+        let home_location_node = self
             .graph
             .create_blank_node_with_id(clean_string(home_location));
 
         self.graph.add_triple(&Triple::new(
             &home_location_node,
-            &self.graph.create_uri_node(&Uri::new("a".to_string())),
+            &self.graph.create_uri_node(&Uri::new(RDF_TYPE.to_string())),
             &self
                 .graph
                 .create_uri_node(&Uri::new("http://schema.org/Place".to_string())),
@@ -407,6 +351,7 @@ impl Profile {
                 .create_uri_node(&Uri::new("http://schema.org/homeLocation".to_string())),
             &home_location_node,
         ));
+        self.attribute("add_home_location");
     }
 
     pub fn add_email(&mut self, email: &str) {
@@ -428,15 +373,16 @@ impl Profile {
                 .graph
                 .create_uri_node(&Uri::new(format!("mailto:{}", email))),
         ));
+        self.attribute("add_email");
     }
 
     pub fn add_alumni_relationship(&mut self, school_name: &str) {
-        let school = &self
+        let school = self
             .graph
             .create_blank_node_with_id(clean_string(school_name));
         self.graph.add_triple(&Triple::new(
             &school,
-            &self.graph.create_uri_node(&Uri::new("a".to_string())),
+            &self.graph.create_uri_node(&Uri::new(RDF_TYPE.to_string())),
             &self.graph.create_uri_node(&Uri::new(
                 "http://schema.org/EducationalOrganization".to_string(),
             )),
@@ -456,15 +402,16 @@ impl Profile {
                 .create_uri_node(&Uri::new("http://schema.org/alumniOf".to_string())),
             &school,
         ));
+        self.attribute("add_alumni_relationship");
     }
 
     pub fn add_work_experience(&mut self, employer: &str, title: &str) {
-        let org = &self
+        let org = self
             .graph
             .create_blank_node_with_id(clean_string(employer));
         self.graph.add_triple(&Triple::new(
             &org,
-            &self.graph.create_uri_node(&Uri::new("a".to_string())),
+            &self.graph.create_uri_node(&Uri::new(RDF_TYPE.to_string())),
             &self
                 .graph
                 .create_uri_node(&Uri::new("http://schema.org/Organization".to_string())),
@@ -492,16 +439,19 @@ impl Profile {
                 &self.graph.create_literal_node(title.to_string()),
             ));
         }
+        self.attribute("add_work_experience");
     }
 
     pub fn add_profile_page(&mut self, url: &str) {
+        let url = canonical_url(url);
         self.graph.add_triple(&Triple::new(
             &self.graph.create_uri_node(&Uri::new("#me".to_string())),
             &self
                 .graph
                 .create_uri_node(&Uri::new("http://xmlns.com/foaf/0.1/page".to_string())),
-            &self.graph.create_uri_node(&Uri::new(url.to_string())),
+            &self.graph.create_uri_node(&Uri::new(url)),
         ));
+        self.attribute("add_profile_page");
     }
 
     pub fn add_account(&mut self, username: &str, account_holder_id_override: Option<&str>) {
@@ -518,18 +468,23 @@ impl Profile {
             &self
                 .graph
                 .create_uri_node(&Uri::new("http://xmlns.com/foaf/0.1/account".to_string())),
-            &self.graph.create_uri_node(&Uri::new(username.to_string())),
+            &self.graph.create_uri_node(&Uri::new(canonical_url(username))),
         ));
+        self.attribute("add_account");
     }
 
     pub fn add_facebook_friend(&mut self, name: &str, fb_profile_url: &str) {
-        let friend = self
-            .graph
-            .create_uri_node(&Uri::new(format!("#{}", clean_string(name))));
+        self.add_facebook_friend_as(&format!("#{}", clean_string(name)), name, fb_profile_url);
+    }
+
+    /// Like `add_facebook_friend`, but describes the friend as `node` (e.g.
+    /// `#Alice_Nguyen_2`), so friends who share a name stay apart.
+    pub fn add_facebook_friend_as(&mut self, node: &str, name: &str, fb_profile_url: &str) {
+        let friend = self.graph.create_uri_node(&Uri::new(node.to_string()));
 
         self.graph.add_triple(&Triple::new(
             &friend,
-            &self.graph.create_uri_node(&Uri::new("a".to_string())),
+            &self.graph.create_uri_node(&Uri::new(RDF_TYPE.to_string())),
             &self
                 .graph
                 .create_uri_node(&Uri::new("http://xmlns.com/foaf/0.1/Person".to_string())),
@@ -544,7 +499,8 @@ impl Profile {
         ));
 
         if !fb_profile_url.is_empty() {
-            self.add_account(fb_profile_url, Some(&clean_string(name)));
+            self.attribute("add_facebook_friend");
+            self.add_online_account(node, fb_profile_url);
         }
 
         self.graph.add_triple(&Triple::new(
@@ -554,16 +510,237 @@ impl Profile {
                 .create_uri_node(&Uri::new("http://xmlns.com/foaf/0.1/knows".to_string())),
             &friend,
         ));
+        self.attribute("add_facebook_friend");
+    }
+
+    /// Describes the Facebook profile at `profile_url` as a `foaf:OnlineAccount`
+    /// held by `holder` (e.g. `#Alice_Nguyen`).
+    pub fn add_online_account(&mut self, holder: &str, profile_url: &str) {
+        let profile_url = canonical_url(profile_url);
+        let holder_node = self.graph.create_uri_node(&Uri::new(holder.to_string()));
+        let account = self
+            .graph
+            .create_blank_node_with_id(format!("{}_facebook", holder.trim_start_matches('#')));
+        let foaf = |local: &str| Uri::new(format!("http://xmlns.com/foaf/0.1/{}", local));
+
+        self.graph.add_triple(&Triple::new(
+            &holder_node,
+            &self.graph.create_uri_node(&foaf("account")),
+            &account,
+        ));
+        self.graph.add_triple(&Triple::new(
+            &account,
+            &self.graph.create_uri_node(&Uri::new(RDF_TYPE.to_string())),
+            &self.graph.create_uri_node(&foaf("OnlineAccount")),
+        ));
+        self.graph.add_triple(&Triple::new(
+            &account,
+            &self.graph.create_uri_node(&foaf("accountServiceHomepage")),
+            &self
+                .graph
+                .create_uri_node(&Uri::new("https://www.facebook.com/".to_string())),
+        ));
+        if let Some(account_name) = facebook_account_name(&profile_url) {
+            self.graph.add_triple(&Triple::new(
+                &account,
+                &self.graph.create_uri_node(&foaf("accountName")),
+                &self.graph.create_literal_node(account_name),
+            ));
+        }
+        self.graph.add_triple(&Triple::new(
+            &account,
+            &self.graph.create_uri_node(&foaf("page")),
+            &self.graph.create_uri_node(&Uri::new(profile_url)),
+        ));
+        self.attribute("add_online_account");
+    }
+
+    /// Records when the friendship with `name` began, using a `schema:Role`
+    /// between `#me` and the friend. `date` is an `xsd:date` (YYYY-MM-DD).
+    pub fn add_friendship_start(&mut self, name: &str, date: &str) {
+        self.add_friendship_start_of(&format!("#{}", clean_string(name)), date);
+    }
+
+    /// Like `add_friendship_start`, for the friend described as `node`.
+    pub fn add_friendship_start_of(&mut self, node: &str, date: &str) {
+        let friend = self.graph.create_uri_node(&Uri::new(node.to_string()));
+        let role = self
+            .graph
+            .create_blank_node_with_id(format!("{}_friendship", node.trim_start_matches('#')));
+        let schema = |local: &str| Uri::new(format!("http://schema.org/{}", local));
+        self.graph.add_namespace(&Namespace::new(
+            "xsd".to_string(),
+            Uri::new(XSD.to_string()),
+        ));
+
+        self.graph.add_triple(&Triple::new(
+            &self.graph.create_uri_node(&Uri::new("#me".to_string())),
+            &self.graph.create_uri_node(&schema("knows")),
+            &role,
+        ));
+        self.graph.add_triple(&Triple::new(
+            &role,
+            &self.graph.create_uri_node(&Uri::new(RDF_TYPE.to_string())),
+            &self.graph.create_uri_node(&schema("Role")),
+        ));
+        self.graph.add_triple(&Triple::new(
+            &role,
+            &self.graph.create_uri_node(&schema("knows")),
+            &friend,
+        ));
+        self.graph.add_triple(&Triple::new(
+            &role,
+            &self.graph.create_uri_node(&schema("startDate")),
+            &self
+                .graph
+                .create_typed_literal_node(date.to_string(), &Uri::new(format!("{}date", XSD))),
+        ));
+        self.attribute("add_friendship_start");
+    }
+
+    /// Starts the ActivityStreams collection `#{collection}`, linked from
+    /// `#me` by `as:{link}` (`followers`, `following` or `streams`).
+    pub fn add_collection(&mut self, link: &str, collection: &str, title: &str) {
+        let collection = self
+            .graph
+            .create_uri_node(&Uri::new(format!("#{}", collection)));
+        let activity_streams = |local: &str| Uri::new(format!("{}{}", AS, local));
+        self.graph.add_namespace(&Namespace::new(
+            "as".to_string(),
+            Uri::new(AS.to_string()),
+        ));
+
+        self.graph.add_triple(&Triple::new(
+            &self.graph.create_uri_node(&Uri::new("#me".to_string())),
+            &self.graph.create_uri_node(&activity_streams(link)),
+            &collection,
+        ));
+        self.graph.add_triple(&Triple::new(
+            &collection,
+            &self.graph.create_uri_node(&Uri::new(RDF_TYPE.to_string())),
+            &self.graph.create_uri_node(&activity_streams("Collection")),
+        ));
+        self.graph.add_triple(&Triple::new(
+            &collection,
+            &self.graph.create_uri_node(&activity_streams("name")),
+            &self.graph.create_literal_node(title.to_string()),
+        ));
+        self.attribute("add_collection");
+    }
+
+    /// Adds the person called `name` to `#{collection}`, describing them the
+    /// way `add_facebook_friend` does unless that already happened.
+    pub fn add_collection_member(&mut self, collection: &str, name: &str) {
+        let person = self.describe_person(name);
+        self.graph.add_triple(&Triple::new(
+            &self
+                .graph
+                .create_uri_node(&Uri::new(format!("#{}", collection))),
+            &self.graph.create_uri_node(&Uri::new(format!("{}items", AS))),
+            &person,
+        ));
+        self.attribute("add_collection_member");
+    }
+
+    /// Describes the friend list called `name` as a `vcard:Group`, which Solid
+    /// access control can grant permissions to.
+    pub fn add_group(&mut self, name: &str) {
+        let group = self.graph.create_uri_node(&Uri::new(group_iri(name)));
+        self.graph.add_namespace(&Namespace::new(
+            "vcard".to_string(),
+            Uri::new(VCARD.to_string()),
+        ));
+
+        self.graph.add_triple(&Triple::new(
+            &group,
+            &self.graph.create_uri_node(&Uri::new(RDF_TYPE.to_string())),
+            &self.graph.create_uri_node(&Uri::new(format!("{}Group", VCARD))),
+        ));
+        self.graph.add_triple(&Triple::new(
+            &group,
+            &self.graph.create_uri_node(&Uri::new(format!("{}fn", VCARD))),
+            &self.graph.create_literal_node(name.to_string()),
+        ));
+        self.attribute("add_group");
+    }
+
+    /// Adds the friend called `name` to the group made by `add_group`.
+    pub fn add_group_member(&mut self, group: &str, name: &str) {
+        let person = self.describe_person(name);
+        self.graph.add_triple(&Triple::new(
+            &self.graph.create_uri_node(&Uri::new(group_iri(group))),
+            &self
+                .graph
+                .create_uri_node(&Uri::new(format!("{}hasMember", VCARD))),
+            &person,
+        ));
+        self.attribute("add_group_member");
+    }
+
+    /// The node of the person called `name`, typed and named unless it
+    /// already is.
+    fn describe_person(&mut self, name: &str) -> Node {
+        let person = self
+            .graph
+            .create_uri_node(&Uri::new(format!("#{}", clean_string(name))));
+        let foaf_name = self
+            .graph
+            .create_uri_node(&Uri::new("http://xmlns.com/foaf/0.1/name".to_string()));
+        if !self
+            .graph
+            .triples()
+            .iter()
+            .any(|t| t.subject == person && t.predicate == foaf_name)
+        {
+            self.graph.add_triple(&Triple::new(
+                &person,
+                &self.graph.create_uri_node(&Uri::new(RDF_TYPE.to_string())),
+                &self
+                    .graph
+                    .create_uri_node(&Uri::new("http://xmlns.com/foaf/0.1/Person".to_string())),
+            ));
+            self.graph.add_triple(&Triple::new(
+                &person,
+                &foaf_name,
+                &self.graph.create_literal_node(name.to_string()),
+            ));
+        }
+        person
     }
 
     pub fn write_to_string(&mut self) -> String {
         self.graph.serialize_turtle()
     }
+
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn origins(&self) -> &[Origin] {
+        &self.origins
+    }
+
+    pub fn into_graph(self) -> Graph {
+        self.graph
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::clean_string;
+    use super::{clean_string, facebook_account_name};
+
+    #[test]
+    fn account_names_come_from_the_profile_url() {
+        assert_eq!(
+            facebook_account_name("https://www.facebook.com/alice.nguyen.503").as_deref(),
+            Some("alice.nguyen.503")
+        );
+        assert_eq!(
+            facebook_account_name("https://www.facebook.com/profile.php?id=100012345").as_deref(),
+            Some("100012345")
+        );
+        assert_eq!(facebook_account_name("https://example.com/alice"), None);
+    }
 
     #[test]
     fn clean_string_replaces_spaces_with_underscores() {
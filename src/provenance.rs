@@ -0,0 +1,214 @@
+// ---------------------------------------------------------------------------
+// Provenance
+// Describes the generated document with PROV-O and DCTERMS: which software
+// produced it and when, from which export files (with their SHA-256 hashes
+// and detected generation), and the dates the export records about itself.
+// Consumers can then judge how fresh the data is and tell a Facebook import
+// apart from statements added by hand or by other apps.
+// ---------------------------------------------------------------------------
+
+use crate::rdf::{Graph, Namespace, Node, Triple, Uri, RDF_TYPE, XSD};
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PROV: &str = "http://www.w3.org/ns/prov#";
+const DCTERMS: &str = "http://purl.org/dc/terms/";
+const SPDX: &str = "http://spdx.org/rdf/terms#";
+const FOAF: &str = "http://xmlns.com/foaf/0.1/";
+const SCHEMA: &str = "http://schema.org/";
+
+const HOMEPAGE: &str = "https://github.com/bushidocodes/hatchling";
+
+/// An input file the document was derived from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Source {
+    /// File name, if known.
+    pub name: Option<String>,
+    /// Lowercase hex SHA-256 of the file contents.
+    pub sha256: String,
+    /// Export generation the file was read as, e.g. `profile_v2`.
+    pub version: String,
+}
+
+impl Source {
+    pub fn new(name: Option<&str>, contents: &str, version: &str) -> Source {
+        Source {
+            name: name.map(str::to_string),
+            sha256: sha256_hex(contents.as_bytes()),
+            version: version.to_string(),
+        }
+    }
+}
+
+/// Dates the export records about itself, as Unix timestamps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExportTimes {
+    /// When the Facebook account was registered.
+    pub registered: Option<u64>,
+    /// The most recent change recorded in the profile.
+    pub last_change: Option<u64>,
+}
+
+pub struct Provenance {
+    pub generated_at: SystemTime,
+    /// The profile first, then each friends file.
+    pub sources: Vec<Source>,
+    pub times: ExportTimes,
+}
+
+impl Provenance {
+    /// Adds the provenance of the document `<>` to `graph`. The registration
+    /// date is attached to the Facebook account, if the graph has one.
+    pub fn describe(&self, graph: &mut Graph) {
+        graph.add_namespace(&Namespace::new("prov".to_string(), Uri::new(PROV.to_string())));
+        graph.add_namespace(&Namespace::new("dcterms".to_string(), Uri::new(DCTERMS.to_string())));
+        graph.add_namespace(&Namespace::new("spdx".to_string(), Uri::new(SPDX.to_string())));
+        graph.add_namespace(&Namespace::new("xsd".to_string(), Uri::new(XSD.to_string())));
+
+        let document = uri("");
+        let activity = Node::Blank("hatchlingConversion".to_string());
+        let agent = Node::Blank("hatchlingSoftware".to_string());
+        let generated_at = date_time(unix_seconds(self.generated_at));
+        let mut add = |s: &Node, p: String, o: Node| graph.add_triple(&Triple::new(s, &uri(&p), &o));
+
+        add(&document, format!("{}wasGeneratedBy", PROV), activity.clone());
+        add(&document, format!("{}generatedAtTime", PROV), generated_at.clone());
+        add(&document, format!("{}created", DCTERMS), generated_at.clone());
+
+        add(&activity, RDF_TYPE.to_string(), uri(&format!("{}Activity", PROV)));
+        add(&activity, format!("{}endedAtTime", PROV), generated_at);
+        add(&activity, format!("{}wasAssociatedWith", PROV), agent.clone());
+
+        add(&agent, RDF_TYPE.to_string(), uri(&format!("{}SoftwareAgent", PROV)));
+        add(&agent, format!("{}name", FOAF), literal("hatchling"));
+        add(&agent, format!("{}softwareVersion", SCHEMA), literal(env!("CARGO_PKG_VERSION")));
+        add(&agent, format!("{}homepage", FOAF), uri(HOMEPAGE));
+
+        for (i, source) in self.sources.iter().enumerate() {
+            let entity = Node::Blank(format!("hatchlingSource{}", i));
+            let checksum = Node::Blank(format!("hatchlingSource{}Checksum", i));
+            add(&document, format!("{}wasDerivedFrom", PROV), entity.clone());
+            add(&activity, format!("{}used", PROV), entity.clone());
+            add(&entity, RDF_TYPE.to_string(), uri(&format!("{}Entity", PROV)));
+            if let Some(name) = &source.name {
+                add(&entity, format!("{}title", DCTERMS), literal(name));
+            }
+            add(&entity, format!("{}hasVersion", DCTERMS), literal(&source.version));
+            add(&entity, format!("{}checksum", SPDX), checksum.clone());
+            add(&checksum, format!("{}algorithm", SPDX), uri(&format!("{}checksumAlgorithm_sha256", SPDX)));
+            add(&checksum, format!("{}checksumValue", SPDX), literal(&source.sha256));
+            if i == 0 {
+                if let Some(last_change) = self.times.last_change {
+                    add(&entity, format!("{}modified", DCTERMS), date_time(last_change));
+                }
+            }
+        }
+
+        if let Some(registered) = self.times.registered {
+            let accounts: Vec<Node> = graph
+                .triples()
+                .iter()
+                .filter(|t| {
+                    t.subject == uri("#me")
+                        && t.predicate == uri(&format!("{}account", FOAF))
+                        && matches!(&t.object, Node::Uri(iri) if iri.starts_with("https://www.facebook.com/"))
+                })
+                .map(|t| t.object.clone())
+                .collect();
+            for account in accounts {
+                graph.add_triple(&Triple::new(
+                    &account,
+                    &uri(&format!("{}created", DCTERMS)),
+                    &date_time(registered),
+                ));
+            }
+        }
+    }
+}
+
+fn uri(iri: &str) -> Node {
+    Node::Uri(iri.to_string())
+}
+
+fn literal(value: &str) -> Node {
+    Node::Literal {
+        value: value.to_string(),
+        datatype: None,
+        language: None,
+    }
+}
+
+fn date_time(seconds: u64) -> Node {
+    Node::Literal {
+        value: format_date_time(seconds),
+        datatype: Some(format!("{}dateTime", XSD)),
+        language: None,
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Formats a Unix timestamp as an `xsd:dateTime` in UTC.
+pub fn format_date_time(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64;
+    let rest = seconds % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+/// Formats the UTC day of a Unix timestamp as an `xsd:date`.
+pub fn format_date(seconds: u64) -> String {
+    format_date_time(seconds)[..10].to_string()
+}
+
+/// Converts days since 1970-01-01 to a proleptic Gregorian (year, month, day).
+/// This is Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_date_time, sha256_hex};
+
+    #[test]
+    fn timestamps_are_formatted_in_utc() {
+        assert_eq!(format_date_time(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_date_time(1170892800), "2007-02-08T00:00:00Z");
+        assert_eq!(format_date_time(951782400 + 86_399), "2000-02-29T23:59:59Z");
+    }
+
+    #[test]
+    fn hashes_are_lowercase_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
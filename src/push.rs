@@ -0,0 +1,348 @@
+// ---------------------------------------------------------------------------
+// Uploading to a Solid pod over LDP
+// Resources are written with PUT (guarded by If-Match / If-None-Match so that
+// concurrent edits are never silently overwritten) or updated with PATCH.
+// Missing parent containers are created first, and a whole pod tree can be
+// uploaded below a base URL. Authentication is left to an
+// `Authenticator`, so callers can plug in whatever token flow their pod uses.
+// ---------------------------------------------------------------------------
+
+use crate::patch::PatchFormat;
+use crate::pod::PodFile;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::pkcs8::DecodePrivateKey;
+use p256::SecretKey;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::error;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LDP_BASIC_CONTAINER: &str = "<http://www.w3.org/ns/ldp#BasicContainer>; rel=\"type\"";
+
+/// Supplies the authentication headers for each request sent to the pod.
+pub trait Authenticator {
+    fn headers(&self, method: &str, url: &str) -> Vec<(String, String)>;
+}
+
+/// Sends no credentials; suitable for public-write test servers.
+pub struct Anonymous;
+
+impl Authenticator for Anonymous {
+    fn headers(&self, _method: &str, _url: &str) -> Vec<(String, String)> {
+        Vec::new()
+    }
+}
+
+/// `Authorization: Bearer <token>`.
+pub struct BearerToken(pub String);
+
+impl Authenticator for BearerToken {
+    fn headers(&self, _method: &str, _url: &str) -> Vec<(String, String)> {
+        vec![("Authorization".to_string(), format!("Bearer {}", self.0))]
+    }
+}
+
+/// A DPoP-bound access token and the P-256 key it is bound to. A proof is
+/// only good for one request, so every request gets a fresh one (RFC 9449).
+pub struct DpopToken {
+    pub access_token: String,
+    pub key: SigningKey,
+}
+
+impl DpopToken {
+    /// Reads the key from a PKCS#8 (`BEGIN PRIVATE KEY`) or SEC1
+    /// (`BEGIN EC PRIVATE KEY`) PEM file.
+    pub fn from_pem(access_token: &str, pem: &str) -> Result<DpopToken, Box<dyn error::Error>> {
+        let key = match SigningKey::from_pkcs8_pem(pem) {
+            Ok(key) => key,
+            Err(_) => SigningKey::from(SecretKey::from_sec1_pem(pem).map_err(|_| "not a P-256 private key in PEM")?),
+        };
+        Ok(DpopToken {
+            access_token: access_token.to_string(),
+            key,
+        })
+    }
+
+    /// A signed proof for sending `method` to `url`.
+    pub fn proof(&self, method: &str, url: &str) -> String {
+        let point = self.key.verifying_key().to_encoded_point(false);
+        let (x, y) = point.x().zip(point.y()).expect("an uncompressed point has both coordinates");
+        let header = json!({
+            "typ": "dpop+jwt",
+            "alg": "ES256",
+            "jwk": {"kty": "EC", "crv": "P-256", "x": URL_SAFE_NO_PAD.encode(x), "y": URL_SAFE_NO_PAD.encode(y)},
+        });
+        let mut jti = [0u8; 16];
+        getrandom::getrandom(&mut jti).expect("the system has a random number generator");
+        let iat = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let claims = json!({
+            "htm": method,
+            "htu": url.split(['?', '#']).next().unwrap_or(url),
+            "iat": iat,
+            "jti": jti.iter().map(|b| format!("{:02x}", b)).collect::<String>(),
+            "ath": URL_SAFE_NO_PAD.encode(Sha256::digest(self.access_token.as_bytes())),
+        });
+        let input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let signature: Signature = self.key.sign(input.as_bytes());
+        format!("{}.{}", input, URL_SAFE_NO_PAD.encode(signature.to_bytes()))
+    }
+}
+
+impl Authenticator for DpopToken {
+    fn headers(&self, method: &str, url: &str) -> Vec<(String, String)> {
+        vec![
+            ("Authorization".to_string(), format!("DPoP {}", self.access_token)),
+            ("DPoP".to_string(), self.proof(method, url)),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushOutcome {
+    Created,
+    Updated,
+    Patched,
+}
+
+#[derive(Debug)]
+pub enum PushError {
+    /// The resource changed on the pod after its ETag was read (412).
+    Conflict { url: String },
+    /// The pod answered with an unexpected status code.
+    Status { url: String, status: u16, body: String },
+    /// The request never produced an HTTP response.
+    Transport { url: String, message: String },
+}
+
+impl fmt::Display for PushError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushError::Conflict { url } => write!(
+                f,
+                "{} was modified on the pod since it was read; fetch it again before pushing",
+                url
+            ),
+            PushError::Status { url, status, body } => {
+                write!(f, "{} answered {}", url, status)?;
+                if !body.trim().is_empty() {
+                    write!(f, ": {}", body.trim())?;
+                }
+                Ok(())
+            }
+            PushError::Transport { url, message } => write!(f, "{}: {}", url, message),
+        }
+    }
+}
+
+impl error::Error for PushError {}
+
+pub struct PodClient {
+    agent: ureq::Agent,
+    auth: Box<dyn Authenticator>,
+}
+
+impl PodClient {
+    pub fn new(auth: impl Authenticator + 'static) -> PodClient {
+        PodClient {
+            agent: ureq::AgentBuilder::new().redirects(0).build(),
+            auth: Box::new(auth),
+        }
+    }
+
+    /// Writes `body` to `url`, creating missing parent containers.
+    /// An existing resource is only replaced if it still has the ETag seen here.
+    pub fn put(&self, url: &str, body: &str, content_type: &str) -> Result<PushOutcome, PushError> {
+        self.put_bytes(url, body.as_bytes(), content_type)
+    }
+
+    /// Like `put`, for media files.
+    pub fn put_bytes(&self, url: &str, body: &[u8], content_type: &str) -> Result<PushOutcome, PushError> {
+        let existing = self.head(url)?;
+        let etag = existing
+            .as_ref()
+            .and_then(|response| response.header("ETag").map(str::to_string));
+        let mut headers = vec![("Content-Type", content_type)];
+        match (&existing, &etag) {
+            (None, _) => {
+                for container in parent_containers(url) {
+                    self.ensure_container(&container)?;
+                }
+                headers.push(("If-None-Match", "*"));
+            }
+            (Some(_), Some(etag)) => headers.push(("If-Match", etag)),
+            // The server does not version this resource, so there is nothing to guard on
+            (Some(_), None) => {}
+        }
+        self.send("PUT", url, &headers, Some(body))?;
+        Ok(if existing.is_some() {
+            PushOutcome::Updated
+        } else {
+            PushOutcome::Created
+        })
+    }
+
+    /// Uploads a pod tree read by `pod::read_pod` below `base`, the URL of the
+    /// pod root. Every container is created, including empty ones such as the
+    /// inbox; their metadata and the storage root are left to the server.
+    /// Returns the URL and outcome of each document written.
+    pub fn put_pod(&self, base: &str, files: &[PodFile]) -> Result<Vec<(String, PushOutcome)>, PushError> {
+        let base = format!("{}/", base.trim_end_matches('/'));
+        let mut outcomes = Vec::new();
+        for file in files {
+            if file.path.is_empty() {
+                continue;
+            }
+            let url = format!("{}{}", base, file.path);
+            if file.path.ends_with('/') {
+                for container in parent_containers(&url).into_iter().chain([url.clone()]) {
+                    self.ensure_container(&container)?;
+                }
+                continue;
+            }
+            let outcome = self.put_bytes(&url, &file.body, file.content_type)?;
+            outcomes.push((url, outcome));
+        }
+        Ok(outcomes)
+    }
+
+    /// Applies a patch document to an existing resource.
+    pub fn patch(&self, url: &str, body: &str, format: PatchFormat) -> Result<PushOutcome, PushError> {
+        let mut headers = vec![("Content-Type", format.content_type())];
+        let etag = self.etag(url)?;
+        if let Some(etag) = &etag {
+            headers.push(("If-Match", etag));
+        }
+        self.send("PATCH", url, &headers, Some(body.as_bytes()))?;
+        Ok(PushOutcome::Patched)
+    }
+
+    /// Creates the container at `url` (which must end in '/') unless it exists.
+    pub fn ensure_container(&self, url: &str) -> Result<(), PushError> {
+        if self.exists(url)? {
+            return Ok(());
+        }
+        let headers = [
+            ("Content-Type", "text/turtle"),
+            ("Link", LDP_BASIC_CONTAINER),
+            ("If-None-Match", "*"),
+        ];
+        self.send("PUT", url, &headers, Some(b""))?;
+        Ok(())
+    }
+
+    fn exists(&self, url: &str) -> Result<bool, PushError> {
+        Ok(self.head(url)?.is_some())
+    }
+
+    /// Returns the current ETag of `url`, or `None` if the resource does not exist.
+    fn etag(&self, url: &str) -> Result<Option<String>, PushError> {
+        Ok(self
+            .head(url)?
+            .and_then(|response| response.header("ETag").map(str::to_string)))
+    }
+
+    fn head(&self, url: &str) -> Result<Option<ureq::Response>, PushError> {
+        match self.send("HEAD", url, &[], None) {
+            Ok(response) => Ok(Some(response)),
+            Err(PushError::Status { status: 404, .. }) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Option<&[u8]>,
+    ) -> Result<ureq::Response, PushError> {
+        let mut request = self.agent.request(method, url);
+        for (name, value) in self.auth.headers(method, url) {
+            request = request.set(&name, &value);
+        }
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+        let result = match body {
+            Some(body) => request.send_bytes(body),
+            None => request.call(),
+        };
+        match result {
+            Ok(response) => Ok(response),
+            Err(ureq::Error::Status(412, _)) => Err(PushError::Conflict {
+                url: url.to_string(),
+            }),
+            Err(ureq::Error::Status(status, response)) => Err(PushError::Status {
+                url: url.to_string(),
+                status,
+                body: response.into_string().unwrap_or_default(),
+            }),
+            Err(err) => Err(PushError::Transport {
+                url: url.to_string(),
+                message: err.to_string(),
+            }),
+        }
+    }
+}
+
+/// Lists the containers above `url`, outermost first, excluding the pod root.
+fn parent_containers(url: &str) -> Vec<String> {
+    let path_start = match url.find("://") {
+        Some(i) => url[i + 3..].find('/').map(|j| i + 3 + j).unwrap_or(url.len()),
+        None => return Vec::new(),
+    };
+    let trimmed = url.trim_end_matches('/');
+    let mut containers = Vec::new();
+    let mut search_end = trimmed.len();
+    while let Some(i) = trimmed[..search_end].rfind('/') {
+        if i <= path_start {
+            break;
+        }
+        containers.push(trimmed[..=i].to_string());
+        search_end = i;
+    }
+    containers.reverse();
+    containers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parent_containers, DpopToken};
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use p256::ecdsa::signature::Verifier;
+    use p256::ecdsa::Signature;
+
+    #[test]
+    fn dpop_proof_is_signed_with_the_key() {
+        let token = DpopToken::from_pem("token", include_str!("../tests/fixtures/dpop_key.pem")).unwrap();
+        let proof = token.proof("PUT", "https://pod.example/card?x=1");
+        let (input, signature) = proof.rsplit_once('.').unwrap();
+        let signature = Signature::from_slice(&URL_SAFE_NO_PAD.decode(signature).unwrap()).unwrap();
+        assert!(token.key.verifying_key().verify(input.as_bytes(), &signature).is_ok());
+        let claims = URL_SAFE_NO_PAD.decode(input.split('.').nth(1).unwrap()).unwrap();
+        let claims: serde_json::Value = serde_json::from_slice(&claims).unwrap();
+        assert_eq!(claims["htu"], "https://pod.example/card");
+    }
+
+    #[test]
+    fn parent_containers_lists_ancestors_outermost_first() {
+        assert_eq!(
+            parent_containers("https://pod.example/jane/profile/card"),
+            ["https://pod.example/jane/", "https://pod.example/jane/profile/"]
+        );
+        assert_eq!(
+            parent_containers("https://pod.example/inbox/"),
+            Vec::<String>::new()
+        );
+        assert_eq!(parent_containers("https://pod.example/card"), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,293 @@
+// ---------------------------------------------------------------------------
+// Minimal RDF graph + Turtle serializer — replaces the unmaintained `rdf` crate.
+// oxrdf/oxttl require absolute IRIs and cannot represent the relative-IRI
+// semantics that Solid profile documents require (e.g. <>, <#me>, <./>, <#>).
+// ---------------------------------------------------------------------------
+
+use serde_json::{json, Map, Value};
+
+pub const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+pub const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+pub struct Uri(String);
+
+impl Uri {
+    pub fn new(s: String) -> Self {
+        Uri(s)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Namespace {
+    pub prefix: String,
+    pub iri: String,
+}
+
+impl Namespace {
+    pub fn new(prefix: String, uri: Uri) -> Self {
+        Namespace { prefix, iri: uri.0 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Node {
+    Uri(String),
+    Blank(String),
+    Literal {
+        value: String,
+        datatype: Option<String>,
+        language: Option<String>,
+    },
+}
+
+impl Node {
+    pub fn is_blank(&self) -> bool {
+        matches!(self, Node::Blank(_))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Triple {
+    pub subject: Node,
+    pub predicate: Node,
+    pub object: Node,
+}
+
+impl Triple {
+    pub fn new(s: &Node, p: &Node, o: &Node) -> Self {
+        Triple {
+            subject: s.clone(),
+            predicate: p.clone(),
+            object: o.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    triples: Vec<Triple>,
+    namespaces: Vec<Namespace>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Graph {
+            triples: Vec::new(),
+            namespaces: Vec::new(),
+        }
+    }
+
+    pub fn add_namespace(&mut self, ns: &Namespace) {
+        if let Some(existing) = self.namespaces.iter_mut().find(|n| n.prefix == ns.prefix) {
+            existing.iri = ns.iri.clone();
+        } else {
+            self.namespaces.push(ns.clone());
+        }
+    }
+
+    pub fn namespaces(&self) -> &[Namespace] {
+        &self.namespaces
+    }
+
+    pub fn triples(&self) -> &[Triple] {
+        &self.triples
+    }
+
+    pub fn create_uri_node(&self, uri: &Uri) -> Node {
+        Node::Uri(uri.0.clone())
+    }
+
+    pub fn create_literal_node(&self, s: String) -> Node {
+        Node::Literal {
+            value: s,
+            datatype: None,
+            language: None,
+        }
+    }
+
+    pub fn create_typed_literal_node(&self, s: String, datatype: &Uri) -> Node {
+        Node::Literal {
+            value: s,
+            datatype: Some(datatype.0.clone()),
+            language: None,
+        }
+    }
+
+    pub fn create_blank_node_with_id(&self, id: String) -> Node {
+        Node::Blank(id)
+    }
+
+    pub fn add_triple(&mut self, t: &Triple) {
+        self.triples.push(t.clone());
+    }
+
+    /// Adds the namespaces and triples of `other`.
+    pub fn extend(&mut self, other: &Graph) {
+        for ns in other.namespaces() {
+            self.add_namespace(ns);
+        }
+        self.triples.extend(other.triples.iter().cloned());
+    }
+
+    pub fn format_node(&self, node: &Node) -> String {
+        match node {
+            Node::Uri(iri) => {
+                // Try namespace prefix compression
+                for ns in &self.namespaces {
+                    if !ns.iri.is_empty() && iri.starts_with(ns.iri.as_str()) {
+                        let local = &iri[ns.iri.len()..];
+                        if !is_local_name(local) {
+                            continue;
+                        }
+                        return if ns.prefix.is_empty() {
+                            format!(":{}", local)
+                        } else {
+                            format!("{}:{}", ns.prefix, local)
+                        };
+                    }
+                }
+                // Fall back to angle-bracket form; empty IRI means <> (this document)
+                if iri.is_empty() {
+                    "<>".to_string()
+                } else {
+                    format!("<{}>", iri)
+                }
+            }
+            Node::Blank(id) => format!("_:{}", id),
+            Node::Literal {
+                value,
+                datatype,
+                language,
+            } => {
+                let escaped = value
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\n', "\\n")
+                    .replace('\r', "\\r");
+                if let Some(lang) = language {
+                    format!("\"{}\"@{}", escaped, lang)
+                } else if let Some(dt) = datatype {
+                    format!(
+                        "\"{}\"^^{}",
+                        escaped,
+                        self.format_node(&Node::Uri(dt.clone()))
+                    )
+                } else {
+                    format!("\"{}\"", escaped)
+                }
+            }
+        }
+    }
+
+    /// Formats a node in predicate position, where rdf:type is written as "a".
+    pub fn format_predicate(&self, node: &Node) -> String {
+        match node {
+            Node::Uri(iri) if iri == RDF_TYPE => "a".to_string(),
+            _ => self.format_node(node),
+        }
+    }
+
+    pub fn format_triple(&self, t: &Triple) -> String {
+        format!(
+            "{} {} {} .",
+            self.format_node(&t.subject),
+            self.format_predicate(&t.predicate),
+            self.format_node(&t.object),
+        )
+    }
+
+    pub fn serialize_turtle(&self) -> String {
+        let mut out = String::new();
+        for ns in &self.namespaces {
+            out.push_str(&format!("@prefix {}: <{}> .\n", ns.prefix, ns.iri));
+        }
+        out.push('\n');
+        for t in &self.triples {
+            out.push_str(&self.format_triple(t));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Serializes the graph as flattened JSON-LD. Only absolute namespaces are
+    /// used for compaction; relative IRIs such as `#me` are left for the
+    /// consumer to resolve against the document URL.
+    pub fn serialize_jsonld(&self) -> String {
+        let mut context = Map::new();
+        for ns in &self.namespaces {
+            if !ns.prefix.is_empty() && ns.iri.contains("://") {
+                context.insert(ns.prefix.clone(), Value::String(ns.iri.clone()));
+            }
+        }
+
+        let mut order: Vec<String> = Vec::new();
+        let mut nodes: Map<String, Value> = Map::new();
+        for t in &self.triples {
+            let id = match &t.subject {
+                Node::Uri(iri) => iri.clone(),
+                Node::Blank(id) => format!("_:{}", id),
+                Node::Literal { .. } => continue,
+            };
+            if !nodes.contains_key(&id) {
+                order.push(id.clone());
+                nodes.insert(id.clone(), json!({ "@id": id }));
+            }
+            let node = nodes[&id].as_object_mut().unwrap();
+            let (key, value) = match (&t.predicate, &t.object) {
+                (Node::Uri(p), Node::Uri(o)) if p == RDF_TYPE => {
+                    ("@type".to_string(), Value::String(self.compact_jsonld(o, &context)))
+                }
+                (Node::Uri(p), object) => (self.compact_jsonld(p, &context), self.jsonld_object(object)),
+                _ => continue,
+            };
+            node.entry(key)
+                .or_insert_with(|| Value::Array(Vec::new()))
+                .as_array_mut()
+                .unwrap()
+                .push(value);
+        }
+
+        let graph: Vec<Value> = order.into_iter().map(|id| nodes[&id].clone()).collect();
+        let document = json!({ "@context": context, "@graph": graph });
+        serde_json::to_string_pretty(&document).unwrap()
+    }
+
+    fn compact_jsonld(&self, iri: &str, context: &Map<String, Value>) -> String {
+        for (prefix, ns) in context {
+            let ns = ns.as_str().unwrap();
+            if iri.starts_with(ns) && is_local_name(&iri[ns.len()..]) {
+                return format!("{}:{}", prefix, &iri[ns.len()..]);
+            }
+        }
+        iri.to_string()
+    }
+
+    fn jsonld_object(&self, node: &Node) -> Value {
+        match node {
+            Node::Uri(iri) => json!({ "@id": iri }),
+            Node::Blank(id) => json!({ "@id": format!("_:{}", id) }),
+            Node::Literal {
+                value,
+                datatype,
+                language,
+            } => {
+                let mut literal = json!({ "@value": value });
+                if let Some(lang) = language {
+                    literal["@language"] = Value::String(lang.clone());
+                } else if let Some(dt) = datatype {
+                    literal["@type"] = Value::String(dt.clone());
+                }
+                literal
+            }
+        }
+    }
+}
+
+/// Whether `local` can be written after a prefix without escaping.
+fn is_local_name(local: &str) -> bool {
+    !local.starts_with(['-', '.'])
+        && !local.ends_with('.')
+        && local
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
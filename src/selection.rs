@@ -0,0 +1,132 @@
+// ---------------------------------------------------------------------------
+// Field selection
+// Selectors name the parts of the Facebook export that get turned into
+// triples, e.g. `phone`, `birthday.year` or `friends.urls`. Selectors are
+// hierarchical: excluding `birthday` also drops `birthday.year`, and including
+// `birthday.year` pulls in the birthday it belongs to.
+// ---------------------------------------------------------------------------
+
+use std::fmt;
+
+/// Every selector understood by `Selection`, with a short description.
+pub const SELECTORS: &[(&str, &str)] = &[
+    ("name", "full, first and last name"),
+    ("name.full", "full name"),
+    ("name.first", "first name"),
+    ("name.last", "last name"),
+    ("gender", "gender"),
+    ("birthday", "birthday (month and day)"),
+    ("birthday.year", "year of birth, as part of a full birth date"),
+    ("phone", "phone numbers"),
+    ("email", "email addresses"),
+    ("username", "Facebook account"),
+    ("profile_page", "Facebook profile page"),
+    ("work", "employers"),
+    ("work.title", "job titles"),
+    ("education", "schools graduated from"),
+    ("current_city", "current city"),
+    ("hometown", "hometown"),
+    ("friends", "friends list"),
+    ("friends.urls", "links to friends' Facebook profiles"),
+    ("connections", "followers, followed accounts, friend requests and removed friends"),
+    ("connections.lists", "friend lists such as Close Friends, as groups"),
+    ("posts", "posts: status updates, shared links, places and tagged friends"),
+    ("photos", "photo albums, with the photos copied into the pod"),
+    ("photos.profile_picture", "newest profile picture, as the picture on the card"),
+];
+
+#[derive(Debug)]
+pub struct UnknownSelector(pub String);
+
+impl fmt::Display for UnknownSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let known: Vec<&str> = SELECTORS.iter().map(|(name, _)| *name).collect();
+        write!(
+            f,
+            "unknown field selector '{}' (expected one of: {})",
+            self.0,
+            known.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownSelector {}
+
+/// Which parts of the export to convert. The default selects everything.
+#[derive(Clone, Debug, Default)]
+pub struct Selection {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl Selection {
+    pub fn all() -> Selection {
+        Selection::default()
+    }
+
+    /// Restricts the conversion to `selector` (and whatever else is included).
+    pub fn include(mut self, selector: &str) -> Result<Selection, UnknownSelector> {
+        self.include.push(known(selector)?);
+        Ok(self)
+    }
+
+    /// Leaves `selector` and everything below it out of the conversion.
+    pub fn exclude(mut self, selector: &str) -> Result<Selection, UnknownSelector> {
+        self.exclude.push(known(selector)?);
+        Ok(self)
+    }
+
+    pub fn is_selected(&self, selector: &str) -> bool {
+        let included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|i| covers(i, selector) || covers(selector, i));
+        included && !self.exclude.iter().any(|e| covers(e, selector))
+    }
+}
+
+fn known(selector: &str) -> Result<String, UnknownSelector> {
+    let selector = selector.trim();
+    if SELECTORS.iter().any(|(name, _)| *name == selector) {
+        Ok(selector.to_string())
+    } else {
+        Err(UnknownSelector(selector.to_string()))
+    }
+}
+
+/// Whether `outer` is `inner` or one of its ancestors.
+fn covers(outer: &str, inner: &str) -> bool {
+    inner == outer
+        || inner
+            .strip_prefix(outer)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Selection;
+
+    #[test]
+    fn exclusions_cover_nested_selectors() {
+        let selection = Selection::all().exclude("birthday").unwrap();
+        assert!(!selection.is_selected("birthday"));
+        assert!(!selection.is_selected("birthday.year"));
+        assert!(selection.is_selected("phone"));
+    }
+
+    #[test]
+    fn inclusions_pull_in_parents_but_not_siblings() {
+        let selection = Selection::all().include("name.first").unwrap();
+        assert!(selection.is_selected("name"));
+        assert!(selection.is_selected("name.first"));
+        assert!(!selection.is_selected("name.last"));
+        assert!(!selection.is_selected("phone"));
+    }
+
+    #[test]
+    fn unknown_selectors_are_rejected() {
+        let err = Selection::all().exclude("phones").unwrap_err();
+        assert!(err.to_string().contains("'phones'"));
+    }
+}
@@ -0,0 +1,371 @@
+// ---------------------------------------------------------------------------
+// Local LDP preview server
+// Hosts generated resources on localhost so that Solid apps and validators can
+// be pointed at a profile before it is published. Each resource is offered as
+// Turtle, JSON-LD or HTML depending on the Accept header, and every container
+// above a resource is listed with ldp:contains. Read-only by design.
+// ---------------------------------------------------------------------------
+
+use crate::rdf::{Graph, Namespace, Node, Triple, Uri, RDF_TYPE};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+const LDP: &str = "http://www.w3.org/ns/ldp#";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Turtle,
+    JsonLd,
+    Html,
+}
+
+impl Format {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Format::Turtle => "text/turtle",
+            Format::JsonLd => "application/ld+json",
+            Format::Html => "text/html; charset=utf-8",
+        }
+    }
+}
+
+/// Picks the best representation for an Accept header, or `None` if nothing
+/// offered is acceptable. A missing header or `*/*` yields Turtle.
+pub fn negotiate(accept: Option<&str>) -> Option<Format> {
+    let accept = match accept {
+        Some(a) if !a.trim().is_empty() => a,
+        _ => return Some(Format::Turtle),
+    };
+    let mut ranges: Vec<(f32, usize, &str)> = accept
+        .split(',')
+        .enumerate()
+        .map(|(i, range)| {
+            let mut params = range.split(';');
+            let media = params.next().unwrap_or("").trim();
+            let q = params
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .filter_map(|q| q.parse().ok())
+                .next()
+                .unwrap_or(1.0);
+            (q, i, media)
+        })
+        .filter(|(q, _, _)| *q > 0.0)
+        .collect();
+    ranges.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    ranges.into_iter().find_map(|(_, _, media)| {
+        match media.to_ascii_lowercase().as_str() {
+            "text/turtle" | "text/*" | "*/*" => Some(Format::Turtle),
+            "application/ld+json" | "application/json" | "application/*" => Some(Format::JsonLd),
+            "text/html" | "application/xhtml+xml" => Some(Format::Html),
+            _ => None,
+        }
+    })
+}
+
+#[derive(Default)]
+pub struct Site {
+    resources: Vec<(String, Graph)>,
+}
+
+impl Site {
+    pub fn new() -> Site {
+        Site::default()
+    }
+
+    /// Adds a resource at an absolute path such as `/profile/card`.
+    pub fn add(&mut self, path: &str, graph: Graph) {
+        self.resources.push((path.to_string(), graph));
+    }
+
+    fn resource(&self, path: &str) -> Option<&Graph> {
+        self.resources
+            .iter()
+            .find(|(p, _)| p == path)
+            .map(|(_, g)| g)
+    }
+
+    /// Builds the listing for `path` if it is a container above any resource.
+    fn container(&self, path: &str) -> Option<Graph> {
+        if !path.ends_with('/') {
+            return None;
+        }
+        let mut members: Vec<String> = Vec::new();
+        for (resource, _) in &self.resources {
+            if let Some(rest) = resource.strip_prefix(path) {
+                let member = match rest.find('/') {
+                    Some(i) => &rest[..=i],
+                    None => rest,
+                };
+                if !member.is_empty() && !members.iter().any(|m| m == member) {
+                    members.push(member.to_string());
+                }
+            }
+        }
+        if members.is_empty() && path != "/" {
+            return None;
+        }
+        let mut graph = Graph::new();
+        graph.add_namespace(&Namespace::new("ldp".to_string(), Uri::new(LDP.to_string())));
+        let this = Node::Uri(String::new());
+        let is_a = Node::Uri(RDF_TYPE.to_string());
+        for class in ["BasicContainer", "Container"] {
+            graph.add_triple(&Triple::new(&this, &is_a, &Node::Uri(format!("{}{}", LDP, class))));
+        }
+        for member in members {
+            graph.add_triple(&Triple::new(
+                &this,
+                &Node::Uri(format!("{}contains", LDP)),
+                &Node::Uri(member),
+            ));
+        }
+        Some(graph)
+    }
+}
+
+pub struct Server {
+    listener: TcpListener,
+    site: Arc<Site>,
+}
+
+impl Server {
+    pub fn bind(addr: &str, site: Site) -> io::Result<Server> {
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+            site: Arc::new(site),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves requests until the process exits.
+    pub fn run(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let site = Arc::clone(&self.site);
+            thread::spawn(move || {
+                let _ = handle(stream, &site);
+            });
+        }
+        Ok(())
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    accept: Option<String>,
+    origin: Option<String>,
+}
+
+fn read_request(stream: &TcpStream) -> io::Result<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let target = parts.next().unwrap_or("/");
+    let path = target.split(['?', '#']).next().unwrap_or("/").to_string();
+    let mut request = Request {
+        method,
+        path,
+        accept: None,
+        origin: None,
+    };
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "accept" => request.accept = Some(value.trim().to_string()),
+                "origin" => request.origin = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+    Ok(request)
+}
+
+fn handle(mut stream: TcpStream, site: &Site) -> io::Result<()> {
+    let request = read_request(&stream)?;
+    let mut headers: Vec<(&str, String)> = vec![
+        (
+            "Access-Control-Allow-Origin",
+            request.origin.clone().unwrap_or_else(|| "*".to_string()),
+        ),
+        (
+            "Access-Control-Expose-Headers",
+            "Link, ETag, Content-Type, Allow, Vary".to_string(),
+        ),
+        ("Allow", "GET, HEAD, OPTIONS".to_string()),
+        ("Vary", "Accept, Origin".to_string()),
+    ];
+
+    let (graph, is_container) = match site.resource(&request.path) {
+        Some(graph) => (Some(graph.clone()), false),
+        None => (site.container(&request.path), true),
+    };
+
+    let (status, body) = match (request.method.as_str(), graph) {
+        ("OPTIONS", _) => {
+            headers.push(("Access-Control-Allow-Methods", "GET, HEAD, OPTIONS".to_string()));
+            headers.push(("Access-Control-Allow-Headers", "Accept, Authorization".to_string()));
+            ("204 No Content", String::new())
+        }
+        ("GET" | "HEAD", Some(graph)) => match negotiate(request.accept.as_deref()) {
+            Some(format) => {
+                let body = match format {
+                    Format::Turtle => graph.serialize_turtle(),
+                    Format::JsonLd => graph.serialize_jsonld(),
+                    Format::Html => render_html(&graph, &request.path),
+                };
+                headers.push(("Content-Type", format.content_type().to_string()));
+                headers.push(("ETag", etag(&body)));
+                headers.push(("Link", format!("<{}Resource>; rel=\"type\"", LDP)));
+                if is_container {
+                    headers.push(("Link", format!("<{}BasicContainer>; rel=\"type\"", LDP)));
+                    headers.push(("Link", format!("<{}Container>; rel=\"type\"", LDP)));
+                }
+                if request.path == "/" {
+                    headers.push((
+                        "Link",
+                        "<http://www.w3.org/ns/pim/space#Storage>; rel=\"type\"".to_string(),
+                    ));
+                }
+                ("200 OK", body)
+            }
+            None => (
+                "406 Not Acceptable",
+                "Available representations: text/turtle, application/ld+json, text/html\n"
+                    .to_string(),
+            ),
+        },
+        ("GET" | "HEAD", None) => ("404 Not Found", String::new()),
+        _ => ("405 Method Not Allowed", String::new()),
+    };
+
+    let mut response = format!("HTTP/1.1 {}\r\nConnection: close\r\n", status);
+    for (name, value) in &headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+    if request.method != "HEAD" {
+        response.push_str(&body);
+    }
+    stream.write_all(response.as_bytes())?;
+    stream.flush()
+}
+
+fn etag(body: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A human-readable rendering that groups statements by subject.
+fn render_html(graph: &Graph, path: &str) -> String {
+    let title = graph
+        .triples()
+        .iter()
+        .find(|t| {
+            t.subject == Node::Uri("#me".to_string())
+                && t.predicate == Node::Uri("http://xmlns.com/foaf/0.1/name".to_string())
+        })
+        .map(|t| match &t.object {
+            Node::Literal { value, .. } => value.clone(),
+            _ => path.to_string(),
+        })
+        .unwrap_or_else(|| path.to_string());
+
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n", escape_html(&title)));
+    out.push_str("<link rel=\"alternate\" type=\"text/turtle\" href=\"\">\n");
+    out.push_str("<link rel=\"alternate\" type=\"application/ld+json\" href=\"\">\n");
+    out.push_str("</head>\n<body>\n");
+    out.push_str(&format!("<h1>{}</h1>\n", escape_html(&title)));
+
+    let mut subjects: Vec<&Node> = Vec::new();
+    for t in graph.triples() {
+        if !subjects.contains(&&t.subject) {
+            subjects.push(&t.subject);
+        }
+    }
+    for subject in subjects {
+        let label = graph.format_node(subject);
+        match subject {
+            Node::Uri(iri) if iri.starts_with('#') => {
+                let anchor = &iri[1..];
+                out.push_str(&format!(
+                    "<h2 id=\"{}\">{}</h2>\n",
+                    escape_html(anchor),
+                    escape_html(&label)
+                ));
+            }
+            _ => out.push_str(&format!("<h2>{}</h2>\n", escape_html(&label))),
+        }
+        out.push_str("<table>\n");
+        for t in graph.triples().iter().filter(|t| &t.subject == subject) {
+            let object = match &t.object {
+                Node::Uri(iri) => format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape_html(if iri.is_empty() { path } else { iri }),
+                    escape_html(&graph.format_node(&t.object))
+                ),
+                Node::Literal { value, .. } => escape_html(value),
+                Node::Blank(_) => escape_html(&graph.format_node(&t.object)),
+            };
+            out.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td></tr>\n",
+                escape_html(&graph.format_predicate(&t.predicate)),
+                object
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{negotiate, Format};
+
+    #[test]
+    fn negotiate_defaults_to_turtle() {
+        assert_eq!(negotiate(None), Some(Format::Turtle));
+        assert_eq!(negotiate(Some("*/*")), Some(Format::Turtle));
+    }
+
+    #[test]
+    fn negotiate_prefers_highest_quality() {
+        assert_eq!(
+            negotiate(Some("text/turtle;q=0.5, application/ld+json")),
+            Some(Format::JsonLd)
+        );
+        assert_eq!(
+            negotiate(Some("text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")),
+            Some(Format::Html)
+        );
+    }
+
+    #[test]
+    fn negotiate_rejects_unsupported_types() {
+        assert_eq!(negotiate(Some("image/png")), None);
+        assert_eq!(negotiate(Some("text/turtle;q=0")), None);
+    }
+}
@@ -0,0 +1,148 @@
+// ---------------------------------------------------------------------------
+// Timestamped facts
+// Most DYI records say when they were made: when a friendship started, when
+// the current city was set, and so on. Those times are attached to the facts
+// about the person as standard RDF reification, so every other part of
+// hatchling (diff, patch, split, base resolution) keeps working on a plain
+// graph. `serialize_turtle_star` writes the same statements as RDF-star
+// annotations for consumers that understand Turtle-star:
+//
+//     << :me foaf:knows :Alice >> dcterms:created "2007-09-25T00:00:00Z"^^xsd:dateTime .
+// ---------------------------------------------------------------------------
+
+use crate::profile_builder::Profile;
+use crate::provenance::format_date_time;
+use crate::rdf::{Graph, Namespace, Node, Triple, Uri, RDF_TYPE, XSD};
+use std::collections::HashMap;
+
+pub const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const DCTERMS: &str = "http://purl.org/dc/terms/";
+
+/// Adds a reified statement with `dcterms:created` to `graph` for each
+/// timestamped fact about `#me` in `profile`.
+pub fn reify(profile: &Profile, graph: &mut Graph) {
+    let facts: Vec<(&Triple, u64)> = profile
+        .graph()
+        .triples()
+        .iter()
+        .zip(profile.origins())
+        .filter(|(t, _)| t.subject == Node::Uri("#me".to_string()))
+        .filter_map(|(t, origin)| Some((t, origin.timestamp?)))
+        .collect();
+    if facts.is_empty() {
+        return;
+    }
+    graph.add_namespace(&Namespace::new("rdf".to_string(), Uri::new(RDF.to_string())));
+    graph.add_namespace(&Namespace::new("dcterms".to_string(), Uri::new(DCTERMS.to_string())));
+    graph.add_namespace(&Namespace::new("xsd".to_string(), Uri::new(XSD.to_string())));
+
+    let uri = |iri: String| Node::Uri(iri);
+    for (i, (fact, timestamp)) in facts.into_iter().enumerate() {
+        let statement = Node::Blank(format!("hatchlingFact{}", i));
+        let created = Node::Literal {
+            value: format_date_time(timestamp),
+            datatype: Some(format!("{}dateTime", XSD)),
+            language: None,
+        };
+        for (predicate, object) in [
+            (RDF_TYPE.to_string(), uri(format!("{}Statement", RDF))),
+            (format!("{}subject", RDF), fact.subject.clone()),
+            (format!("{}predicate", RDF), fact.predicate.clone()),
+            (format!("{}object", RDF), fact.object.clone()),
+            (format!("{}created", DCTERMS), created),
+        ] {
+            graph.add_triple(&Triple::new(&statement, &uri(predicate), &object));
+        }
+    }
+}
+
+/// Serializes `graph` as Turtle-star, writing reified statements as
+/// annotations on quoted triples. Everything else is written as in
+/// `Graph::serialize_turtle`.
+pub fn serialize_turtle_star(graph: &Graph) -> String {
+    let statements = statements(graph);
+    let mut out = String::new();
+    for ns in graph.namespaces() {
+        out.push_str(&format!("@prefix {}: <{}> .\n", ns.prefix, ns.iri));
+    }
+    out.push('\n');
+    for t in graph.triples() {
+        match statements.get(&t.subject) {
+            None => out.push_str(&graph.format_triple(t)),
+            Some(quoted) if !is_reification(t) => out.push_str(&format!(
+                "<< {} >> {} {} .",
+                graph.format_triple(quoted).trim_end_matches(" ."),
+                graph.format_predicate(&t.predicate),
+                graph.format_node(&t.object)
+            )),
+            Some(_) => continue,
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Finds the blank nodes that reify exactly one triple, and that triple.
+pub fn statements(graph: &Graph) -> HashMap<Node, Triple> {
+    let value = |node: &Node, predicate: &str| -> Option<Node> {
+        let mut values = graph
+            .triples()
+            .iter()
+            .filter(|t| t.subject == *node && t.predicate == Node::Uri(predicate.to_string()));
+        match (values.next(), values.next()) {
+            (Some(t), None) => Some(t.object.clone()),
+            _ => None,
+        }
+    };
+    graph
+        .triples()
+        .iter()
+        .filter(|t| {
+            t.subject.is_blank()
+                && t.predicate == Node::Uri(RDF_TYPE.to_string())
+                && t.object == Node::Uri(format!("{}Statement", RDF))
+        })
+        .filter_map(|t| {
+            let quoted = Triple::new(
+                &value(&t.subject, &format!("{}subject", RDF))?,
+                &value(&t.subject, &format!("{}predicate", RDF))?,
+                &value(&t.subject, &format!("{}object", RDF))?,
+            );
+            Some((t.subject.clone(), quoted))
+        })
+        .collect()
+}
+
+/// Whether `t` is one of the triples that make up a reification.
+fn is_reification(t: &Triple) -> bool {
+    let Node::Uri(predicate) = &t.predicate else {
+        return false;
+    };
+    predicate == RDF_TYPE
+        || [format!("{}subject", RDF), format!("{}predicate", RDF), format!("{}object", RDF)]
+            .contains(predicate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reify, serialize_turtle_star};
+    use crate::profile_builder::Profile;
+
+    #[test]
+    fn timestamped_facts_become_star_annotations() {
+        let mut profile = Profile::new();
+        profile.set_source("friends[0]");
+        profile.set_timestamp(1190678400);
+        profile.add_facebook_friend("Alice Nguyen", "");
+        let mut graph = profile.graph().clone();
+        reify(&profile, &mut graph);
+
+        let star = serialize_turtle_star(&graph);
+        assert!(star.contains(
+            "<< :me foaf:knows :Alice_Nguyen >> dcterms:created \"2007-09-25T00:00:00Z\"^^xsd:dateTime .\n"
+        ));
+        assert!(star.contains(":me foaf:knows :Alice_Nguyen .\n"));
+        assert!(!star.contains("rdf:Statement"));
+        assert!(graph.serialize_turtle().contains("_:hatchlingFact0 a rdf:Statement ."));
+    }
+}
@@ -0,0 +1,791 @@
+// ---------------------------------------------------------------------------
+// Turtle parser — reads Turtle documents back into an `rdf::Graph`.
+// Relative IRIs are kept relative unless the document declares an @base, so
+// that a parsed card compares equal to the one `Profile` built in memory.
+// ---------------------------------------------------------------------------
+
+use crate::rdf::{Graph, Namespace, Node, Triple, Uri, RDF_TYPE, XSD};
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}, column {}", self.message, self.line, self.column)
+    }
+}
+
+impl error::Error for ParseError {}
+
+pub fn parse(input: &str) -> Result<Graph, ParseError> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+        line: 1,
+        column: 1,
+        base: None,
+        graph: Graph::new(),
+        blank_count: 0,
+        statements: HashMap::new(),
+    };
+    parser.parse_document()?;
+    Ok(parser.graph)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+    base: Option<String>,
+    graph: Graph,
+    blank_count: usize,
+    /// The `rdf:Statement` node of each quoted triple read so far.
+    statements: HashMap<Triple, Node>,
+}
+
+impl Parser {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars().enumerate().all(|(i, c)| self.peek_at(i) == Some(c))
+    }
+
+    fn starts_with_keyword(&self, kw: &str) -> bool {
+        kw.chars()
+            .enumerate()
+            .all(|(i, c)| self.peek_at(i).map(|p| p.to_ascii_uppercase()) == Some(c))
+            && self
+                .peek_at(kw.len())
+                .map(|c| c.is_whitespace() || c == '<')
+                .unwrap_or(true)
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        self.skip_ws();
+        if self.peek() == Some(c) {
+            self.bump();
+            Ok(())
+        } else {
+            self.error(format!("expected '{}'", c))
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.bump();
+            } else if c == '#' {
+                while let Some(c) = self.bump() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn new_blank(&mut self) -> Node {
+        self.blank_count += 1;
+        Node::Blank(format!("genid{}", self.blank_count))
+    }
+
+    fn parse_document(&mut self) -> Result<(), ParseError> {
+        loop {
+            self.skip_ws();
+            if self.peek().is_none() {
+                return Ok(());
+            }
+            if self.starts_with("@prefix") {
+                self.pos += "@prefix".len();
+                self.column += "@prefix".len();
+                self.parse_prefix()?;
+                self.expect('.')?;
+            } else if self.starts_with("@base") {
+                self.pos += "@base".len();
+                self.column += "@base".len();
+                self.parse_base()?;
+                self.expect('.')?;
+            } else if self.starts_with_keyword("PREFIX") {
+                self.pos += "PREFIX".len();
+                self.column += "PREFIX".len();
+                self.parse_prefix()?;
+            } else if self.starts_with_keyword("BASE") {
+                self.pos += "BASE".len();
+                self.column += "BASE".len();
+                self.parse_base()?;
+            } else {
+                self.parse_triples()?;
+                self.expect('.')?;
+            }
+        }
+    }
+
+    fn parse_prefix(&mut self) -> Result<(), ParseError> {
+        self.skip_ws();
+        let mut prefix = String::new();
+        while let Some(c) = self.peek() {
+            if c == ':' {
+                break;
+            }
+            if !(c.is_alphanumeric() || c == '_' || c == '-' || c == '.') {
+                return self.error(format!("invalid character '{}' in prefix", c));
+            }
+            prefix.push(c);
+            self.bump();
+        }
+        self.expect(':')?;
+        self.skip_ws();
+        let iri = self.parse_iri_ref()?;
+        self.graph
+            .add_namespace(&Namespace::new(prefix, Uri::new(iri)));
+        Ok(())
+    }
+
+    fn parse_base(&mut self) -> Result<(), ParseError> {
+        self.skip_ws();
+        let iri = self.parse_iri_ref()?;
+        self.base = Some(iri);
+        Ok(())
+    }
+
+    fn parse_triples(&mut self) -> Result<(), ParseError> {
+        self.skip_ws();
+        let subject = match self.peek() {
+            Some('[') => {
+                let node = self.parse_blank_node_property_list()?;
+                self.skip_ws();
+                // "[ ... ] ." is a complete statement on its own
+                if self.peek() == Some('.') {
+                    return Ok(());
+                }
+                node
+            }
+            Some('(') => self.parse_collection()?,
+            Some('<') if self.peek_at(1) == Some('<') => self.parse_quoted_triple()?,
+            _ => self.parse_resource()?,
+        };
+        self.parse_predicate_object_list(&subject)
+    }
+
+    /// Reads a Turtle-star quoted triple `<< s p o >>` into an `rdf:Statement`
+    /// (see `timestamps`), so annotated documents compare equal to reified ones.
+    fn parse_quoted_triple(&mut self) -> Result<Node, ParseError> {
+        self.expect('<')?;
+        self.expect('<')?;
+        self.skip_ws();
+        let subject = match self.peek() {
+            Some('<') if self.peek_at(1) == Some('<') => self.parse_quoted_triple()?,
+            _ => self.parse_resource()?,
+        };
+        self.skip_ws();
+        let predicate = self.parse_predicate()?;
+        self.skip_ws();
+        let object = self.parse_object()?;
+        self.skip_ws();
+        self.expect('>')?;
+        self.expect('>')?;
+        Ok(self.statement(Triple::new(&subject, &predicate, &object)))
+    }
+
+    /// The `rdf:Statement` for `triple`, reified the first time it is quoted.
+    fn statement(&mut self, triple: Triple) -> Node {
+        if let Some(statement) = self.statements.get(&triple) {
+            return statement.clone();
+        }
+        let statement = self.new_blank();
+        let rdf = |local: &str| Node::Uri(format!("{}{}", RDF, local));
+        for (p, o) in [
+            (Node::Uri(RDF_TYPE.to_string()), rdf("Statement")),
+            (rdf("subject"), triple.subject.clone()),
+            (rdf("predicate"), triple.predicate.clone()),
+            (rdf("object"), triple.object.clone()),
+        ] {
+            self.graph.add_triple(&Triple::new(&statement, &p, &o));
+        }
+        self.statements.insert(triple, statement.clone());
+        statement
+    }
+
+    /// Reads an annotation `{| p o ; ... |}` on the triple just asserted.
+    fn parse_annotation(&mut self, triple: Triple) -> Result<(), ParseError> {
+        self.expect('{')?;
+        self.expect('|')?;
+        let statement = self.statement(triple);
+        self.parse_predicate_object_list(&statement)?;
+        self.skip_ws();
+        self.expect('|')?;
+        self.expect('}')
+    }
+
+    fn parse_predicate_object_list(&mut self, subject: &Node) -> Result<(), ParseError> {
+        loop {
+            self.skip_ws();
+            let predicate = self.parse_predicate()?;
+            loop {
+                self.skip_ws();
+                let object = self.parse_object()?;
+                let triple = Triple::new(subject, &predicate, &object);
+                self.graph.add_triple(&triple);
+                self.skip_ws();
+                if self.starts_with("{|") {
+                    self.parse_annotation(triple)?;
+                    self.skip_ws();
+                }
+                if self.peek() == Some(',') {
+                    self.bump();
+                } else {
+                    break;
+                }
+            }
+            self.skip_ws();
+            if self.peek() != Some(';') {
+                return Ok(());
+            }
+            while self.peek() == Some(';') {
+                self.bump();
+                self.skip_ws();
+            }
+            // A trailing ';' may be followed directly by the end of the list
+            if matches!(self.peek(), Some('.') | Some(']') | Some('|') | None) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_predicate(&mut self) -> Result<Node, ParseError> {
+        if self.peek() == Some('a')
+            && self
+                .peek_at(1)
+                .map(|c| c.is_whitespace() || c == '<' || c == '[' || c == '"')
+                .unwrap_or(false)
+        {
+            self.bump();
+            return Ok(Node::Uri(RDF_TYPE.to_string()));
+        }
+        match self.parse_resource()? {
+            node @ Node::Uri(_) => Ok(node),
+            _ => self.error("predicate must be an IRI"),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Node, ParseError> {
+        match self.peek() {
+            Some('[') => self.parse_blank_node_property_list(),
+            Some('(') => self.parse_collection(),
+            Some('"') | Some('\'') => self.parse_literal(),
+            Some('<') if self.peek_at(1) == Some('<') => self.parse_quoted_triple(),
+            Some(c) if c.is_ascii_digit() || c == '+' || c == '-' || c == '.' => {
+                self.parse_numeric()
+            }
+            _ => {
+                if self.starts_with("true") || self.starts_with("false") {
+                    let value = if self.starts_with("true") { "true" } else { "false" };
+                    let after = self.peek_at(value.len());
+                    if !after.map(|c| c.is_alphanumeric() || c == ':').unwrap_or(false) {
+                        for _ in 0..value.len() {
+                            self.bump();
+                        }
+                        return Ok(Node::Literal {
+                            value: value.to_string(),
+                            datatype: Some(format!("{}boolean", XSD)),
+                            language: None,
+                        });
+                    }
+                }
+                self.parse_resource()
+            }
+        }
+    }
+
+    /// IRI reference, prefixed name or labelled blank node.
+    fn parse_resource(&mut self) -> Result<Node, ParseError> {
+        match self.peek() {
+            Some('<') => Ok(Node::Uri(self.parse_iri_ref()?)),
+            Some('_') if self.peek_at(1) == Some(':') => {
+                self.bump();
+                self.bump();
+                let label = self.parse_local_name();
+                if label.is_empty() {
+                    return self.error("empty blank node label");
+                }
+                Ok(Node::Blank(label))
+            }
+            Some(_) => self.parse_prefixed_name(),
+            None => self.error("unexpected end of input"),
+        }
+    }
+
+    fn parse_iri_ref(&mut self) -> Result<String, ParseError> {
+        if self.peek() != Some('<') {
+            return self.error("expected '<'");
+        }
+        self.bump();
+        let mut iri = String::new();
+        loop {
+            match self.bump() {
+                Some('>') => break,
+                Some('\\') => iri.push(self.parse_unicode_escape()?),
+                Some(c) if c.is_whitespace() => {
+                    return self.error("whitespace in IRI");
+                }
+                Some(c) => iri.push(c),
+                None => return self.error("unterminated IRI"),
+            }
+        }
+        Ok(self.resolve(&iri))
+    }
+
+    fn parse_prefixed_name(&mut self) -> Result<Node, ParseError> {
+        let mut prefix = String::new();
+        while let Some(c) = self.peek() {
+            if c == ':' {
+                break;
+            }
+            if !(c.is_alphanumeric() || c == '_' || c == '-' || c == '.') {
+                return self.error(format!("unexpected character '{}'", c));
+            }
+            prefix.push(c);
+            self.bump();
+        }
+        if self.peek() != Some(':') {
+            return self.error(format!("expected prefixed name, found '{}'", prefix));
+        }
+        self.bump();
+        let local = self.parse_local_name();
+        let ns = match self.graph.namespaces().iter().find(|n| n.prefix == prefix) {
+            Some(ns) => ns.iri.clone(),
+            None => return self.error(format!("undeclared prefix '{}:'", prefix)),
+        };
+        Ok(Node::Uri(format!("{}{}", ns, local)))
+    }
+
+    fn parse_local_name(&mut self) -> String {
+        let mut local = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || c == '_' || c == '-' || c == ':' || c == '%' {
+                local.push(c);
+                self.bump();
+            } else if c == '\\' {
+                self.bump();
+                if let Some(escaped) = self.bump() {
+                    local.push(escaped);
+                }
+            } else if c == '.'
+                && self
+                    .peek_at(1)
+                    .map(|n| n.is_alphanumeric() || n == '_' || n == '-' || n == ':')
+                    .unwrap_or(false)
+            {
+                // A dot is part of the name only when it is not the final character
+                local.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        local
+    }
+
+    fn parse_blank_node_property_list(&mut self) -> Result<Node, ParseError> {
+        self.expect('[')?;
+        let node = self.new_blank();
+        self.skip_ws();
+        if self.peek() != Some(']') {
+            self.parse_predicate_object_list(&node)?;
+        }
+        self.expect(']')?;
+        Ok(node)
+    }
+
+    fn parse_collection(&mut self) -> Result<Node, ParseError> {
+        self.expect('(')?;
+        let nil = Node::Uri(format!("{}nil", RDF));
+        let first = Node::Uri(format!("{}first", RDF));
+        let rest = Node::Uri(format!("{}rest", RDF));
+        let mut items = Vec::new();
+        loop {
+            self.skip_ws();
+            match self.peek() {
+                Some(')') => {
+                    self.bump();
+                    break;
+                }
+                None => return self.error("unterminated collection"),
+                _ => items.push(self.parse_object()?),
+            }
+        }
+        let mut head = nil;
+        for item in items.into_iter().rev() {
+            let cell = self.new_blank();
+            self.graph.add_triple(&Triple::new(&cell, &first, &item));
+            self.graph.add_triple(&Triple::new(&cell, &rest, &head));
+            head = cell;
+        }
+        Ok(head)
+    }
+
+    fn parse_literal(&mut self) -> Result<Node, ParseError> {
+        let quote = self.bump().unwrap();
+        let long = self.peek() == Some(quote) && self.peek_at(1) == Some(quote);
+        if long {
+            self.bump();
+            self.bump();
+        }
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                None => return self.error("unterminated string literal"),
+                Some(c) if c == quote => {
+                    if !long {
+                        break;
+                    }
+                    if self.peek() == Some(quote) && self.peek_at(1) == Some(quote) {
+                        self.bump();
+                        self.bump();
+                        break;
+                    }
+                    value.push(c);
+                }
+                Some('\n') if !long => return self.error("newline in string literal"),
+                Some('\\') => match self.bump() {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some('b') => value.push('\u{8}'),
+                    Some('f') => value.push('\u{c}'),
+                    Some('"') => value.push('"'),
+                    Some('\'') => value.push('\''),
+                    Some('\\') => value.push('\\'),
+                    Some('u') | Some('U') => {
+                        self.pos -= 1;
+                        self.column -= 1;
+                        value.push(self.parse_unicode_escape()?);
+                    }
+                    _ => return self.error("invalid escape sequence"),
+                },
+                Some(c) => value.push(c),
+            }
+        }
+        if self.peek() == Some('@') {
+            self.bump();
+            let mut language = String::new();
+            while let Some(c) = self.peek() {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    language.push(c);
+                    self.bump();
+                } else {
+                    break;
+                }
+            }
+            return Ok(Node::Literal {
+                value,
+                datatype: None,
+                language: Some(language),
+            });
+        }
+        if self.starts_with("^^") {
+            self.bump();
+            self.bump();
+            let datatype = match self.parse_resource()? {
+                Node::Uri(iri) => iri,
+                _ => return self.error("datatype must be an IRI"),
+            };
+            return Ok(Node::Literal {
+                value,
+                datatype: Some(datatype),
+                language: None,
+            });
+        }
+        Ok(Node::Literal {
+            value,
+            datatype: None,
+            language: None,
+        })
+    }
+
+    /// Parses the `uXXXX` / `UXXXXXXXX` part of an escape; the backslash is already consumed.
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let digits = match self.bump() {
+            Some('u') => 4,
+            Some('U') => 8,
+            _ => return self.error("invalid escape sequence"),
+        };
+        let mut code = 0u32;
+        for _ in 0..digits {
+            match self.bump().and_then(|c| c.to_digit(16)) {
+                Some(d) => code = code * 16 + d,
+                None => return self.error("invalid unicode escape"),
+            }
+        }
+        match char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => self.error("invalid unicode code point"),
+        }
+    }
+
+    fn parse_numeric(&mut self) -> Result<Node, ParseError> {
+        let mut value = String::new();
+        if let Some(c @ ('+' | '-')) = self.peek() {
+            value.push(c);
+            self.bump();
+        }
+        let mut kind = "integer";
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() {
+                value.push(c);
+            } else if c == '.'
+                && kind == "integer"
+                && self.peek_at(1).map(|n| n.is_ascii_digit()).unwrap_or(false)
+            {
+                kind = "decimal";
+                value.push(c);
+            } else if (c == 'e' || c == 'E') && kind != "double" {
+                kind = "double";
+                value.push(c);
+                if let Some(sign @ ('+' | '-')) = self.peek_at(1) {
+                    self.bump();
+                    value.push(sign);
+                }
+            } else {
+                break;
+            }
+            self.bump();
+        }
+        if !value.chars().any(|c| c.is_ascii_digit()) {
+            return self.error("invalid numeric literal");
+        }
+        Ok(Node::Literal {
+            value,
+            datatype: Some(format!("{}{}", XSD, kind)),
+            language: None,
+        })
+    }
+
+    fn resolve(&self, iri: &str) -> String {
+        match &self.base {
+            Some(base) => resolve_iri(base, iri),
+            None => iri.to_string(),
+        }
+    }
+}
+
+/// Resolves `reference` against `base` (RFC 3986, section 5.2).
+pub fn resolve_iri(base: &str, reference: &str) -> String {
+    if has_scheme(reference) {
+        return reference.to_string();
+    }
+    let without_fragment = base.split('#').next().unwrap_or(base);
+    if reference.is_empty() {
+        return without_fragment.to_string();
+    }
+    if reference.starts_with('#') {
+        return format!("{}{}", without_fragment, reference);
+    }
+    let scheme_end = base.find("://").map(|i| i + 3);
+    let authority_end = scheme_end
+        .map(|start| {
+            base[start..]
+                .find(['/', '?', '#'])
+                .map(|i| start + i)
+                .unwrap_or(base.len())
+        })
+        .unwrap_or(0);
+    if reference.starts_with("//") {
+        let scheme = base.split(':').next().unwrap_or("");
+        return format!("{}:{}", scheme, reference);
+    }
+    if reference.starts_with('/') {
+        return format!("{}{}", &base[..authority_end], remove_dot_segments(reference));
+    }
+    let without_query = without_fragment.split('?').next().unwrap_or(without_fragment);
+    if reference.starts_with('?') {
+        return format!("{}{}", without_query, reference);
+    }
+    let path = &without_query[authority_end..];
+    let directory = match path.rfind('/') {
+        Some(i) => &path[..=i],
+        None => "/",
+    };
+    format!(
+        "{}{}",
+        &base[..authority_end],
+        remove_dot_segments(&format!("{}{}", directory, reference))
+    )
+}
+
+fn has_scheme(iri: &str) -> bool {
+    match iri.find(':') {
+        Some(i) => {
+            i > 0
+                && iri[..i]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+                && iri[..i].starts_with(|c: char| c.is_ascii_alphabetic())
+        }
+        None => false,
+    }
+}
+
+fn remove_dot_segments(path: &str) -> String {
+    let (path, suffix) = match path.find(['?', '#']) {
+        Some(i) => (&path[..i], &path[i..]),
+        None => (path, ""),
+    };
+    let mut output: Vec<&str> = Vec::new();
+    let segments: Vec<&str> = path.split('/').collect();
+    for (i, segment) in segments.iter().enumerate() {
+        let last = i == segments.len() - 1;
+        match *segment {
+            "." => {
+                if last {
+                    output.push("");
+                }
+            }
+            ".." => {
+                if output.len() > 1 {
+                    output.pop();
+                }
+                if last {
+                    output.push("");
+                }
+            }
+            s => output.push(s),
+        }
+    }
+    format!("{}{}", output.join("/"), suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, resolve_iri};
+    use crate::rdf::Node;
+
+    #[test]
+    fn parses_prefixed_names_and_predicate_lists() {
+        let graph = parse(
+            "@prefix : <#> .\n@prefix foaf: <http://xmlns.com/foaf/0.1/> .\n:me a foaf:Person ; foaf:name \"Jane\", \"J\" .\n",
+        )
+        .unwrap();
+        assert_eq!(graph.triples().len(), 3);
+        assert_eq!(graph.triples()[0].subject, Node::Uri("#me".to_string()));
+    }
+
+    #[test]
+    fn parses_typed_and_language_tagged_literals() {
+        let graph = parse(
+            "<#me> <http://schema.org/birthDate> \"1985-03-14\"^^<http://www.w3.org/2001/XMLSchema#date> ; <http://schema.org/name> \"Jane\"@en ; <http://schema.org/age> 41 .",
+        )
+        .unwrap();
+        let objects: Vec<String> = graph
+            .triples()
+            .iter()
+            .map(|t| graph.format_node(&t.object))
+            .collect();
+        assert_eq!(
+            objects,
+            [
+                "\"1985-03-14\"^^<http://www.w3.org/2001/XMLSchema#date>",
+                "\"Jane\"@en",
+                "\"41\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_anonymous_blank_nodes() {
+        let graph = parse("<#me> <http://schema.org/homeLocation> [ a <http://schema.org/Place> ] .")
+            .unwrap();
+        assert_eq!(graph.triples().len(), 2);
+        assert!(graph.triples()[0].subject.is_blank());
+    }
+
+    #[test]
+    fn parses_quoted_triples_as_statements() {
+        let graph = parse("<< <#me> <http://xmlns.com/foaf/0.1/knows> <#alice> >> <http://purl.org/dc/terms/created> \"2007\" .")
+            .unwrap();
+        assert_eq!(graph.triples().len(), 5);
+        assert_eq!(graph.triples()[2].object, Node::Uri("http://xmlns.com/foaf/0.1/knows".to_string()));
+        assert!(graph.triples()[4].subject.is_blank());
+    }
+
+    #[test]
+    fn quoting_a_triple_twice_reuses_its_statement() {
+        let graph = parse("<< <#me> <http://xmlns.com/foaf/0.1/knows> <#alice> >> <http://purl.org/dc/terms/created> \"2007\" .\n<< <#me> <http://xmlns.com/foaf/0.1/knows> <#alice> >> <http://purl.org/dc/terms/source> <#export> .")
+            .unwrap();
+        assert_eq!(graph.triples().len(), 6);
+        assert_eq!(graph.triples()[4].subject, graph.triples()[5].subject);
+    }
+
+    #[test]
+    fn parses_annotations_on_asserted_triples() {
+        let graph = parse("<#me> <http://xmlns.com/foaf/0.1/knows> <#alice> {| <http://purl.org/dc/terms/created> \"2007\" ; <http://purl.org/dc/terms/source> <#export> |} , <#bob> .")
+            .unwrap();
+        assert_eq!(graph.triples().len(), 8);
+        let statement = &graph.triples()[1].subject;
+        assert!(statement.is_blank());
+        assert_eq!(graph.triples()[4].object, Node::Uri("#alice".to_string()));
+        assert_eq!(&graph.triples()[5].subject, statement);
+        assert_eq!(&graph.triples()[6].subject, statement);
+        assert_eq!(graph.triples()[7].object, Node::Uri("#bob".to_string()));
+    }
+
+    #[test]
+    fn reports_unclosed_annotations() {
+        let err = parse("<#me> <http://xmlns.com/foaf/0.1/knows> <#alice> {| <http://purl.org/dc/terms/created> \"2007\" .").unwrap_err();
+        assert_eq!(err.message, "expected '|'");
+    }
+
+    #[test]
+    fn reports_error_position() {
+        let err = parse("<#me> <http://xmlns.com/foaf/0.1/name> \"Jane\"\n<#me>").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn resolves_relative_iris_against_base() {
+        let base = "https://pod.example/profile/card";
+        assert_eq!(resolve_iri(base, "#me"), "https://pod.example/profile/card#me");
+        assert_eq!(resolve_iri(base, "./"), "https://pod.example/profile/");
+        assert_eq!(resolve_iri(base, "../inbox/"), "https://pod.example/inbox/");
+        assert_eq!(resolve_iri(base, "/settings/prefs.ttl"), "https://pod.example/settings/prefs.ttl");
+        assert_eq!(resolve_iri(base, "mailto:jane@example.com"), "mailto:jane@example.com");
+    }
+}
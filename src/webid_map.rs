@@ -0,0 +1,211 @@
+// ---------------------------------------------------------------------------
+// WebID map
+// Friends are described as nodes local to the card (`#Alice_Nguyen`), which
+// mean nothing outside of it. A WebID map lists the friends who already have
+// a WebID, by Facebook profile URL or by name. Matched friends are described
+// under their WebID instead, with `owl:sameAs` their Facebook profile, and the
+// rest are reported so the user can invite them to Solid.
+// ---------------------------------------------------------------------------
+
+use crate::convert::map_iris;
+use crate::facebook_url::FacebookId;
+use crate::normalize_name;
+use crate::rdf::{Graph, Namespace, Node, Triple, Uri};
+use crate::turtle;
+use std::collections::HashMap;
+use std::error;
+
+const FOAF: &str = "http://xmlns.com/foaf/0.1/";
+const OWL: &str = "http://www.w3.org/2002/07/owl#";
+
+#[derive(Clone, Debug, Default)]
+pub struct WebIdMap {
+    /// WebIDs by Facebook profile.
+    by_account: HashMap<FacebookId, String>,
+    /// WebIDs by normalized name.
+    by_name: HashMap<String, String>,
+}
+
+impl WebIdMap {
+    /// Reads lines of `facebook,webid`, where `facebook` is a Facebook profile
+    /// URL or a name. A header line, blank lines and `#` comments are skipped.
+    pub fn from_csv(contents: &str) -> Result<WebIdMap, Box<dyn error::Error>> {
+        let mut map = WebIdMap::default();
+        let mut header = true;
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // Names may contain commas, WebIDs do not
+            let (key, webid) = line
+                .rsplit_once(',')
+                .ok_or_else(|| format!("line {}: expected two columns, found '{}'", i + 1, line))?;
+            let unquote = |field: &str| field.trim().trim_matches('"').to_string();
+            let (key, webid) = (unquote(key), unquote(webid));
+            if !webid.contains("://") {
+                if header {
+                    header = false;
+                    continue;
+                }
+                return Err(format!("line {}: '{}' is not a WebID", i + 1, webid).into());
+            }
+            header = false;
+            map.insert(&key, &webid);
+        }
+        Ok(map)
+    }
+
+    /// Reads `<webid> owl:sameAs <facebook profile>` and `<webid> foaf:name
+    /// "name"` statements. `owl:sameAs` may point either way.
+    pub fn from_turtle(contents: &str) -> Result<WebIdMap, Box<dyn error::Error>> {
+        let graph = turtle::parse(contents)?;
+        let mut map = WebIdMap::default();
+        let is_facebook = |iri: &str| FacebookId::from_url(iri).is_some();
+        for t in graph.triples() {
+            match (&t.subject, &t.predicate, &t.object) {
+                (Node::Uri(s), Node::Uri(p), Node::Uri(o)) if *p == format!("{}sameAs", OWL) => {
+                    match (is_facebook(s), is_facebook(o)) {
+                        (false, true) => map.insert(o, s),
+                        (true, false) => map.insert(s, o),
+                        _ => {}
+                    }
+                }
+                (Node::Uri(s), Node::Uri(p), Node::Literal { value, .. })
+                    if *p == format!("{}name", FOAF) && s.contains("://") =>
+                {
+                    map.insert(value, s)
+                }
+                _ => {}
+            }
+        }
+        Ok(map)
+    }
+
+    /// Maps the friend with the Facebook profile URL or the name `key` to
+    /// `webid`.
+    pub fn insert(&mut self, key: &str, webid: &str) {
+        match FacebookId::from_url(key) {
+            Some(account) => self.by_account.insert(account, webid.to_string()),
+            None => self.by_name.insert(normalize_name(key), webid.to_string()),
+        };
+    }
+
+    /// The WebID of a friend, looked up by profile URL before name.
+    pub fn webid(&self, name: &str, profile_url: Option<&str>) -> Option<&str> {
+        profile_url
+            .and_then(FacebookId::from_url)
+            .and_then(|account| self.by_account.get(&account))
+            .or_else(|| self.by_name.get(&normalize_name(name)))
+            .map(String::as_str)
+    }
+}
+
+/// Describes every friend of `#me` that `map` knows under their WebID.
+/// Returns the new graph and the names of the friends without a WebID.
+pub fn apply(graph: &Graph, map: &WebIdMap) -> (Graph, Vec<String>) {
+    let Matches {
+        renamed,
+        same_as,
+        without_webid,
+    } = match_friends(graph, map);
+    let mut mapped = map_iris(graph, |iri| renamed.get(iri).cloned().unwrap_or_else(|| iri.to_string()));
+    if !same_as.is_empty() {
+        mapped.add_namespace(&Namespace::new("owl".to_string(), Uri::new(OWL.to_string())));
+    }
+    for (webid, page) in same_as {
+        mapped.add_triple(&Triple::new(
+            &Node::Uri(webid),
+            &Node::Uri(format!("{}sameAs", OWL)),
+            &Node::Uri(page),
+        ));
+    }
+    (mapped, without_webid)
+}
+
+/// The WebID `apply` gives each friend node of `graph`, by the node's IRI.
+pub fn renaming(graph: &Graph, map: &WebIdMap) -> HashMap<String, String> {
+    match_friends(graph, map).renamed
+}
+
+struct Matches {
+    /// Friend node IRI to WebID.
+    renamed: HashMap<String, String>,
+    /// Each WebID with the Facebook page it was matched by.
+    same_as: Vec<(String, String)>,
+    /// Names of the friends without a WebID.
+    without_webid: Vec<String>,
+}
+
+fn match_friends(graph: &Graph, map: &WebIdMap) -> Matches {
+    let uri = |local: &str| Node::Uri(format!("{}{}", FOAF, local));
+    let values = |subject: &Node, predicate: &Node| -> Vec<Node> {
+        graph
+            .triples()
+            .iter()
+            .filter(|t| t.subject == *subject && t.predicate == *predicate)
+            .map(|t| t.object.clone())
+            .collect()
+    };
+    let mut friends: Vec<Node> = values(&Node::Uri("#me".to_string()), &uri("knows"));
+    friends.dedup();
+
+    let mut renamed: HashMap<String, String> = HashMap::new();
+    let mut same_as: Vec<(String, String)> = Vec::new();
+    let mut without_webid = Vec::new();
+    for friend in friends {
+        let Node::Uri(iri) = &friend else { continue };
+        if !iri.starts_with('#') || renamed.contains_key(iri) {
+            continue;
+        }
+        let name = match values(&friend, &uri("name")).first() {
+            Some(Node::Literal { value, .. }) => value.clone(),
+            _ => continue,
+        };
+        let page = values(&friend, &uri("account"))
+            .iter()
+            .flat_map(|account| values(account, &uri("page")))
+            .find_map(|page| match page {
+                Node::Uri(page) => Some(page),
+                _ => None,
+            });
+        match map.webid(&name, page.as_deref()) {
+            Some(webid) => {
+                renamed.insert(iri.clone(), webid.to_string());
+                if let Some(page) = page {
+                    same_as.push((webid.to_string(), page));
+                }
+            }
+            None => without_webid.push(name),
+        }
+    }
+
+    Matches {
+        renamed,
+        same_as,
+        without_webid,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WebIdMap;
+
+    #[test]
+    fn csv_maps_profile_urls_and_names() {
+        let map = WebIdMap::from_csv(
+            "facebook,webid\n\
+             https://www.facebook.com/alice.nguyen.503,https://alice.example/profile/card#me\n\
+             # a comment\n\
+             \"Okonkwo, David\",https://david.example/card#me\n",
+        )
+        .unwrap();
+        assert_eq!(
+            map.webid("A. Nguyen", Some("https://www.facebook.com/alice.nguyen.503?ref=x")),
+            Some("https://alice.example/profile/card#me")
+        );
+        assert_eq!(map.webid("okonkwo,  david", None), Some("https://david.example/card#me"));
+        assert_eq!(map.webid("Zoe Park", None), None);
+        assert!(WebIdMap::from_csv("a,b\nc,d\n").is_err());
+    }
+}
@@ -1,8 +1,27 @@
-use hatchling::convert_facebook_to_solid;
+use hatchling::error::{Error, Input, LooksLike};
+use hatchling::lint::{self, Severity};
+use hatchling::patch::PatchFormat;
+use hatchling::selection::Selection;
+use hatchling::convert::{Converter, Mode, OutputFormat, ProvenanceOptions, Timestamps, Vocabulary};
+use hatchling::explain;
+use hatchling::facebook_parser::{ConnectionList, FBAlbum, FBConnections, FBFriendLists, FBPosts, FBProfileInformation, FriendsVersion, ProfileVersion};
+use hatchling::access::{self, AccessControl, VisibilityPolicy};
+use hatchling::photos::{MediaFile, Photos};
+use hatchling::pod::{build_pod, build_pod_with, copy_media, write_pod, PodOptions};
+use hatchling::drift::UnmappedKind;
+use hatchling::webid_map::WebIdMap;
+use hatchling::{
+    convert_facebook_to_graph, convert_facebook_to_solid, diff_turtle, patch_turtle};
+use std::fs;
+use std::time::{Duration, UNIX_EPOCH};
 
 const PROFILE: &str = include_str!("fixtures/profile_information.json");
 const FRIENDS_DYI: &str = include_str!("fixtures/your_friends.json");
 const FRIENDS_SCRAPED: &str = include_str!("fixtures/friends_scraped.json");
+const FRIENDS_PAGE: &str = include_str!("fixtures/friends_page.html");
+const POSTS: &str = include_str!("fixtures/your_posts_1.json");
+const ALBUM_PATH: &str = "tests/fixtures/export/photos_and_videos/album/0.json";
+const ALBUM: &str = include_str!("fixtures/export/photos_and_videos/album/0.json");
 
 // ---------------------------------------------------------------------------
 // Profile parsing
@@ -273,3 +292,1473 @@ fn facebook_broken_utf8_encoding_is_fixed() {
     // After the fix, Ã© → é and Ã¨ → è
     assert!(ttl.contains("Café élève"), "broken UTF-8 not repaired; expected 'Café élève'");
 }
+
+// ---------------------------------------------------------------------------
+// Diff
+// ---------------------------------------------------------------------------
+
+#[test]
+fn diff_of_identical_conversions_is_empty() {
+    let ttl = convert_facebook_to_solid(PROFILE, Some(FRIENDS_SCRAPED)).unwrap();
+    let changes = diff_turtle(&ttl, &ttl).unwrap();
+    assert!(changes.is_empty());
+    assert_eq!(changes.to_string(), "No changes.\n");
+}
+
+#[test]
+fn diff_reports_new_job_and_new_friends() {
+    let old = convert_facebook_to_solid(&profile_with_work("[]"), None).unwrap();
+    let new = convert_facebook_to_solid(
+        &profile_with_work(r#"[{"employer":{"name":"Initech"},"title":"TPS Report Author"}]"#),
+        Some(FRIENDS_DYI),
+    )
+    .unwrap();
+    let changes = diff_turtle(&old, &new).unwrap();
+    assert!(changes.removed.is_empty());
+    let report = changes.to_string();
+    assert!(report.contains("+ schema:jobTitle \"TPS Report Author\""));
+    assert!(report.contains("+ foaf:knows :Alice_Nguyen"));
+    assert!(!report.contains("\n  - "));
+}
+
+#[test]
+fn diff_reports_moved_city_as_removed_and_added() {
+    let old = convert_facebook_to_solid(PROFILE, None).unwrap();
+    let moved = PROFILE.replace("Portland, Oregon", "Seattle, Washington");
+    let new = convert_facebook_to_solid(&moved, None).unwrap();
+    let report = diff_turtle(&old, &new).unwrap().to_string();
+    assert!(report.contains("- schema:address \"Portland, Oregon\""));
+    assert!(report.contains("+ schema:address \"Seattle, Washington\""));
+}
+
+#[test]
+fn diff_rejects_invalid_turtle() {
+    assert!(diff_turtle("<#me> <broken", "").is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Patches
+// ---------------------------------------------------------------------------
+
+#[test]
+fn n3_patch_deletes_old_job_and_inserts_new_one() {
+    let old = convert_facebook_to_solid(PROFILE, None).unwrap();
+    let new = old.replace("Senior Software Engineer", "Staff Engineer");
+    let patch = patch_turtle(&old, &new, PatchFormat::N3).unwrap();
+    assert!(patch.contains("a solid:InsertDeletePatch"));
+    assert!(patch.contains("solid:deletes {\n    :me schema:jobTitle \"Senior Software Engineer\" ."));
+    assert!(patch.contains("solid:inserts {\n    :me schema:jobTitle \"Staff Engineer\" ."));
+    assert!(!patch.contains("solid:where"), "no blank nodes changed, so no WHERE clause");
+}
+
+#[test]
+fn n3_patch_binds_removed_blank_nodes_with_where() {
+    let old = convert_facebook_to_solid(PROFILE, None).unwrap();
+    let new = old.replace("Portland, Oregon", "Seattle, Washington");
+    let patch = patch_turtle(&old, &new, PatchFormat::N3).unwrap();
+    assert!(patch.contains("solid:where {\n    ?b0 a schema:Place ."));
+    assert!(patch.contains("?b0 schema:address \"Portland, Oregon\" ."));
+    assert!(!patch.contains("_:Portland_Oregon schema:address \"Portland"));
+}
+
+#[test]
+fn sparql_update_uses_data_blocks_for_ground_triples() {
+    let old = convert_facebook_to_solid(PROFILE, None).unwrap();
+    let new = convert_facebook_to_solid(PROFILE, Some(FRIENDS_DYI)).unwrap();
+    let patch = patch_turtle(&old, &new, PatchFormat::SparqlUpdate).unwrap();
+    assert!(patch.contains("PREFIX foaf: <http://xmlns.com/foaf/0.1/>"));
+    assert!(patch.contains("INSERT DATA {\n  :Alice_Nguyen a foaf:Person ."));
+    assert!(patch.contains("  :me foaf:knows :Alice_Nguyen .\n"));
+    assert!(!patch.contains("DELETE"));
+}
+
+#[test]
+fn patch_content_types() {
+    assert_eq!(PatchFormat::N3.content_type(), "text/n3");
+    assert_eq!(PatchFormat::SparqlUpdate.content_type(), "application/sparql-update");
+}
+
+// ---------------------------------------------------------------------------
+// Pod skeleton
+// ---------------------------------------------------------------------------
+
+/// A fresh, empty directory under the system temp dir for tests that write files.
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("hatchling-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn pod_card_links_inbox_storage_preferences_and_type_index() {
+    let pod = build_pod(convert_facebook_to_graph(PROFILE, None).unwrap());
+    let card = pod.iter().find(|r| r.path == "profile/card").unwrap();
+    let ttl = card.graph.serialize_turtle();
+    assert!(ttl.contains(":me ldp:inbox <../inbox/> ."));
+    assert!(ttl.contains(":me pim:storage <../> ."));
+    assert!(ttl.contains(":me pim:preferencesFile <../settings/prefs.ttl> ."));
+    assert!(ttl.contains(":me solid:publicTypeIndex <../settings/publicTypeIndex.ttl> ."));
+    assert!(!ttl.contains("privateTypeIndex"), "private type index must not be advertised publicly");
+}
+
+#[test]
+fn pod_preferences_link_private_type_index() {
+    let pod = build_pod(convert_facebook_to_graph(PROFILE, None).unwrap());
+    let prefs = pod.iter().find(|r| r.path == "settings/prefs.ttl").unwrap();
+    let ttl = prefs.graph.serialize_turtle();
+    assert!(ttl.contains("<> a pim:ConfigurationFile ."));
+    assert!(ttl.contains(
+        "<../profile/card#me> solid:privateTypeIndex <../settings/privateTypeIndex.ttl> ."
+    ));
+}
+
+#[test]
+fn pod_tree_is_written_with_file_backend_names() {
+    let dir = temp_dir("pod");
+    let pod = build_pod(convert_facebook_to_graph(PROFILE, None).unwrap());
+    write_pod(&dir, &pod).unwrap();
+
+    let card = fs::read_to_string(dir.join("profile/card$.ttl")).unwrap();
+    assert!(card.contains("Jane Doe-Smith"));
+    assert!(fs::read_to_string(dir.join(".meta")).unwrap().contains("<> a pim:Storage ."));
+    assert!(fs::read_to_string(dir.join("inbox/.meta"))
+        .unwrap()
+        .contains("<> a ldp:BasicContainer ."));
+    let public_index = fs::read_to_string(dir.join("settings/publicTypeIndex.ttl")).unwrap();
+    assert!(public_index.contains("<> a solid:ListedDocument ."));
+    let private_index = fs::read_to_string(dir.join("settings/privateTypeIndex.ttl")).unwrap();
+    assert!(private_index.contains("<> a solid:UnlistedDocument ."));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// ---------------------------------------------------------------------------
+// Public/private split
+// ---------------------------------------------------------------------------
+
+#[test]
+fn split_moves_contact_details_and_friends_to_private_document() {
+    let graph = convert_facebook_to_graph(PROFILE, Some(FRIENDS_DYI)).unwrap();
+    let (public, private) = access::split(&graph, &VisibilityPolicy::default(), "card", "private.ttl");
+    let public = public.serialize_turtle();
+    let private = private.serialize_turtle();
+
+    assert!(public.contains("Jane Doe-Smith"));
+    assert!(public.contains(":me rdfs:seeAlso <private.ttl> ."));
+    for hidden in ["tel:", "mailto:", "birthDate", "foaf:knows", "Alice Nguyen"] {
+        assert!(!public.contains(hidden), "{} leaked into the public card", hidden);
+    }
+    assert!(private.contains("<card#me> foaf:phone <tel:+15035550123> ."));
+    assert!(private.contains("<card#me> foaf:knows :Alice_Nguyen ."));
+    assert!(private.contains(":Alice_Nguyen foaf:name \"Alice Nguyen\" ."));
+}
+
+#[test]
+fn split_follows_a_custom_policy() {
+    let graph = convert_facebook_to_graph(PROFILE, Some(FRIENDS_DYI)).unwrap();
+    let policy = VisibilityPolicy::from_json(r#"{"public": ["foaf:knows"]}"#).unwrap();
+    let (public, _) = access::split(&graph, &policy, "card", "private.ttl");
+    let public = public.serialize_turtle();
+    assert!(public.contains(":me foaf:knows :Alice_Nguyen ."));
+    assert!(!public.contains("tel:"));
+}
+
+#[test]
+fn pod_split_writes_private_document_with_owner_only_acl() {
+    let options = PodOptions {
+        split: Some(VisibilityPolicy::default()),
+        access_control: Some(AccessControl::Wac),
+        ..PodOptions::default()
+    };
+    let pod = build_pod_with(convert_facebook_to_graph(PROFILE, None).unwrap(), &options);
+    let acl = |path: &str| pod.iter().find(|r| r.path == path).unwrap().graph.serialize_turtle();
+
+    assert!(acl("profile/private.ttl").contains("foaf:phone"));
+    let card_acl = acl("profile/card.acl");
+    assert!(card_acl.contains("<#owner> acl:agent <../profile/card#me> ."));
+    assert!(card_acl.contains("<#public> acl:agentClass foaf:Agent ."));
+    let private_acl = acl("profile/private.ttl.acl");
+    assert!(private_acl.contains("<#owner> acl:accessTo <private.ttl> ."));
+    assert!(!private_acl.contains("#public"));
+    assert!(acl(".acl").contains("<#owner> acl:default <./> ."));
+    assert!(acl("inbox/.acl").contains("<#public> acl:mode acl:Append ."));
+}
+
+#[test]
+fn pod_access_control_can_use_acp() {
+    let options = PodOptions {
+        split: Some(VisibilityPolicy::default()),
+        access_control: Some(AccessControl::Acp),
+        ..PodOptions::default()
+    };
+    let pod = build_pod_with(convert_facebook_to_graph(PROFILE, None).unwrap(), &options);
+    let card_acr = pod.iter().find(|r| r.path == "profile/card.acr").unwrap();
+    let ttl = card_acr.graph.serialize_turtle();
+    assert!(ttl.contains("<#root> acp:resource <card> ."));
+    assert!(ttl.contains("<#publicMatcher> acp:agent acp:PublicAgent ."));
+    assert!(!pod.iter().any(|r| r.path.ends_with(".acl")));
+}
+
+// ---------------------------------------------------------------------------
+// Privacy lint
+// ---------------------------------------------------------------------------
+
+#[test]
+fn lint_names_the_export_field_behind_each_finding() {
+    let profile = Converter::new().profile(PROFILE).into_profile().unwrap();
+    let findings = lint::lint(&profile);
+
+    let phone = findings.iter().find(|f| f.setter == "add_phone_number").unwrap();
+    assert_eq!(phone.severity, Severity::High);
+    assert_eq!(phone.field, "phone_numbers[0].phone_number");
+    let birthday = findings.iter().find(|f| f.field == "birthday").unwrap();
+    assert_eq!(birthday.severity, Severity::High);
+    assert_eq!(findings.iter().filter(|f| f.setter == "add_email").count(), 2);
+    assert!(!findings.iter().any(|f| f.field.starts_with("name")));
+}
+
+#[test]
+fn lint_flags_every_published_friend() {
+    let profile = Converter::new().profile(PROFILE).friends(FRIENDS_DYI).into_profile().unwrap();
+    let friends: Vec<_> = lint::lint(&profile)
+        .into_iter()
+        .filter(|f| f.field.starts_with("friends["))
+        .collect();
+    let mut fields: Vec<&str> = friends.iter().map(|f| f.field.as_str()).collect();
+    fields.dedup();
+    assert_eq!(fields.len(), 10);
+    assert!(friends.iter().all(|f| f.severity == Severity::Medium));
+}
+
+#[test]
+fn lint_flags_a_birthday_without_the_year() {
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .selection(Selection::all().exclude("birthday.year").unwrap())
+        .convert()
+        .unwrap();
+    let birthday = conversion.report.findings.iter().find(|f| f.field == "birthday").unwrap();
+    assert_eq!((birthday.severity, birthday.setter), (Severity::Low, "set_birthday"));
+}
+
+#[test]
+fn lint_flags_people_on_connections_lists() {
+    let conversion = Converter::new().profile(PROFILE).connections(FOLLOWERS).convert().unwrap();
+    let follower = conversion.report.findings.iter().find(|f| f.field == "followers_v2[1]").unwrap();
+    assert_eq!((follower.severity, follower.setter), (Severity::Medium, "add_collection_member"));
+}
+
+#[test]
+fn lint_flags_members_of_friend_lists() {
+    let conversion = Converter::new().profile(PROFILE).connections(FRIEND_LISTS).convert().unwrap();
+    let member = conversion.report.findings.iter().find(|f| f.setter == "add_group_member").unwrap();
+    assert_eq!(member.severity, Severity::Medium);
+    assert!(member.field.contains(".members["), "{}", member.field);
+}
+
+#[test]
+fn lint_findings_name_the_triples_as_published() {
+    let map = WebIdMap::from_csv("Alice Nguyen,https://alice.example/profile/card#me\n").unwrap();
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .friends(FRIENDS_DYI)
+        .webid_map(map)
+        .base("https://jane.example/profile/card")
+        .convert()
+        .unwrap();
+    let alice = hatchling::rdf::Node::Uri("https://alice.example/profile/card#me".to_string());
+    let friend = conversion
+        .report
+        .findings
+        .iter()
+        .find(|f| f.triples.iter().any(|t| t.subject == alice))
+        .expect("the mapped friend is still flagged");
+    assert_eq!(friend.severity, Severity::Medium);
+    for finding in &conversion.report.findings {
+        assert!(finding.triples.iter().all(|t| conversion.graph.triples().contains(t)));
+    }
+}
+
+#[test]
+fn lint_is_quiet_for_a_profile_without_sensitive_fields() {
+    let json = r#"{"profile_v2": {"name": {"full_name": "Jane Doe"}, "emails": {"emails": []}}}"#;
+    let profile = Converter::new().profile(json).into_profile().unwrap();
+    assert!(lint::lint(&profile).is_empty());
+    assert_eq!(profile.origins().len(), profile.graph().triples().len());
+}
+
+// ---------------------------------------------------------------------------
+// Field selection
+// ---------------------------------------------------------------------------
+
+fn convert_selected(selection: Selection, friends: Option<&str>) -> String {
+    friends
+        .into_iter()
+        .fold(Converter::new().profile(PROFILE), Converter::friends)
+        .selection(selection)
+        .into_profile()
+        .unwrap()
+        .graph()
+        .serialize_turtle()
+}
+
+#[test]
+fn exclude_drops_phone_birth_year_and_friend_urls() {
+    let selection = Selection::all()
+        .exclude("phone")
+        .and_then(|s| s.exclude("birthday.year"))
+        .and_then(|s| s.exclude("friends.urls"))
+        .unwrap();
+    let ttl = convert_selected(selection, Some(FRIENDS_SCRAPED));
+    assert!(!ttl.contains("tel:"));
+    assert!(ttl.contains(":me foaf:birthday \"--03-14\" ."));
+    assert!(!ttl.contains("birthDate"));
+    assert!(ttl.contains("foaf:knows"));
+    assert!(!ttl.contains("facebook.com/alice"), "friend URLs must be dropped");
+}
+
+#[test]
+fn include_keeps_only_the_selected_fields() {
+    let selection = Selection::all().include("name").and_then(|s| s.include("work")).unwrap();
+    let ttl = convert_selected(selection, Some(FRIENDS_DYI));
+    assert!(ttl.contains(":me foaf:name \"Jane Doe-Smith\" ."));
+    assert!(ttl.contains("schema:jobTitle"));
+    for dropped in ["mailto:", "tel:", "birthDate", "foaf:knows", "alumniOf", "homeLocation"] {
+        assert!(!ttl.contains(dropped), "{} should not be converted", dropped);
+    }
+}
+
+#[test]
+fn excluding_a_nested_field_keeps_its_parent() {
+    let ttl = convert_selected(Selection::all().exclude("work.title").unwrap(), None);
+    assert!(ttl.contains("schema:worksFor"));
+    assert!(!ttl.contains("schema:jobTitle"));
+}
+
+// ---------------------------------------------------------------------------
+// Converter
+// ---------------------------------------------------------------------------
+
+#[test]
+fn converter_matches_the_plain_entry_point_by_default() {
+    let conversion = Converter::new().profile(PROFILE).friends(FRIENDS_DYI).convert().unwrap();
+    assert_eq!(
+        conversion.serialize(),
+        convert_facebook_to_solid(PROFILE, Some(FRIENDS_DYI)).unwrap()
+    );
+    assert!(conversion.report.warnings.is_empty());
+    assert!(conversion.report.findings.iter().any(|f| f.setter == "add_phone_number"));
+}
+
+#[test]
+fn converter_resolves_iris_against_base_and_webid() {
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .base("https://jane.example/profile/card")
+        .webid("https://id.example/jane#i")
+        .convert()
+        .unwrap();
+    let ttl = conversion.serialize();
+    assert!(ttl.contains("profile:card foaf:primaryTopic <https://id.example/jane#i> ."));
+    assert!(ttl.contains("@prefix profile: <https://jane.example/profile/> ."));
+    assert!(ttl.contains("@prefix : <https://jane.example/profile/card#> ."));
+    assert!(!ttl.contains(":me "));
+}
+
+#[test]
+fn converter_keeps_one_vocabulary_where_both_have_a_term() {
+    let ttl = Converter::new()
+        .profile(PROFILE)
+        .friends(FRIENDS_DYI)
+        .vocabulary(Vocabulary::Schema)
+        .convert()
+        .unwrap()
+        .serialize();
+    assert!(ttl.contains(":me schema:name \"Jane Doe-Smith\" ."));
+    assert!(!ttl.contains("foaf:name \"Jane Doe-Smith\""));
+    assert!(!ttl.contains("foaf:phone"));
+    assert!(!ttl.contains(":me a foaf:Person ."));
+    // foaf:knows has no schema.org counterpart here, and friends only have foaf:name
+    assert!(ttl.contains(":me foaf:knows :Alice_Nguyen ."));
+    assert!(ttl.contains(":Alice_Nguyen foaf:name \"Alice Nguyen\" ."));
+}
+
+#[test]
+fn foaf_vocabulary_keeps_the_year_of_birth() {
+    let ttl = Converter::new()
+        .profile(PROFILE)
+        .vocabulary(Vocabulary::Foaf)
+        .convert()
+        .unwrap()
+        .serialize();
+    assert!(ttl.contains(":me foaf:birthday \"--03-14\" ."));
+    assert!(ttl.contains(":me schema:birthDate \"1985-03-14\" ."));
+    assert!(!ttl.contains(":me schema:name"));
+}
+
+#[test]
+fn converter_writes_json_ld() {
+    let json = Converter::new()
+        .profile(PROFILE)
+        .format(OutputFormat::JsonLd)
+        .convert()
+        .unwrap()
+        .serialize();
+    let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert!(document["@graph"].as_array().unwrap().iter().any(|n| n["@id"] == "#me"));
+}
+
+#[test]
+fn converter_reads_several_friends_files_and_reports_warnings() {
+    let unnamed = r#"[{"name": "", "target": "https://www.facebook.com/nobody"}]"#;
+    let mut seen = Vec::new();
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .friends(FRIENDS_SCRAPED)
+        .friends(unnamed)
+        .on_warning(|w| seen.push(w.field.clone()))
+        .convert()
+        .unwrap();
+    assert_eq!(conversion.report.warnings.len(), 1);
+    assert_eq!(seen, vec!["friends[10]".to_string()]);
+    assert!(!conversion.serialize().contains("nobody"));
+}
+
+#[test]
+fn warnings_reach_the_sink_when_the_conversion_fails() {
+    let unnamed = r#"[{"name": "", "target": "https://www.facebook.com/nobody"}]"#;
+    let mut seen = Vec::new();
+    let result = Converter::new()
+        .profile(PROFILE)
+        .friends(unnamed)
+        .friends("not json")
+        .on_warning(|w| seen.push(w.field.clone()))
+        .convert();
+    assert!(result.is_err());
+    assert_eq!(seen, vec!["friends[0]".to_string()]);
+}
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+#[test]
+fn wrong_type_is_reported_with_its_path() {
+    let json = "{\"profile_v2\": {\n  \"birthday\": {\"year\": \"1985\"}\n}}";
+    match convert_facebook_to_solid(json, None).unwrap_err() {
+        Error::InvalidValue { location, .. } => {
+            assert_eq!(location.path, "profile_v2.birthday.year");
+            assert_eq!(location.input, Input::Profile);
+            assert_eq!(location.line, 2);
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+}
+
+#[test]
+fn malformed_friend_entry_points_at_the_friends_file() {
+    let friends = r#"{"friends_v2": [{"name": "Alice", "timestamp": "yesterday"}]}"#;
+    let err = convert_facebook_to_solid(PROFILE, Some(friends)).unwrap_err();
+    let location = err.location().unwrap();
+    assert!(matches!(err, Error::InvalidValue { .. }));
+    assert_eq!(location.input, Input::Friends(0));
+    assert_eq!(location.path, "friends_v2[0].timestamp");
+    let err = err.in_file("your_friends.json");
+    assert!(err.to_string().starts_with("your_friends.json:1:"));
+}
+
+#[test]
+fn swapped_inputs_are_recognised() {
+    let err = convert_facebook_to_solid(FRIENDS_DYI, None).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::UnsupportedLayout { looks_like: Some(LooksLike::FriendsFile), .. }
+    ));
+    assert!(err.suggestion().unwrap().contains("--friends"));
+
+    let err = convert_facebook_to_solid(PROFILE, Some(PROFILE)).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::UnsupportedLayout { looks_like: Some(LooksLike::ProfileFile), .. }
+    ));
+}
+
+#[test]
+fn html_export_gets_a_suggestion() {
+    let err = convert_facebook_to_solid("<!DOCTYPE html><html>", None).unwrap_err();
+    assert!(err.suggestion().unwrap().contains("HTML export"));
+}
+
+// ---------------------------------------------------------------------------
+// Lenient conversion
+// ---------------------------------------------------------------------------
+
+const DAMAGED_PROFILE: &str = r#"{"profile_v2": {
+    "name": {"full_name": "Jane Doe"},
+    "birthday": {"year": "1985", "month": 3, "day": 14},
+    "family_members": [{"name": "Bob", "relation": "Brother"}, {"name": ["not", "a", "name"]}],
+    "gender": 7,
+    "education_experiences": [
+        {"school_type": "Bootcamp", "name": "Code Camp", "graduated": true},
+        {"school_type": "College", "name": "University of Oregon", "graduated": true}
+    ]
+}}"#;
+
+#[test]
+fn export_without_emails_converts_leniently() {
+    let json = r#"{"profile_v2": {"name": {"full_name": "Jane Doe"}}}"#;
+    let conversion = Converter::new().profile(json).mode(Mode::Lenient).convert().unwrap();
+    assert!(conversion.serialize().contains("\"Jane Doe\""));
+    let warning = &conversion.report.warnings[0];
+    assert_eq!(warning.field, "emails");
+    assert!(warning.message.contains("missing section"));
+}
+
+#[test]
+fn strict_mode_requires_emails() {
+    let json = r#"{"profile_v2": {"name": {"full_name": "Jane Doe"}}}"#;
+    match Converter::new().profile(json).mode(Mode::Strict).convert() {
+        Err(Error::MissingField { field, .. }) => assert_eq!(field, "emails"),
+        other => panic!("expected a missing field, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn lenient_mode_converts_what_parses_and_warns_about_the_rest() {
+    assert!(convert_facebook_to_solid(DAMAGED_PROFILE, None).is_err());
+
+    let conversion = Converter::new()
+        .profile(DAMAGED_PROFILE)
+        .mode(Mode::Lenient)
+        .convert()
+        .unwrap();
+    let ttl = conversion.serialize();
+    assert!(ttl.contains(":me schema:birthDate \"1985-03-14\" ."), "year is coerced");
+    assert!(ttl.contains("University of Oregon"));
+    assert!(!ttl.contains("foaf:gender"));
+
+    let warnings: Vec<&str> = conversion.report.warnings.iter().map(|w| w.field.as_str()).collect();
+    assert!(warnings.contains(&"birthday"));
+    assert!(warnings.contains(&"family_members[1]"));
+    assert!(warnings.contains(&"gender"));
+    assert!(warnings.contains(&"education_experiences[0].school_type"));
+}
+
+#[test]
+fn lenient_mode_skips_unreadable_friends() {
+    let friends = r#"{"friends_v2": [{"name": "Alice", "timestamp": 1}, {"name": 42, "timestamp": {}}]}"#;
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .friends(friends)
+        .mode(Mode::Lenient)
+        .convert()
+        .unwrap();
+    assert!(conversion.serialize().contains(":Alice foaf:name \"Alice\" ."));
+    assert_eq!(conversion.report.warnings.len(), 1);
+    assert_eq!(conversion.report.warnings[0].field, "friends_v2[1]");
+}
+
+// ---------------------------------------------------------------------------
+// Unmapped data
+// ---------------------------------------------------------------------------
+
+#[test]
+fn fixture_keys_unknown_to_hatchling_are_reported() {
+    let conversion = Converter::new().profile(PROFILE).friends(FRIENDS_DYI).convert().unwrap();
+    let paths: Vec<&str> = conversion.report.unmapped.iter().map(|u| u.path.as_str()).collect();
+    for path in [
+        "profile_v2.websites",
+        "profile_v2.profile_category",
+        "profile_v2.other_names",
+    ] {
+        assert!(paths.contains(&path), "{} not reported in {:?}", path, paths);
+    }
+    let unknown = conversion.report.unmapped.iter().filter(|u| u.kind == UnmappedKind::UnknownKey);
+    assert!(!unknown.clone().any(|u| u.path.starts_with("profile_v2.name")));
+    assert!(!paths.iter().any(|p| p.starts_with("friends_v2")));
+}
+
+#[test]
+fn fixture_sections_that_are_not_converted_are_reported() {
+    let conversion = Converter::new().profile(PROFILE).convert().unwrap();
+    let not_converted: Vec<&str> = conversion
+        .report
+        .unmapped
+        .iter()
+        .filter(|u| u.kind == UnmappedKind::NotConverted)
+        .map(|u| u.path.as_str())
+        .collect();
+    for path in ["profile_v2.relationship", "profile_v2.family_members", "profile_v2.name.middle_name"] {
+        assert!(not_converted.contains(&path), "{} not reported in {:?}", path, not_converted);
+    }
+    assert!(!not_converted.contains(&"profile_v2.name.full_name"));
+}
+
+#[test]
+fn unmapped_report_serializes_to_json() {
+    let conversion = Converter::new().profile(PROFILE).convert().unwrap();
+    let json = serde_json::to_value(&conversion.report.unmapped).unwrap();
+    let entry = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|u| u["path"] == "profile_v2.phone_numbers[].creation_time")
+        .unwrap();
+    assert_eq!(entry["kind"], "unknown_key");
+    assert_eq!(entry["samples"][0], "profile_v2.phone_numbers[0].creation_time");
+}
+
+// ---------------------------------------------------------------------------
+// Export versions
+// ---------------------------------------------------------------------------
+
+const PROFILE_V1: &str = r#"{"profile": {
+    "name": {"full_name": "Jane Doe"},
+    "emails": {"emails": []},
+    "work_experiences": [{"employer": "Initech", "title": "TPS Report Author"}],
+    "languages": ["English", "Spanish"],
+    "intro_bio": "Amateur astronomer"
+}}"#;
+
+#[test]
+fn each_export_generation_is_detected() {
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .friends(FRIENDS_DYI)
+        .friends(FRIENDS_SCRAPED)
+        .friends(r#"{"friends": [{"name": "Alice", "timestamp": 1}]}"#)
+        .convert()
+        .unwrap();
+    let version = conversion.report.version;
+    assert_eq!(version.profile, ProfileVersion::V2);
+    assert_eq!(
+        version.friends,
+        [FriendsVersion::V2, FriendsVersion::Scraped, FriendsVersion::V1]
+    );
+
+    let conversion = Converter::new().profile(PROFILE_V1).convert().unwrap();
+    assert_eq!(conversion.report.version.profile, ProfileVersion::V1);
+}
+
+#[test]
+fn first_generation_profile_is_normalized() {
+    let info = FBProfileInformation::new(PROFILE_V1).unwrap();
+    assert_eq!(info.profile.work_experiences[0].employer, "Initech");
+    assert_eq!(info.profile.languages, ["English", "Spanish"]);
+    assert_eq!(info.profile.intro_bio, "Amateur astronomer");
+
+    let ttl = convert_facebook_to_solid(PROFILE_V1, None).unwrap();
+    assert!(ttl.contains("Initech"));
+    assert!(ttl.contains("TPS Report Author"));
+}
+
+#[test]
+fn second_generation_profile_is_normalized() {
+    let info = FBProfileInformation::new(PROFILE).unwrap();
+    assert_eq!(info.profile.work_experiences[0].employer, "Acme Software Inc.");
+    assert_eq!(info.profile.languages, ["English language", "Spanish"]);
+    assert!(info.profile.intro_bio.starts_with("Software engineer"));
+}
+
+#[test]
+fn fields_in_the_other_generations_shape_are_rejected() {
+    let mixed = r#"{"profile_v2": {"languages": ["English"]}}"#;
+    let err = match FBProfileInformation::new(mixed) {
+        Err(err) => err,
+        Ok(_) => panic!("string languages are a first-generation layout"),
+    };
+    assert_eq!(err.location().unwrap().path, "profile_v2.languages[0]");
+}
+
+// ---------------------------------------------------------------------------
+// Provenance
+// ---------------------------------------------------------------------------
+
+fn with_provenance() -> String {
+    Converter::new()
+        .profile(PROFILE)
+        .friends(FRIENDS_DYI)
+        .provenance(ProvenanceOptions {
+            generated_at: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            file_names: vec!["profile_information.json".to_string()],
+        })
+        .convert()
+        .unwrap()
+        .serialize()
+}
+
+#[test]
+fn provenance_is_only_written_on_request() {
+    let ttl = convert_facebook_to_solid(PROFILE, None).unwrap();
+    assert!(!ttl.contains("prov:"));
+}
+
+#[test]
+fn provenance_names_the_software_and_time() {
+    let ttl = with_provenance();
+    assert!(ttl.contains("<> prov:wasGeneratedBy _:hatchlingConversion ."));
+    assert!(ttl.contains("<> prov:generatedAtTime \"2023-11-14T22:13:20Z\"^^xsd:dateTime ."));
+    assert!(ttl.contains("_:hatchlingSoftware a prov:SoftwareAgent ."));
+    assert!(ttl.contains(&format!(
+        "_:hatchlingSoftware schema:softwareVersion \"{}\" .",
+        env!("CARGO_PKG_VERSION")
+    )));
+}
+
+#[test]
+fn provenance_lists_each_source_with_hash_and_version() {
+    let ttl = with_provenance();
+    assert!(ttl.contains("<> prov:wasDerivedFrom _:hatchlingSource0 ."));
+    assert!(ttl.contains("_:hatchlingSource0 dcterms:title \"profile_information.json\" ."));
+    assert!(ttl.contains("_:hatchlingSource0 dcterms:hasVersion \"profile_v2\" ."));
+    assert!(ttl.contains("_:hatchlingSource1 dcterms:hasVersion \"friends_v2\" ."));
+    assert!(!ttl.contains("_:hatchlingSource1 dcterms:title"), "unnamed input");
+    let hash = hatchling::provenance::sha256_hex(PROFILE.as_bytes());
+    assert!(ttl.contains(&format!("_:hatchlingSource0Checksum spdx:checksumValue \"{}\" .", hash)));
+}
+
+#[test]
+fn provenance_carries_the_exports_own_dates() {
+    let ttl = with_provenance();
+    assert!(ttl.contains(
+        "<https://www.facebook.com/jane.doe.smith.1985> dcterms:created \"2007-02-08T00:00:00Z\"^^xsd:dateTime ."
+    ));
+    assert!(ttl.contains("_:hatchlingSource0 dcterms:modified "));
+}
+
+// ---------------------------------------------------------------------------
+// Explain
+// ---------------------------------------------------------------------------
+
+#[test]
+fn every_triple_is_explained() {
+    let profile = Converter::new().profile(PROFILE).friends(FRIENDS_SCRAPED).into_profile().unwrap();
+    let explained = explain::explain(&profile, &[(Input::Profile, "profile_information.json"), (Input::Friends(0), "friends.json")]);
+    assert_eq!(explained.len(), profile.graph().triples().len());
+    assert!(explained.iter().all(|e| e.file.is_some() || e.method == "Profile::new"));
+}
+
+#[test]
+fn explanations_point_into_the_profile() {
+    let profile = Converter::new().profile(PROFILE).into_profile().unwrap();
+    let explained = explain::explain(&profile, &[(Input::Profile, "profile_information.json")]);
+    let birth_place = explained
+        .iter()
+        .find(|e| e.triple.contains("schema:birthPlace"))
+        .unwrap();
+    assert_eq!(birth_place.pointer, "/profile_v2/hometown/name");
+    assert_eq!(birth_place.method, "Profile::add_birth_place");
+    let phone = explained.iter().find(|e| e.triple.contains("foaf:phone")).unwrap();
+    assert_eq!(phone.pointer, "/profile_v2/phone_numbers/0/phone_number");
+}
+
+#[test]
+fn explanations_point_into_each_friends_file() {
+    let profile = Converter::new().profile(PROFILE).friends(FRIENDS_DYI).into_profile().unwrap();
+    let explained = explain::explain(&profile, &[(Input::Profile, "profile_information.json"), (Input::Friends(0), "your_friends.json")]);
+    let knows: Vec<&explain::Explanation> =
+        explained.iter().filter(|e| e.triple.contains("foaf:knows")).collect();
+    assert_eq!(knows[2].file.as_deref(), Some("your_friends.json"));
+    assert_eq!(knows[2].pointer, "/friends_v2/2");
+
+    let scraped = Converter::new().profile(PROFILE).friends(FRIENDS_SCRAPED).into_profile().unwrap();
+    let explained = explain::explain(&scraped, &[]);
+    let account = explained
+        .iter()
+        .find(|e| e.method == "Profile::add_online_account" && e.pointer == "/1")
+        .unwrap();
+    assert!(account.triple.contains("foaf:account"));
+    assert_eq!(account.file.as_deref(), Some("friends file"));
+}
+
+#[test]
+fn annotated_turtle_is_still_valid_turtle() {
+    let profile = Converter::new().profile(PROFILE).friends(FRIENDS_DYI).into_profile().unwrap();
+    let annotated = explain::annotated_turtle(&profile, &[(Input::Profile, "profile_information.json")]);
+    assert!(annotated.contains("# profile_information.json /profile_v2/name/full_name via Profile::set_name\n"));
+    let plain = convert_facebook_to_solid(PROFILE, Some(FRIENDS_DYI)).unwrap();
+    assert!(diff_turtle(&plain, &annotated).unwrap().is_empty());
+}
+
+// ---------------------------------------------------------------------------
+// Timestamps
+// ---------------------------------------------------------------------------
+
+fn with_timestamps(timestamps: Timestamps) -> String {
+    Converter::new()
+        .profile(PROFILE)
+        .friends(FRIENDS_DYI)
+        .timestamps(timestamps)
+        .convert()
+        .unwrap()
+        .serialize()
+}
+
+#[test]
+fn timestamps_are_left_out_by_default() {
+    let ttl = with_timestamps(Timestamps::Off);
+    assert!(!ttl.contains("dcterms:created"));
+    assert!(!ttl.contains("rdf:Statement"));
+}
+
+#[test]
+fn friendships_and_places_are_annotated_with_rdf_star() {
+    let ttl = with_timestamps(Timestamps::Star);
+    assert!(ttl.contains(
+        "<< :me foaf:knows :Alice_Nguyen >> dcterms:created \"2007-09-25T00:00:00Z\"^^xsd:dateTime ."
+    ));
+    assert!(ttl.contains("<< :me schema:homeLocation _:Portland_Oregon >> dcterms:created "));
+    assert!(ttl.contains(":me foaf:knows :Alice_Nguyen ."), "annotated triples stay asserted");
+}
+
+#[test]
+fn reification_carries_the_same_timestamps() {
+    let reified = with_timestamps(Timestamps::Reification);
+    assert!(reified.contains("a rdf:Statement ."));
+    assert!(reified.contains("rdf:predicate foaf:knows ."));
+    assert!(!reified.contains("<<"));
+    let star = with_timestamps(Timestamps::Star);
+    assert!(diff_turtle(&star, &reified).unwrap().is_empty());
+}
+
+#[test]
+fn timestamps_of_private_facts_move_with_the_split() {
+    let graph = Converter::new()
+        .profile(PROFILE)
+        .friends(FRIENDS_DYI)
+        .timestamps(Timestamps::Reification)
+        .convert()
+        .unwrap()
+        .graph;
+    let (public, private) = access::split(&graph, &VisibilityPolicy::default(), "card", "private.ttl");
+    let public = public.serialize_turtle();
+    assert!(!public.contains("Alice_Nguyen"), "friend leaked through a statement");
+    assert!(public.contains("rdf:predicate schema:homeLocation ."));
+    assert!(private.serialize_turtle().contains("rdf:object :Alice_Nguyen ."));
+}
+
+// ---------------------------------------------------------------------------
+// Friendships
+// ---------------------------------------------------------------------------
+
+#[test]
+fn friendship_start_is_kept_as_a_dated_role() {
+    let ttl = convert_facebook_to_solid(PROFILE, Some(FRIENDS_DYI)).unwrap();
+    assert!(ttl.contains(":me schema:knows _:Alice_Nguyen_friendship ."));
+    assert!(ttl.contains("_:Alice_Nguyen_friendship a schema:Role ."));
+    assert!(ttl.contains("_:Alice_Nguyen_friendship schema:knows :Alice_Nguyen ."));
+    assert!(ttl.contains("_:Alice_Nguyen_friendship schema:startDate \"2007-09-25\"^^xsd:date ."));
+}
+
+#[test]
+fn friend_accounts_are_online_accounts() {
+    let ttl = convert_facebook_to_solid(PROFILE, Some(FRIENDS_SCRAPED)).unwrap();
+    assert!(ttl.contains(":Alice_Nguyen foaf:account _:Alice_Nguyen_facebook ."));
+    assert!(ttl.contains("_:Alice_Nguyen_facebook a foaf:OnlineAccount ."));
+    assert!(ttl.contains("_:Alice_Nguyen_facebook foaf:accountServiceHomepage <https://www.facebook.com/> ."));
+    assert!(ttl.contains("_:Alice_Nguyen_facebook foaf:accountName \"alice.nguyen.503\" ."));
+    assert!(ttl.contains("_:Alice_Nguyen_facebook foaf:page <https://www.facebook.com/alice.nguyen.503> ."));
+}
+
+#[test]
+fn dyi_and_scraped_friends_are_merged_by_name() {
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .friends(FRIENDS_DYI)
+        .friends(FRIENDS_SCRAPED)
+        .convert()
+        .unwrap();
+    let ttl = conversion.serialize();
+    assert_eq!(ttl.matches(":me foaf:knows :Alice_Nguyen .").count(), 1);
+    assert_eq!(ttl.matches(":Alice_Nguyen foaf:name").count(), 1);
+    assert!(ttl.contains("_:Alice_Nguyen_facebook foaf:accountName \"alice.nguyen.503\" ."));
+    assert!(ttl.contains("_:Alice_Nguyen_friendship schema:startDate \"2007-09-25\"^^xsd:date ."));
+}
+
+#[test]
+fn namesakes_in_one_file_are_not_merged() {
+    let friends = r#"[{"name": "Sam Lee", "target": "https://www.facebook.com/sam.1"},
+                      {"name": "Sam Lee", "target": "https://www.facebook.com/sam.2"}]"#;
+    let ttl = convert_facebook_to_solid(PROFILE, Some(friends)).unwrap();
+    assert!(ttl.contains(":me foaf:knows :Sam_Lee_sam_1 ."));
+    assert!(ttl.contains(":me foaf:knows :Sam_Lee_sam_2 ."));
+    assert!(ttl.contains("_:Sam_Lee_sam_1_facebook foaf:accountName \"sam.1\" ."));
+    assert!(ttl.contains("_:Sam_Lee_sam_2_facebook foaf:accountName \"sam.2\" ."));
+    assert_eq!(ttl.matches("foaf:accountName").count(), 2);
+}
+
+#[test]
+fn friendship_roles_stay_private_with_the_split() {
+    let graph = convert_facebook_to_graph(PROFILE, Some(FRIENDS_DYI)).unwrap();
+    let (public, _) = access::split(&graph, &VisibilityPolicy::default(), "card", "private.ttl");
+    let public = public.serialize_turtle();
+    assert!(!public.contains("schema:knows"));
+    assert!(!public.contains("startDate"));
+}
+
+// ---------------------------------------------------------------------------
+// Reconciling friends files
+// ---------------------------------------------------------------------------
+
+fn reconcile(dyi: &str, scraped: &str) -> hatchling::convert::Conversion {
+    Converter::new()
+        .profile(PROFILE)
+        .friends(dyi)
+        .friends(scraped)
+        .convert()
+        .unwrap()
+}
+
+#[test]
+fn names_are_matched_regardless_of_case_and_spacing() {
+    let dyi = r#"{"friends_v2": [{"name": "alice  nguyen", "timestamp": 1190678400}]}"#;
+    let scraped = r#"[{"name": " Alice Nguyen", "target": "https://www.facebook.com/alice.nguyen.503"}]"#;
+    let conversion = reconcile(dyi, scraped);
+    let ttl = conversion.serialize();
+    assert_eq!(ttl.matches("foaf:knows").count(), 1);
+    assert!(ttl.contains("_:alice_nguyen_facebook foaf:accountName \"alice.nguyen.503\" ."));
+    assert!(ttl.contains("_:alice_nguyen_friendship schema:startDate \"2007-09-25\"^^xsd:date ."));
+    assert!(conversion.report.warnings.is_empty());
+}
+
+#[test]
+fn ambiguous_names_are_reported_and_not_matched() {
+    let dyi = r#"{"friends_v2": [{"name": "Sam Lee", "timestamp": 1190678400},
+                                 {"name": "Sam Lee", "timestamp": 1201824000}]}"#;
+    let scraped = r#"[{"name": "Sam Lee", "target": "https://www.facebook.com/sam.1"}]"#;
+    let conversion = reconcile(dyi, scraped);
+    let warnings = &conversion.report.warnings;
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].message.contains("\"Sam Lee\""));
+    assert_eq!(warnings[0].field, "friends[2]");
+    let ttl = conversion.serialize();
+    assert_eq!(ttl.matches("schema:startDate").count(), 2);
+    assert!(ttl.contains("_:Sam_Lee_1_friendship schema:startDate \"2007-09-25\"^^xsd:date ."));
+    assert!(ttl.contains("_:Sam_Lee_2_friendship schema:startDate \"2008-02-01\"^^xsd:date ."));
+    assert!(ttl.contains("_:Sam_Lee_sam_1_facebook foaf:accountName \"sam.1\" ."));
+    assert_eq!(ttl.matches(":me foaf:knows :Sam_Lee").count(), 3);
+    assert_eq!(ttl.matches(" foaf:name \"Sam Lee\" .").count(), 3);
+}
+
+#[test]
+fn unmatched_friends_from_either_file_are_kept() {
+    let dyi = r#"{"friends_v2": [{"name": "Alice Nguyen", "timestamp": 1190678400},
+                                 {"name": "Bob Kowalski", "timestamp": 1201824000}]}"#;
+    let scraped = r#"[{"name": "Alice Nguyen", "target": "https://www.facebook.com/alice.nguyen.503"},
+                      {"name": "Carmen Reyes-Morales", "target": "https://www.facebook.com/carmen.reyes.morales"}]"#;
+    let ttl = reconcile(dyi, scraped).serialize();
+    assert_eq!(ttl.matches(":me foaf:knows").count(), 3);
+    assert!(ttl.contains("_:Bob_Kowalski_friendship schema:startDate"));
+    assert!(ttl.contains("\"carmen.reyes.morales\""));
+}
+
+#[test]
+fn the_same_friend_in_two_scraped_lists_is_kept_once() {
+    let ttl = reconcile(FRIENDS_SCRAPED, FRIENDS_SCRAPED).serialize();
+    assert_eq!(ttl.matches(":me foaf:knows :Alice_Nguyen .").count(), 1);
+    assert_eq!(ttl.matches("foaf:accountName \"alice.nguyen.503\"").count(), 1);
+}
+
+// ---------------------------------------------------------------------------
+// Connections
+// ---------------------------------------------------------------------------
+
+const FOLLOWERS: &str = r#"{"followers_v2": [{"name": "Alice Nguyen", "timestamp": 1190678400},
+                                              {"name": "Zoe Park", "timestamp": 1201824000}]}"#;
+const REMOVED: &str = r#"{"deleted_friends_v2": [{"name": "Mallory Smith", "timestamp": 1230768000}]}"#;
+
+fn with_connections(connections: &[&str]) -> hatchling::convert::Conversion {
+    connections
+        .iter()
+        .fold(Converter::new().profile(PROFILE).friends(FRIENDS_DYI), |c, json| c.connections(json))
+        .convert()
+        .unwrap()
+}
+
+#[test]
+fn each_connections_list_is_detected() {
+    for (json, list, key) in [
+        (FOLLOWERS, ConnectionList::Followers, "followers_v2"),
+        (r#"{"following_v3": []}"#, ConnectionList::Following, "following_v3"),
+        (r#"{"sent_requests_v2": []}"#, ConnectionList::SentRequests, "sent_requests_v2"),
+        (r#"{"received_requests_v2": []}"#, ConnectionList::ReceivedRequests, "received_requests_v2"),
+        (REMOVED, ConnectionList::Removed, "deleted_friends_v2"),
+    ] {
+        let connections = FBConnections::new(json).unwrap();
+        assert_eq!((connections.list, connections.key), (list, key));
+    }
+    assert!(FBConnections::new(FRIENDS_DYI).is_err());
+}
+
+#[test]
+fn followers_become_an_activity_streams_collection() {
+    let conversion = with_connections(&[FOLLOWERS]);
+    let ttl = conversion.serialize();
+    assert!(ttl.contains(":me as:followers :followers ."));
+    assert!(ttl.contains(":followers a as:Collection ."));
+    assert!(ttl.contains(":followers as:items :Alice_Nguyen ."));
+    assert!(ttl.contains(":Zoe_Park foaf:name \"Zoe Park\" ."));
+    // Alice is described once, as a friend
+    assert_eq!(ttl.matches(":Alice_Nguyen foaf:name").count(), 1);
+    assert_eq!(conversion.report.version.connections, vec!["followers_v2"]);
+}
+
+#[test]
+fn requests_and_removed_friends_never_go_into_the_card() {
+    let conversion = with_connections(&[FOLLOWERS, REMOVED]);
+    let (card, private) = (conversion.graph.serialize_turtle(), conversion.private.serialize_turtle());
+    assert!(card.contains(":me as:followers :followers ."));
+    assert!(!card.contains("as:streams"));
+    assert!(!card.contains("Mallory"));
+    assert!(card.contains(":me rdfs:seeAlso <private.ttl> ."));
+    assert!(private.contains("<card#me> as:streams :removed_friends ."));
+    assert!(private.contains("Mallory Smith"));
+}
+
+#[test]
+fn requests_and_removed_friends_stay_private_with_the_split() {
+    let conversion = with_connections(&[FOLLOWERS, r#"{"sent_requests_v2": [{"name": "Pat Doe"}]}"#]);
+    let (public, private) = access::split(&conversion.graph, &VisibilityPolicy::default(), "card", "private.ttl");
+    let public = public.serialize_turtle();
+    assert!(!public.contains("Pat Doe"));
+    assert_eq!(public.matches("rdfs:seeAlso").count(), 1);
+    assert!(private.serialize_turtle().contains("<card#me> foaf:knows <card#Alice_Nguyen> ."));
+    assert!(conversion.private.serialize_turtle().contains(":sent_friend_requests as:items :Pat_Doe ."));
+}
+
+#[test]
+fn connections_can_be_excluded() {
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .connections(FOLLOWERS)
+        .selection(Selection::all().exclude("connections").unwrap())
+        .convert()
+        .unwrap();
+    assert!(!conversion.serialize().contains("as:"));
+    assert_eq!(conversion.report.version.connections, vec!["followers_v2"]);
+}
+
+#[test]
+fn unreadable_connections_are_reported_against_their_file() {
+    let Err(err) = Converter::new()
+        .profile(PROFILE)
+        .connections(r#"{"followers_v2": [{"name": 5}]}"#)
+        .convert()
+    else {
+        panic!("conversion should fail");
+    };
+    assert_eq!(err.location().unwrap().input, Input::Connections(0));
+}
+
+// ---------------------------------------------------------------------------
+// Friend lists
+// ---------------------------------------------------------------------------
+
+const FRIEND_LISTS: &str = r#"{"friend_lists_v2": [
+    {"name": "Close Friends", "timestamp": 1190678400,
+     "members": [{"name": "Alice Nguyen"}, {"name": "Bob  Kowalski"}]},
+    {"name": "Book Club", "members": [{"name": "Zoe Park"}]}
+]}"#;
+
+#[test]
+fn friend_lists_become_vcard_groups_of_the_friend_nodes() {
+    let conversion = with_connections(&[FRIEND_LISTS]);
+    let ttl = conversion.serialize();
+    assert!(ttl.contains(":Close_Friends_group a vcard:Group ."));
+    assert!(ttl.contains(":Close_Friends_group vcard:fn \"Close Friends\" ."));
+    assert!(ttl.contains(":Close_Friends_group vcard:hasMember :Alice_Nguyen ."));
+    assert!(ttl.contains(":Close_Friends_group vcard:hasMember :Bob_Kowalski ."));
+    assert!(ttl.contains(":Book_Club_group vcard:hasMember :Zoe_Park ."));
+    assert_eq!(ttl.matches(":Alice_Nguyen foaf:name").count(), 1);
+    assert_eq!(conversion.report.version.connections, vec!["friend_lists_v2"]);
+}
+
+#[test]
+fn older_friend_lists_keep_their_members_under_data() {
+    let lists = FBFriendLists::new(r#"{"friend_lists": [{"name": "Family", "data": [{"name": "Ann"}]}]}"#).unwrap();
+    assert_eq!(lists.key, "friend_lists");
+    assert_eq!(lists.lists[0].members, vec!["Ann"]);
+}
+
+#[test]
+fn group_members_stay_private_with_the_split() {
+    let graph = with_connections(&[FRIEND_LISTS]).graph;
+    let (public, private) = access::split(&graph, &VisibilityPolicy::default(), "card", "private.ttl");
+    let (public, private) = (public.serialize_turtle(), private.serialize_turtle());
+    assert!(!public.contains("Zoe"));
+    assert!(!public.contains("vcard:hasMember"));
+    assert!(private.contains("<card#Book_Club_group> vcard:hasMember :Zoe_Park ."));
+}
+
+#[test]
+fn friend_lists_can_be_excluded_on_their_own() {
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .connections(FRIEND_LISTS)
+        .connections(FOLLOWERS)
+        .selection(Selection::all().exclude("connections.lists").unwrap())
+        .convert()
+        .unwrap();
+    let ttl = conversion.serialize();
+    assert!(!ttl.contains("vcard:Group"));
+    assert!(ttl.contains(":me as:followers :followers ."));
+}
+
+// ---------------------------------------------------------------------------
+// WebID map
+// ---------------------------------------------------------------------------
+
+fn with_webid_map(map: WebIdMap) -> hatchling::convert::Conversion {
+    Converter::new()
+        .profile(PROFILE)
+        .friends(FRIENDS_DYI)
+        .friends(FRIENDS_SCRAPED)
+        .webid_map(map)
+        .convert()
+        .unwrap()
+}
+
+#[test]
+fn mapped_friends_are_known_by_their_webid() {
+    let map = WebIdMap::from_csv(
+        "https://www.facebook.com/alice.nguyen.503,https://alice.example/profile/card#me\n\
+         Bob Kowalski,https://bob.example/card#me\n",
+    )
+    .unwrap();
+    let ttl = with_webid_map(map).serialize();
+    assert!(ttl.contains(":me foaf:knows <https://alice.example/profile/card#me> ."));
+    assert!(ttl.contains("<https://alice.example/profile/card#me> owl:sameAs <https://www.facebook.com/alice.nguyen.503> ."));
+    assert!(ttl.contains("<https://alice.example/profile/card#me> foaf:name \"Alice Nguyen\" ."));
+    assert!(ttl.contains(":me foaf:knows <https://bob.example/card#me> ."));
+    assert!(!ttl.contains(":Alice_Nguyen "));
+    assert!(ttl.contains(":me foaf:knows :Carmen_Reyes_Morales ."));
+}
+
+#[test]
+fn webid_map_can_be_turtle() {
+    let map = WebIdMap::from_turtle(
+        "@prefix owl: <http://www.w3.org/2002/07/owl#> .\n\
+         <https://alice.example/profile/card#me> owl:sameAs <https://www.facebook.com/alice.nguyen.503> .\n\
+         <https://www.facebook.com/profile.php?id=100012345678> owl:sameAs <https://jose.example/card#me> .\n",
+    )
+    .unwrap();
+    let ttl = with_webid_map(map).serialize();
+    assert!(ttl.contains(":me foaf:knows <https://alice.example/profile/card#me> ."));
+    assert!(ttl.contains(":me foaf:knows <https://jose.example/card#me> ."));
+}
+
+#[test]
+fn friends_without_a_webid_are_reported() {
+    let map = WebIdMap::from_csv("Alice Nguyen,https://alice.example/profile/card#me\n").unwrap();
+    let report = with_webid_map(map).report;
+    assert_eq!(report.without_webid.len(), 9);
+    assert!(report.without_webid.contains(&"Bob Kowalski".to_string()));
+    assert!(!report.without_webid.contains(&"Alice Nguyen".to_string()));
+    let without_map = Converter::new().profile(PROFILE).friends(FRIENDS_DYI).convert().unwrap();
+    assert!(without_map.report.without_webid.is_empty());
+}
+
+#[test]
+fn mapped_friends_stay_private_with_the_split() {
+    let map = WebIdMap::from_csv("Alice Nguyen,https://alice.example/profile/card#me\n").unwrap();
+    let graph = with_webid_map(map).graph;
+    let (public, private) = access::split(&graph, &VisibilityPolicy::default(), "card", "private.ttl");
+    let (public, private) = (public.serialize_turtle(), private.serialize_turtle());
+    assert!(!public.contains("alice.example"));
+    assert!(private.contains("<https://alice.example/profile/card#me> foaf:name \"Alice Nguyen\" ."));
+}
+
+// ---------------------------------------------------------------------------
+// Facebook URLs
+// ---------------------------------------------------------------------------
+
+#[test]
+fn friend_profile_urls_are_canonicalized() {
+    let friends = r#"[{"name": "Alice Nguyen", "target": "https://m.facebook.com/Alice.Nguyen.503/?ref=bookmarks"},
+                      {"name": "José María Vázquez", "target": "https://web.facebook.com/profile.php?id=100012345678&sk=about"}]"#;
+    let ttl = convert_facebook_to_solid(PROFILE, Some(friends)).unwrap();
+    assert!(ttl.contains("_:Alice_Nguyen_facebook foaf:page <https://www.facebook.com/alice.nguyen.503> ."));
+    assert!(ttl.contains("_:Alice_Nguyen_facebook foaf:accountName \"alice.nguyen.503\" ."));
+    assert!(ttl.contains("foaf:page <https://www.facebook.com/profile.php?id=100012345678> ."));
+    assert!(ttl.contains("foaf:accountName \"100012345678\" ."));
+}
+
+#[test]
+fn one_profile_under_two_url_shapes_is_one_friend() {
+    let friends = r#"[{"name": "Alice Nguyen", "target": "https://www.facebook.com/alice.nguyen.503"},
+                      {"name": "Alice Nguyen", "target": "https://m.facebook.com/alice.nguyen.503/"}]"#;
+    let ttl = convert_facebook_to_solid(PROFILE, Some(friends)).unwrap();
+    assert_eq!(ttl.matches("foaf:page <https://www.facebook.com/alice.nguyen.503>").count(), 1);
+
+    let other_shape = r#"[{"name": "Alice Nguyen", "target": "https://web.facebook.com/alice.nguyen.503?fref=pb"}]"#;
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .friends(FRIENDS_SCRAPED)
+        .friends(other_shape)
+        .convert()
+        .unwrap();
+    let ttl = conversion.serialize();
+    assert_eq!(ttl.matches(":me foaf:knows :Alice_Nguyen .").count(), 1);
+    assert_eq!(ttl.matches("_:Alice_Nguyen_facebook foaf:page").count(), 1);
+}
+
+#[test]
+fn own_account_and_profile_page_are_canonical() {
+    let profile = PROFILE
+        .replace("\"jane.doe.smith.1985\"", "\"Jane.Doe.Smith.1985\"")
+        .replace(
+            "https://www.facebook.com/jane.doe.smith.1985",
+            "https://m.facebook.com/Jane.Doe.Smith.1985/",
+        );
+    let ttl = convert_facebook_to_solid(&profile, None).unwrap();
+    assert!(ttl.contains(":me foaf:account <https://www.facebook.com/jane.doe.smith.1985> ."));
+    assert!(ttl.contains(":me foaf:page <https://www.facebook.com/jane.doe.smith.1985> ."));
+}
+
+// ---------------------------------------------------------------------------
+// Saved friends page
+// ---------------------------------------------------------------------------
+
+#[test]
+fn saved_friends_page_yields_the_snippets_friends() {
+    let friends = hatchling::friends_page::scrape(FRIENDS_PAGE);
+    let pairs: Vec<(&str, &str)> = friends.iter().map(|f| (f.name.as_str(), f.target.as_str())).collect();
+    assert_eq!(
+        pairs,
+        [
+            ("Alice Nguyen", "https://www.facebook.com/alice.nguyen.503"),
+            ("Bob Kowalski", "https://www.facebook.com/bob.kowalski.77"),
+            ("José María Vázquez", "https://www.facebook.com/profile.php?id=100012345678"),
+            ("François & Beaumont", "https://www.facebook.com/francois.beaumont"),
+        ]
+    );
+}
+
+#[test]
+fn saved_friends_page_converts_like_a_friends_file() {
+    let conversion = Converter::new().profile(PROFILE).friends(FRIENDS_PAGE).convert().unwrap();
+    assert_eq!(conversion.report.version.friends, [FriendsVersion::SavedPage]);
+    let ttl = conversion.serialize();
+    assert_eq!(ttl.matches(":me foaf:knows").count(), 4);
+    assert!(ttl.contains("_:Bob_Kowalski_facebook foaf:page <https://www.facebook.com/bob.kowalski.77> ."));
+    assert!(!ttl.contains("foaf:knows :Jane"));
+}
+
+#[test]
+fn html_without_friends_is_still_rejected() {
+    let page = "<!DOCTYPE html><html><body><a href=\"https://www.facebook.com/help\">Help</a></body></html>";
+    let Err(err) = Converter::new().profile(PROFILE).friends(page).convert() else {
+        panic!("a page without friends was accepted");
+    };
+    assert!(err.suggestion().unwrap().contains("/friends"));
+}
+
+// ---------------------------------------------------------------------------
+// Posts
+// ---------------------------------------------------------------------------
+
+fn with_posts(posts: &str) -> hatchling::convert::Conversion {
+    Converter::new()
+        .profile(PROFILE)
+        .friends(FRIENDS_SCRAPED)
+        .posts(posts)
+        .convert()
+        .unwrap()
+}
+
+#[test]
+fn posts_file_is_parsed() {
+    let posts = FBPosts::new(POSTS).unwrap().posts;
+    assert_eq!(posts.len(), 3);
+    assert_eq!(posts[0].text, "Happy new year from Powell's! 🎉");
+    assert_eq!(posts[0].places[0].name, "Powell's City of Books");
+    assert_eq!(posts[0].places[0].coordinate, Some((45.5231, -122.6813)));
+    assert_eq!(posts[0].tags, ["Alice Nguyen", "Marcus Webb"]);
+    assert_eq!(posts[1].urls, ["https://example.org/articles/decentralization"]);
+    assert_eq!(posts[2].tags, ["Bob Kowalski"]);
+}
+
+#[test]
+fn posts_are_written_per_year_outside_the_card() {
+    let conversion = with_posts(POSTS);
+    assert!(!conversion.serialize().contains("SocialMediaPosting"));
+    let paths: Vec<&str> = conversion.posts.iter().map(|r| r.path.as_str()).collect();
+    assert_eq!(paths, ["posts/2019", "posts/2020"]);
+
+    let ttl = conversion.posts[1].graph.serialize_turtle();
+    assert!(ttl.contains("<#1577880000> a schema:SocialMediaPosting ."));
+    assert!(ttl.contains("<#1577880000> a sioc:Post ."));
+    assert!(ttl.contains("<#1577880000> schema:author <../profile/card#me> ."));
+    assert!(ttl.contains("schema:dateCreated \"2020-01-01T12:00:00Z\"^^xsd:dateTime"));
+    assert!(ttl.contains("schema:text \"Happy new year from Powell's! 🎉\""));
+    assert!(ttl.contains("schema:mentions <../profile/card#Alice_Nguyen>"));
+    assert!(ttl.contains("schema:name \"Marcus Webb\""));
+    assert!(ttl.contains("schema:latitude \"45.5231\"^^xsd:double"));
+
+    let ttl = conversion.posts[0].graph.serialize_turtle();
+    assert!(ttl.contains("schema:sharedContent <https://example.org/articles/decentralization>"));
+    assert!(ttl.contains("<#1546300800_2>"));
+    assert!(ttl.contains("schema:mentions <../profile/card#Bob_Kowalski>"));
+}
+
+#[test]
+fn pod_keeps_posts_private_and_registers_them() {
+    let conversion = with_posts(POSTS);
+    let options = PodOptions {
+        access_control: Some(AccessControl::Wac),
+        posts: conversion.posts,
+        ..PodOptions::default()
+    };
+    let pod = build_pod_with(conversion.graph, &options);
+    let ttl = |path: &str| pod.iter().find(|r| r.path == path).unwrap().graph.serialize_turtle();
+    assert!(ttl("posts/").contains("ldp:BasicContainer"));
+    assert!(ttl("posts/2020").contains("SocialMediaPosting"));
+    let acl = ttl("posts/.acl");
+    assert!(acl.contains("<#owner> acl:default <./> ."));
+    assert!(!acl.contains("#public"));
+    let index = ttl("settings/privateTypeIndex.ttl");
+    assert!(index.contains("solid:forClass schema:SocialMediaPosting"));
+    assert!(index.contains("solid:instanceContainer <../posts/>"));
+    assert!(!ttl("settings/publicTypeIndex.ttl").contains("posts"));
+}
+
+#[test]
+fn posts_can_be_excluded_and_unreadable_ones_skipped() {
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .posts(POSTS)
+        .selection(Selection::all().exclude("posts").unwrap())
+        .convert()
+        .unwrap();
+    assert!(conversion.posts.is_empty());
+
+    let posts = r#"[{"timestamp": "yesterday"}, {"timestamp": 1546300800, "data": [{"post": "Kept"}]}]"#;
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .posts(posts)
+        .mode(Mode::Lenient)
+        .convert()
+        .unwrap();
+    assert_eq!(conversion.posts.len(), 1);
+    assert_eq!(conversion.report.warnings[0].field, "your_posts[0]");
+    let Err(err) = Converter::new().profile(PROFILE).posts(posts).convert() else {
+        panic!("an unreadable post was accepted");
+    };
+    assert_eq!(err.location().unwrap().input, Input::Posts(0));
+}
+
+// ---------------------------------------------------------------------------
+// Photos
+// ---------------------------------------------------------------------------
+
+fn with_album(album: &str) -> hatchling::convert::Conversion {
+    Converter::new().profile(PROFILE).album(album).convert().unwrap()
+}
+
+#[test]
+fn album_file_is_parsed_without_the_upload_ip() {
+    let album = FBAlbum::new(ALBUM).unwrap();
+    assert_eq!(album.name, "Profile Pictures");
+    assert_eq!(album.photos.len(), 3);
+    let photo = &album.photos[0];
+    assert_eq!(photo.description, "New year, new picture café edition");
+    assert_eq!(photo.taken_timestamp, Some(1614470400));
+    assert_eq!(photo.coordinate, Some((45.5231, -122.6813)));
+    assert!(photo.exif.contains(&("camera_model".to_string(), "iPhone 11".to_string())));
+    assert!(photo.exif.contains(&("iso_speed".to_string(), "32".to_string())));
+    // Older exports put the fields straight into photo_metadata
+    assert_eq!(album.photos[1].exif, [("orientation".to_string(), "1".to_string())]);
+    assert!(!format!("{:?}", album).contains("upload_ip"));
+}
+
+#[test]
+fn albums_become_image_galleries_and_the_card_gets_the_profile_picture() {
+    let conversion = with_album(ALBUM);
+    let ttl = conversion.serialize();
+    assert!(ttl.contains("foaf:img <../photos/profile_pictures/10158_2021_n.jpg>"));
+    assert!(ttl.contains("schema:image <../photos/profile_pictures/10158_2021_n.jpg>"));
+
+    let album = &conversion.photos.albums[0];
+    assert_eq!(album.path, "photos/profile_pictures");
+    let ttl = album.graph.serialize_turtle();
+    assert!(ttl.contains("<#album> a schema:ImageGallery ."));
+    assert!(ttl.contains("<#album> schema:hasPart <#photo1> ."));
+    assert!(ttl.contains("<#photo1> a schema:ImageObject ."));
+    assert!(ttl.contains("<#photo1> schema:contentUrl <profile_pictures/10158_2021_n.jpg> ."));
+    assert!(ttl.contains("<#photo1> schema:uploadDate \"2021-03-01T00:00:00Z\"^^xsd:dateTime ."));
+    assert!(ttl.contains("<#photo1> schema:dateCreated \"2021-02-28T00:00:00Z\"^^xsd:dateTime ."));
+    assert!(ttl.contains("_:photo1_camera_make schema:value \"Apple\" ."));
+    assert!(!ttl.contains("203.0.113.7"));
+    assert!(!ttl.contains("schema:latitude"));
+
+    let conversion = Converter::new()
+        .profile(PROFILE)
+        .album(ALBUM)
+        .selection(Selection::all().exclude("photos.profile_picture").unwrap())
+        .convert()
+        .unwrap();
+    assert!(!conversion.serialize().contains("foaf:img"));
+    assert_eq!(conversion.photos.profile_picture, None);
+    assert_eq!(conversion.photos.albums.len(), 1);
+}
+
+#[test]
+fn photo_locations_are_only_kept_on_request() {
+    let conversion = Converter::new().profile(PROFILE).album(ALBUM).photo_locations(true).convert().unwrap();
+    let ttl = conversion.photos.albums[0].graph.serialize_turtle();
+    assert!(ttl.contains("<#photo1> schema:locationCreated _:photo1_place ."));
+    assert!(ttl.contains("_:photo1_geo schema:latitude \"45.5231\"^^xsd:double ."));
+}
+
+#[test]
+fn pod_keeps_photos_private_except_the_profile_picture() {
+    let conversion = with_album(ALBUM);
+    let options = PodOptions {
+        access_control: Some(AccessControl::Wac),
+        photos: conversion.photos,
+        ..PodOptions::default()
+    };
+    let pod = build_pod_with(conversion.graph, &options);
+    let ttl = |path: &str| pod.iter().find(|r| r.path == path).unwrap().graph.serialize_turtle();
+    assert!(ttl("photos/").contains("ldp:BasicContainer"));
+    assert!(!ttl("photos/.acl").contains("#public"));
+    let picture_acl = ttl("photos/profile_pictures/10158_2021_n.jpg.acl");
+    assert!(picture_acl.contains("<#public> acl:accessTo <10158_2021_n.jpg> ."));
+    assert!(picture_acl.contains("<#owner> acl:agent <../../profile/card#me> ."));
+    assert!(ttl("settings/privateTypeIndex.ttl").contains("solid:forClass schema:ImageGallery"));
+}
+
+#[test]
+fn media_files_are_copied_into_the_pod() {
+    let dir = temp_dir("photos");
+    let conversion = with_album(ALBUM);
+    let missing = copy_media(&dir, &conversion.photos, &[std::path::Path::new(ALBUM_PATH)]).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("photos/profile_pictures/10158_2021_n.jpg")).unwrap(),
+        "not really a jpeg (2021)\n"
+    );
+    assert!(dir.join("photos/profile_pictures/10153_2016_n.jpg").is_file());
+    let missing: Vec<&str> = missing.iter().map(|file| file.uri.as_str()).collect();
+    assert_eq!(missing, ["photos_and_videos/ProfilePictures_a1b2c3/10150_2010_n.jpg"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn media_outside_the_export_is_not_copied() {
+    let dir = temp_dir("escaping-photos");
+    let outside = fs::canonicalize("tests/fixtures/profile_information.json").unwrap();
+    let media = |path: &str, uri: &str| MediaFile {
+        path: path.to_string(),
+        uri: uri.to_string(),
+    };
+    let photos = Photos {
+        media: vec![
+            media("photos/a/absolute.jpg", outside.to_str().unwrap()),
+            media("photos/a/climbing.jpg", "photos_and_videos/../../profile_information.json"),
+            media("photos/a/../../../escaped.jpg", "photos_and_videos/ProfilePictures_a1b2c3/10158_2021_n.jpg"),
+        ],
+        ..Photos::default()
+    };
+    let missing = copy_media(&dir, &photos, &[std::path::Path::new(ALBUM_PATH)]).unwrap();
+    assert_eq!(missing.len(), 3);
+    assert!(!dir.exists());
+}
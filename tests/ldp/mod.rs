@@ -0,0 +1,209 @@
+//! A minimal in-memory LDP server used as a stand-in for a Solid pod.
+//! It understands just enough HTTP to exercise `hatchling::push`: HEAD, GET,
+//! PUT and PATCH with ETag preconditions, and it refuses to create a resource
+//! whose parent container does not exist yet.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Clone, Debug)]
+pub struct Resource {
+    pub body: String,
+    pub content_type: String,
+    pub version: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+#[derive(Default)]
+struct State {
+    resources: HashMap<String, Resource>,
+    requests: Vec<Request>,
+    /// Paths whose version is bumped right after the next HEAD, simulating a
+    /// concurrent edit by another app.
+    bump_after_head: Vec<String>,
+}
+
+pub struct LdpServer {
+    pub base: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl LdpServer {
+    pub fn start() -> LdpServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+        state.lock().unwrap().resources.insert(
+            "/".to_string(),
+            Resource {
+                body: String::new(),
+                content_type: "text/turtle".to_string(),
+                version: 1,
+            },
+        );
+        let shared = Arc::clone(&state);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                handle(stream, &shared);
+            }
+        });
+        LdpServer { base, state }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base, path)
+    }
+
+    pub fn resource(&self, path: &str) -> Option<Resource> {
+        self.state.lock().unwrap().resources.get(path).cloned()
+    }
+
+    pub fn insert(&self, path: &str, body: &str) {
+        self.state.lock().unwrap().resources.insert(
+            path.to_string(),
+            Resource {
+                body: body.to_string(),
+                content_type: "text/turtle".to_string(),
+                version: 1,
+            },
+        );
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn bump_after_head(&self, path: &str) {
+        self.state.lock().unwrap().bump_after_head.push(path.to_string());
+    }
+}
+
+fn etag(resource: &Resource) -> String {
+    format!("\"v{}\"", resource.version)
+}
+
+fn parent(path: &str) -> String {
+    let trimmed = path.trim_end_matches('/');
+    match trimmed.rfind('/') {
+        Some(i) => trimmed[..=i].to_string(),
+        None => "/".to_string(),
+    }
+}
+
+fn handle(stream: TcpStream, state: &Arc<Mutex<State>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("/").to_string();
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+    let request = Request {
+        method,
+        path,
+        headers,
+        body: String::from_utf8(body).unwrap(),
+    };
+
+    let (status, response_headers, response_body) = respond(&request, state);
+    state.lock().unwrap().requests.push(request);
+
+    let mut out = stream;
+    let mut response = format!("HTTP/1.1 {}\r\nConnection: close\r\n", status);
+    for (name, value) in response_headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str(&format!("Content-Length: {}\r\n\r\n", response_body.len()));
+    response.push_str(&response_body);
+    let _ = out.write_all(response.as_bytes());
+}
+
+fn respond(request: &Request, state: &Arc<Mutex<State>>) -> (&'static str, Vec<(String, String)>, String) {
+    let mut state = state.lock().unwrap();
+    let current = state.resources.get(&request.path).cloned();
+    let precondition_failed = match (request.headers.get("if-match"), &current) {
+        (Some(expected), Some(resource)) => *expected != etag(resource),
+        (Some(_), None) => true,
+        _ => false,
+    } || (request.headers.get("if-none-match").map(String::as_str) == Some("*")
+        && current.is_some());
+
+    match request.method.as_str() {
+        "HEAD" | "GET" => match current {
+            Some(resource) => {
+                let headers = vec![
+                    ("ETag".to_string(), etag(&resource)),
+                    ("Content-Type".to_string(), resource.content_type.clone()),
+                ];
+                if request.method == "HEAD" {
+                    if let Some(i) = state.bump_after_head.iter().position(|p| *p == request.path) {
+                        state.bump_after_head.remove(i);
+                        state.resources.get_mut(&request.path).unwrap().version += 1;
+                    }
+                    ("200 OK", headers, String::new())
+                } else {
+                    ("200 OK", headers, resource.body)
+                }
+            }
+            None => ("404 Not Found", Vec::new(), String::new()),
+        },
+        "PUT" | "PATCH" => {
+            if precondition_failed {
+                return ("412 Precondition Failed", Vec::new(), String::new());
+            }
+            if current.is_none() && !state.resources.contains_key(&parent(&request.path)) {
+                return ("409 Conflict", Vec::new(), "parent container missing".to_string());
+            }
+            let version = current.as_ref().map(|r| r.version + 1).unwrap_or(1);
+            let (body, content_type) = if request.method == "PATCH" {
+                // Patches are recorded rather than applied; tests inspect them.
+                current
+                    .map(|r| (r.body, r.content_type))
+                    .unwrap_or_else(|| (String::new(), "text/turtle".to_string()))
+            } else {
+                let content_type = request.headers.get("content-type").cloned();
+                (request.body.clone(), content_type.unwrap_or_default())
+            };
+            let status = if version == 1 { "201 Created" } else { "205 Reset Content" };
+            state.resources.insert(
+                request.path.clone(),
+                Resource {
+                    body,
+                    content_type,
+                    version,
+                },
+            );
+            (status, Vec::new(), String::new())
+        }
+        _ => ("405 Method Not Allowed", Vec::new(), String::new()),
+    }
+}
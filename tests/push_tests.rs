@@ -0,0 +1,179 @@
+mod ldp;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hatchling::access::AccessControl;
+use hatchling::patch::PatchFormat;
+use hatchling::pod::{build_pod_with, read_pod, write_pod, PodOptions};
+use hatchling::{convert_facebook_to_graph, convert_facebook_to_solid};
+use hatchling::push::{Anonymous, BearerToken, DpopToken, PodClient, PushError, PushOutcome};
+use ldp::LdpServer;
+use std::fs;
+
+const PROFILE: &str = include_str!("fixtures/profile_information.json");
+const DPOP_KEY: &str = include_str!("fixtures/dpop_key.pem");
+
+// ---------------------------------------------------------------------------
+// PUT
+// ---------------------------------------------------------------------------
+
+#[test]
+fn put_creates_missing_containers_then_the_resource() {
+    let pod = LdpServer::start();
+    let ttl = convert_facebook_to_solid(PROFILE, None).unwrap();
+    let client = PodClient::new(Anonymous);
+
+    let outcome = client
+        .put(&pod.url("/jane/profile/card"), &ttl, "text/turtle")
+        .unwrap();
+
+    assert_eq!(outcome, PushOutcome::Created);
+    assert!(pod.resource("/jane/").is_some(), "outer container missing");
+    assert!(pod.resource("/jane/profile/").is_some(), "profile container missing");
+    let card = pod.resource("/jane/profile/card").unwrap();
+    assert_eq!(card.body, ttl);
+    assert_eq!(card.content_type, "text/turtle");
+
+    let container_put = pod
+        .requests()
+        .into_iter()
+        .find(|r| r.method == "PUT" && r.path == "/jane/")
+        .unwrap();
+    assert_eq!(
+        container_put.headers["link"],
+        "<http://www.w3.org/ns/ldp#BasicContainer>; rel=\"type\""
+    );
+}
+
+#[test]
+fn put_over_existing_resource_sends_if_match() {
+    let pod = LdpServer::start();
+    pod.insert("/card", "<#me> <http://xmlns.com/foaf/0.1/name> \"Old\" .");
+    let client = PodClient::new(Anonymous);
+
+    let outcome = client.put(&pod.url("/card"), "# new", "text/turtle").unwrap();
+
+    assert_eq!(outcome, PushOutcome::Updated);
+    let put = pod.requests().into_iter().find(|r| r.method == "PUT").unwrap();
+    assert_eq!(put.headers["if-match"], "\"v1\"");
+    assert_eq!(pod.resource("/card").unwrap().body, "# new");
+}
+
+#[test]
+fn put_refuses_to_overwrite_a_concurrent_edit() {
+    let pod = LdpServer::start();
+    pod.insert("/card", "# edited by another app");
+    pod.bump_after_head("/card");
+    let client = PodClient::new(Anonymous);
+
+    let err = client.put(&pod.url("/card"), "# new", "text/turtle").unwrap_err();
+
+    assert!(matches!(err, PushError::Conflict { .. }), "got {err:?}");
+    assert_eq!(pod.resource("/card").unwrap().body, "# edited by another app");
+}
+
+// ---------------------------------------------------------------------------
+// Pod layout
+// ---------------------------------------------------------------------------
+
+#[test]
+fn pod_layout_is_pushed_below_the_base_url() {
+    let dir = std::env::temp_dir().join(format!("hatchling-push-pod-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let options = PodOptions {
+        access_control: Some(AccessControl::Wac),
+        ..PodOptions::default()
+    };
+    write_pod(&dir, &build_pod_with(convert_facebook_to_graph(PROFILE, None).unwrap(), &options)).unwrap();
+    fs::create_dir_all(dir.join("photos/album")).unwrap();
+    fs::write(dir.join("photos/album/1.jpg"), "jpeg").unwrap();
+    let pod = LdpServer::start();
+    let client = PodClient::new(Anonymous);
+
+    let outcomes = client.put_pod(&pod.url("/jane"), &read_pod(&dir).unwrap()).unwrap();
+
+    let card = pod.resource("/jane/profile/card").unwrap();
+    assert!(card.body.contains("Jane Doe-Smith"));
+    assert_eq!(card.content_type, "text/turtle");
+    assert!(pod.resource("/jane/profile/card.acl").is_some());
+    assert!(pod.resource("/jane/settings/publicTypeIndex.ttl").is_some());
+    assert!(pod.resource("/jane/inbox/").is_some(), "empty inbox container missing");
+    assert_eq!(pod.resource("/jane/photos/album/1.jpg").unwrap().content_type, "image/jpeg");
+    assert!(pod.resource("/jane/.meta").is_none() && pod.resource("/jane/inbox/.meta").is_none());
+    assert!(outcomes.contains(&(pod.url("/jane/profile/card"), PushOutcome::Created)));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// ---------------------------------------------------------------------------
+// PATCH
+// ---------------------------------------------------------------------------
+
+#[test]
+fn patch_sends_patch_document_with_content_type() {
+    let pod = LdpServer::start();
+    pod.insert("/card", "");
+    let client = PodClient::new(Anonymous);
+
+    let outcome = client
+        .patch(&pod.url("/card"), "_:p a solid:InsertDeletePatch .", PatchFormat::N3)
+        .unwrap();
+
+    assert_eq!(outcome, PushOutcome::Patched);
+    let patch = pod.requests().into_iter().find(|r| r.method == "PATCH").unwrap();
+    assert_eq!(patch.headers["content-type"], "text/n3");
+    assert_eq!(patch.headers["if-match"], "\"v1\"");
+    assert_eq!(patch.body, "_:p a solid:InsertDeletePatch .");
+}
+
+// ---------------------------------------------------------------------------
+// Authentication
+// ---------------------------------------------------------------------------
+
+#[test]
+fn bearer_token_is_sent_with_every_request() {
+    let pod = LdpServer::start();
+    let client = PodClient::new(BearerToken("secret".to_string()));
+
+    client.put(&pod.url("/profile/card"), "", "text/turtle").unwrap();
+
+    let requests = pod.requests();
+    assert!(!requests.is_empty());
+    for r in requests {
+        assert_eq!(r.headers["authorization"], "Bearer secret", "{} {}", r.method, r.path);
+    }
+}
+
+fn dpop_claims(request: &ldp::Request) -> serde_json::Value {
+    let payload = request.headers["dpop"].split('.').nth(1).unwrap();
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap()
+}
+
+#[test]
+fn dpop_token_sends_a_fresh_proof_with_every_request() {
+    let pod = LdpServer::start();
+    let client = PodClient::new(DpopToken::from_pem("token", DPOP_KEY).unwrap());
+
+    client.put(&pod.url("/card"), "", "text/turtle").unwrap();
+
+    let requests = pod.requests();
+    let head = requests.iter().find(|r| r.method == "HEAD").unwrap();
+    let put = requests.iter().find(|r| r.method == "PUT").unwrap();
+    assert_eq!(put.headers["authorization"], "DPoP token");
+    let (head, put) = (dpop_claims(head), dpop_claims(put));
+    assert_eq!((head["htm"].as_str(), put["htm"].as_str()), (Some("HEAD"), Some("PUT")));
+    assert_eq!(put["htu"].as_str(), Some(pod.url("/card").as_str()));
+    assert_ne!(head["jti"], put["jti"]);
+}
+
+#[test]
+fn error_status_is_reported() {
+    let pod = LdpServer::start();
+    let client = PodClient::new(Anonymous);
+
+    let err = client
+        .patch(&pod.url("/missing/card"), "", PatchFormat::N3)
+        .unwrap_err();
+
+    assert!(matches!(err, PushError::Status { status: 409, .. }), "got {err:?}");
+    assert!(err.to_string().contains("parent container missing"));
+}
@@ -0,0 +1,103 @@
+use hatchling::convert_facebook_to_solid;
+use hatchling::serve::{Server, Site};
+use hatchling::turtle;
+use std::thread;
+
+const PROFILE: &str = include_str!("fixtures/profile_information.json");
+
+/// Starts a preview server for the fixture profile and returns its base URL.
+fn start() -> String {
+    let ttl = convert_facebook_to_solid(PROFILE, None).unwrap();
+    let mut site = Site::new();
+    site.add("/profile/card", turtle::parse(&ttl).unwrap());
+    let server = Server::bind("127.0.0.1:0", site).unwrap();
+    let base = format!("http://{}", server.local_addr().unwrap());
+    thread::spawn(move || server.run());
+    base
+}
+
+fn get(url: &str, accept: &str) -> ureq::Response {
+    ureq::get(url).set("Accept", accept).call().unwrap()
+}
+
+#[test]
+fn serves_turtle_by_default() {
+    let base = start();
+    let response = ureq::get(&format!("{base}/profile/card")).call().unwrap();
+    assert_eq!(response.header("Content-Type"), Some("text/turtle"));
+    assert_eq!(
+        response.header("Link"),
+        Some("<http://www.w3.org/ns/ldp#Resource>; rel=\"type\"")
+    );
+    assert!(response.header("ETag").is_some());
+    let body = response.into_string().unwrap();
+    assert!(body.contains("foaf:name \"Jane Doe-Smith\""));
+}
+
+#[test]
+fn served_turtle_round_trips() {
+    let base = start();
+    let body = get(&format!("{base}/profile/card"), "text/turtle")
+        .into_string()
+        .unwrap();
+    let original = convert_facebook_to_solid(PROFILE, None).unwrap();
+    assert!(hatchling::diff_turtle(&original, &body).unwrap().is_empty());
+}
+
+#[test]
+fn serves_json_ld_when_requested() {
+    let base = start();
+    let response = get(&format!("{base}/profile/card"), "application/ld+json");
+    assert_eq!(response.header("Content-Type"), Some("application/ld+json"));
+    let doc: serde_json::Value = serde_json::from_str(&response.into_string().unwrap()).unwrap();
+    assert_eq!(doc["@context"]["foaf"], "http://xmlns.com/foaf/0.1/");
+    let me = doc["@graph"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|n| n["@id"] == "#me")
+        .unwrap();
+    assert_eq!(me["foaf:name"][0]["@value"], "Jane Doe-Smith");
+    assert!(me["@type"].as_array().unwrap().contains(&"foaf:Person".into()));
+}
+
+#[test]
+fn serves_html_to_browsers() {
+    let base = start();
+    let response = get(
+        &format!("{base}/profile/card"),
+        "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+    );
+    assert_eq!(response.header("Content-Type"), Some("text/html; charset=utf-8"));
+    let body = response.into_string().unwrap();
+    assert!(body.contains("<title>Jane Doe-Smith</title>"));
+    assert!(body.contains("<h2 id=\"me\">:me</h2>"));
+}
+
+#[test]
+fn lists_containers_above_the_card() {
+    let base = start();
+    let response = get(&format!("{base}/profile/"), "text/turtle");
+    let links: Vec<&str> = response.all("Link");
+    assert!(links.contains(&"<http://www.w3.org/ns/ldp#BasicContainer>; rel=\"type\""));
+    assert!(response.into_string().unwrap().contains("<> ldp:contains <card> ."));
+
+    let root = get(&format!("{base}/"), "text/turtle");
+    assert!(root
+        .all("Link")
+        .contains(&"<http://www.w3.org/ns/pim/space#Storage>; rel=\"type\""));
+    assert!(root.into_string().unwrap().contains("<> ldp:contains <profile/> ."));
+}
+
+#[test]
+fn unknown_paths_and_types_are_rejected() {
+    let base = start();
+    match ureq::get(&format!("{base}/nope")).call() {
+        Err(ureq::Error::Status(404, _)) => {}
+        other => panic!("expected 404, got {other:?}"),
+    }
+    match ureq::get(&format!("{base}/profile/card")).set("Accept", "image/png").call() {
+        Err(ureq::Error::Status(406, _)) => {}
+        other => panic!("expected 406, got {other:?}"),
+    }
+}